@@ -0,0 +1,55 @@
+//! Compares packed ternary dot products with the naive floating-point code
+//! they replace. Run with `cargo bench --bench dot_product`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use balanced_ternary::random::TritRng;
+use balanced_ternary::trit::Trit;
+use balanced_ternary::weights::{TernaryMatrix, TernaryVector};
+
+const ROWS: usize = 256;
+const COLUMNS: usize = 1024;
+const ITERATIONS: u32 = 200;
+
+/// Time a matrix-vector product, averaged over several runs.
+fn time<T>(name: &str, mut run: impl FnMut() -> T) -> Duration {
+    black_box(run());
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(run());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!("{:<28} {:>10.1?}", name, elapsed);
+    elapsed
+}
+
+fn main() {
+    let mut rng = TritRng::new(1);
+    let weights: Vec<Vec<Trit>> = (0..ROWS).map(|_| rng.by_ref().take(COLUMNS).collect()).collect();
+    let float_weights: Vec<Vec<f32>> = weights.iter()
+        .map(|row| row.iter().map(|trit| f32::from(i8::from(*trit))).collect())
+        .collect();
+    let matrix = TernaryMatrix::from_rows(weights.iter().map(|row| TernaryVector::from_trits(row)).collect())
+        .expect("Rows have the same length");
+
+    let floats: Vec<f32> = (0..COLUMNS).map(|idx| (idx % 17) as f32 * 0.1 - 0.8).collect();
+    let bytes: Vec<i8> = (0..COLUMNS).map(|idx| (idx % 255) as u8 as i8).collect();
+    let ternary: TernaryVector = rng.by_ref().take(COLUMNS).collect();
+    let ternary_floats: Vec<f32> = ternary.iter().map(|trit| f32::from(i8::from(trit))).collect();
+
+    println!("{} x {} matrix-vector products", ROWS, COLUMNS);
+    let naive = |activations: &[f32]| -> Vec<f32> {
+        float_weights.iter()
+            .map(|row| row.iter().zip(activations).map(|(weight, activation)| weight * activation).sum())
+            .collect()
+    };
+    let baseline = time("naive f32", || naive(black_box(&floats)));
+    let packed = time("packed f32", || matrix.mul_f32(black_box(&floats)));
+    time("packed i8", || matrix.mul_i8(black_box(&bytes)));
+    let naive_ternary = time("naive f32, ternary input", || naive(black_box(&ternary_floats)));
+    let popcount = time("packed ternary (popcount)", || matrix.mul_ternary(black_box(&ternary)));
+
+    println!("packed f32 speedup: {:.1}x", baseline.as_secs_f64() / packed.as_secs_f64());
+    println!("popcount speedup: {:.1}x", naive_ternary.as_secs_f64() / popcount.as_secs_f64());
+}
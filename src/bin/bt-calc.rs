@@ -0,0 +1,311 @@
+//! An interactive balanced ternary calculator.
+//!
+//! Each line is either an expression, an assignment like `x = 3 * +-0`, or a
+//! command starting with ':'. Expressions use the grammar of the library's
+//! `expr` module, so numbers can be written in decimal (`42`), as bare trit
+//! strings (`+-0`) or with a `0t` prefix (`0t+-0`). Results are shown in both
+//! notations and kept in a numbered history, where `_` refers to the latest
+//! result and `_3` to the third.
+//!
+//! Run with `--width N` to choose the number of trits used for arithmetic.
+
+mod common;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+
+use balanced_ternary::expr::{self, EvalError, EvalErrorKind, ParseError};
+use balanced_ternary::number::Number;
+
+use common::{with_width, WIDTHS};
+
+const DEFAULT_WIDTH: usize = 27;
+
+const HELP: &str = "\
+Enter an expression to evaluate it, or assign it with `name = expression`.
+  Numbers:    42 (decimal), +-0 or 0t+-0 (balanced ternary)
+  Operators:  + - * / % << >> & | ^ and parentheses, with unary - and !
+  Functions:  pow(x, n) abs(x) sign(x) min(a, b) max(a, b)
+  History:    _ is the latest result, _1, _2, ... are earlier ones
+Commands:
+  :width [N]  show or set the number of trits
+  :vars       list variables
+  :history    list previous results
+  :help       show this message
+  :quit       exit";
+
+/// Errors reported to the user in place of a result.
+#[derive(Debug, Eq, PartialEq)]
+enum CalcError {
+    Syntax(ParseError),
+    Evaluation(EvalError),
+    /// A history entry that was dropped by narrowing the width
+    Dropped(String)
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalcError::Syntax(error) => write!(f, "syntax error: {}", error.kind),
+            CalcError::Evaluation(EvalError { kind: EvalErrorKind::Overflow, .. }) =>
+                write!(f, "overflow: result does not fit in the current width"),
+            CalcError::Evaluation(error) => write!(f, "{}", error.kind),
+            CalcError::Dropped(name) => write!(f, "'{}' no longer fits in the current width", name)
+        }
+    }
+}
+
+/// The variables and history of a session. Values are kept as integers so they
+/// survive changes of width, and history entries that no longer fit are kept
+/// as `None` so later entries keep their numbers.
+struct Session {
+    width: usize,
+    variables: HashMap<String, i128>,
+    history: Vec<Option<i128>>
+}
+
+impl Session {
+    fn new(width: usize) -> Self {
+        Session { width, variables: HashMap::new(), history: Vec::new() }
+    }
+
+    /// Handle a line of input, returning the text to show, if any, or `None`
+    /// to quit.
+    fn handle(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+        if line.is_empty() {
+            return Some(String::new());
+        }
+
+        let (name, expression) = match line.split_once('=') {
+            Some((name, expression)) if is_identifier(name.trim()) => (Some(name.trim()), expression),
+            _ => (None, line)
+        };
+        if name.is_some_and(|name| name.starts_with('_')) {
+            return Some("names starting with '_' are reserved for history".to_string());
+        }
+
+        match with_width!(self.width, evaluate(expression, self)) {
+            Ok(value) => {
+                self.history.push(Some(value));
+                if let Some(name) = name {
+                    self.variables.insert(name.to_string(), value);
+                }
+                Some(format!("_{} = {}", self.history.len(), with_width!(self.width, describe(value))))
+            },
+            Err(error) => Some(format!("error: {}", error))
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Option<String> {
+        let mut words = command.split_whitespace();
+        let output = match (words.next(), words.next()) {
+            (Some("q" | "quit"), None) => return None,
+            (Some("help"), None) => HELP.to_string(),
+            (Some("width"), None) => format!("width is {} trits", self.width),
+            (Some("width"), Some(width)) => match width.parse() {
+                Ok(width) if WIDTHS.contains(&width) => self.set_width(width),
+                _ => format!("supported widths are {:?}", WIDTHS)
+            },
+            (Some("vars"), None) => {
+                let mut names: Vec<_> = self.variables.keys().collect();
+                names.sort();
+                names.iter()
+                    .map(|name| format!("{} = {}", name, with_width!(self.width, describe(self.variables[*name]))))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+            (Some("history"), None) => self.history.iter().enumerate()
+                .map(|(idx, value)| match value {
+                    Some(value) => format!("_{} = {}", idx + 1, with_width!(self.width, describe(*value))),
+                    None => format!("_{} no longer fits", idx + 1)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => format!("unknown command ':{}', try :help", command)
+        };
+        Some(output)
+    }
+
+    /// The history entry named like `_` or `_3`, which is `Some(None)` if it
+    /// was dropped by narrowing the width.
+    fn history_entry(&self, name: &str) -> Option<Option<i128>> {
+        match name.strip_prefix('_')? {
+            "" => self.history.last().copied(),
+            index => index.parse::<usize>().ok()?
+                .checked_sub(1)
+                .and_then(|index| self.history.get(index))
+                .copied()
+        }
+    }
+
+    /// Change the width, forgetting any variables and history entries that no
+    /// longer fit.
+    fn set_width(&mut self, width: usize) -> String {
+        let fits = |value: &i128| with_width!(width, fits(*value));
+        let mut dropped = self.variables.len();
+
+        self.variables.retain(|_, value| fits(value));
+        dropped -= self.variables.len();
+        for entry in &mut self.history {
+            if entry.is_some_and(|value| !fits(&value)) {
+                *entry = None;
+                dropped += 1;
+            }
+        }
+        self.width = width;
+
+        match dropped {
+            0 => format!("width is now {} trits", width),
+            _ => format!("width is now {} trits, {} values that no longer fit were dropped", width, dropped)
+        }
+    }
+}
+
+fn fits<const N: usize>(value: i128) -> bool {
+    Number::<N>::try_from(value).is_ok()
+}
+
+/// Show a value in balanced ternary and decimal.
+fn describe<const N: usize>(value: i128) -> String {
+    let number = Number::<N>::try_from(value).expect("Stored values fit the current width");
+    let trits: String = number.trits().iter().map(|trit| trit.to_string()).collect();
+    let trits = trits.trim_start_matches('0');
+    format!("{} ({})", if trits.is_empty() { "0" } else { trits }, value)
+}
+
+fn evaluate<const N: usize>(expression: &str, session: &Session) -> Result<i128, CalcError> {
+    let expression = expr::parse(expression).map_err(CalcError::Syntax)?;
+    let value = expression.evaluate_with(&|name| lookup::<N>(name, session))
+        .map_err(|error| match error.kind {
+            EvalErrorKind::UnknownVariable(name) if session.history_entry(&name) == Some(None) => CalcError::Dropped(name),
+            kind => CalcError::Evaluation(EvalError { kind, ..error })
+        })?;
+    Ok(i128::try_from(value).expect("Supported widths fit in an i128"))
+}
+
+/// Look up a variable, or a history entry such as `_` or `_3`.
+fn lookup<const N: usize>(name: &str, session: &Session) -> Option<Number<N>> {
+    let entry = match name.starts_with('_') {
+        true => session.history_entry(name).flatten(),
+        false => session.variables.get(name).copied()
+    };
+    entry.and_then(|value| Number::<N>::try_from(value).ok())
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let width = match (args.next().as_deref(), args.next(), args.next()) {
+        (None, _, _) => DEFAULT_WIDTH,
+        (Some("--width"), Some(width), None) => match width.parse() {
+            Ok(width) if WIDTHS.contains(&width) => width,
+            _ => {
+                eprintln!("supported widths are {:?}", WIDTHS);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => {
+            eprintln!("usage: bt-calc [--width N]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("Balanced ternary calculator using {} trits, :help for help", width);
+    }
+
+    let mut session = Session::new(width);
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().ok();
+        }
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        match session.handle(&line) {
+            Some(output) if output.is_empty() => (),
+            Some(output) => println!("{}", output),
+            None => break
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_both_notations() {
+        let mut session = Session::new(9);
+        assert_eq!(session.handle("+-0-- + 7").unwrap(), "_1 = +-0+0 (57)");
+        assert_eq!(session.handle("0t+0 * -(2 - 5) % 10").unwrap(), "_2 = +00 (9)");
+        // A run of trits is a number unless an operand follows it
+        assert_eq!(session.handle("++ - -").unwrap(), "_3 = +-- (5)");
+        assert_eq!(session.handle("-+ * --3").unwrap(), "_4 = -+0 (-6)");
+        // The full expression grammar is available
+        assert_eq!(session.handle("max(_1, 3) << 1 | 0t12").unwrap(), "_5 = +00+00 (252)");
+    }
+
+    #[test]
+    fn variables_and_history() {
+        let mut session = Session::new(9);
+        session.handle("x = 10");
+        session.handle("y = x * 2");
+        assert_eq!(session.handle("y - _1 + _").unwrap(), "_3 = +0+0 (30)");
+        assert_eq!(session.handle(":vars").unwrap(), "x = +0+ (10)\ny = +-+- (20)");
+        assert_eq!(session.handle("z + 1").unwrap(), "error: unknown variable 'z'");
+        assert_eq!(session.handle("_9").unwrap(), "error: unknown variable '_9'");
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut session = Session::new(6);
+        assert_eq!(session.handle("1 / (3 - 3)").unwrap(), "error: division by zero");
+        assert_eq!(session.handle("300 + 100").unwrap(), "error: overflow: result does not fit in the current width");
+        assert_eq!(session.handle("2 *").unwrap(), "error: syntax error: unexpected end of input");
+        assert_eq!(session.handle("(2").unwrap(), "error: syntax error: missing ')'");
+        assert_eq!(session.handle("pow(2, 0 - 1)").unwrap(), "error: negative exponent");
+        assert!(session.history.is_empty());
+    }
+
+    #[test]
+    fn changing_width() {
+        let mut session = Session::new(9);
+        session.handle("big = 9000");
+        session.handle("small = 9");
+
+        assert_eq!(session.handle(":width 6").unwrap(), "width is now 6 trits, 2 values that no longer fit were dropped");
+        assert_eq!(session.handle("small").unwrap(), "_3 = +00 (9)");
+        assert_eq!(session.handle(":width 7").unwrap(), format!("supported widths are {:?}", WIDTHS));
+        assert_eq!(session.handle(":quit"), None);
+    }
+
+    #[test]
+    fn history_numbers_survive_narrowing() {
+        let mut session = Session::new(9);
+        session.handle("9000");
+        session.handle("9");
+        session.handle("10");
+
+        assert_eq!(session.handle(":width 6").unwrap(), "width is now 6 trits, 1 values that no longer fit were dropped");
+        assert_eq!(session.handle("_2 + _3").unwrap(), "_4 = +-0+ (19)");
+        assert_eq!(session.handle("_1").unwrap(), "error: '_1' no longer fits in the current width");
+        assert_eq!(session.handle(":history").unwrap(), "_1 no longer fits\n_2 = +00 (9)\n_3 = +0+ (10)\n_4 = +-0+ (19)");
+    }
+}
@@ -0,0 +1,285 @@
+//! Converts numbers between balanced ternary and binary notations.
+//!
+//! ```text
+//! bt-convert [--width N] [--to FORMAT,...] [VALUE...]
+//! ```
+//!
+//! Values are read from the arguments, or from whitespace separated standard
+//! input if there are none. Each value can be written as:
+//!
+//! * a balanced ternary trit string, e.g. `+-0--` or `0t+-0--`
+//! * balanced base-27 with a `0z` prefix, e.g. `0zBW`
+//! * a decimal integer, e.g. `50`
+//! * hexadecimal with a `0x` prefix, e.g. `0x32`
+//! * binary with a `0b` prefix, e.g. `0b110010`
+//!
+//! Integers in decimal, hexadecimal and binary can have a leading '-'. A value
+//! of only zeros, with or without a leading '-', is a trit string, so `-00` is
+//! -9 rather than a negative decimal zero.
+//! Base-27 digits each hold three trits, using the tryte alphabet where `9` is
+//! zero, `A` to `M` are 1 to 13 and `N` to `Z` are -13 to -1.
+//!
+//! Each value is printed on its own line in every requested format, separated
+//! by spaces. The formats are `trits`, `base27`, `decimal`, `hex`, `binary` and
+//! `packed`, which shows the bytes of the five-trits-per-byte packed encoding
+//! in hexadecimal. `--width` chooses the number of trits values must fit in.
+//!
+//! The exit code is 0 on success, 1 for invalid arguments, 2 if any value
+//! could not be parsed and 3 if any value did not fit in the width.
+
+mod common;
+
+use std::fmt;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use balanced_ternary::number::{Number, ParseNumberError};
+use balanced_ternary::trit::Trit;
+
+use common::{with_width, WIDTHS};
+
+const DEFAULT_WIDTH: usize = 40;
+
+const DEFAULT_FORMATS: [Format; 4] = [Format::Trits, Format::Base27, Format::Decimal, Format::Hex];
+
+/// The characters of balanced base-27 digits from -13 to 13.
+const BASE27_DIGITS: &str = "NOPQRSTUVWXYZ9ABCDEFGHIJKLM";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Trits,
+    Base27,
+    Decimal,
+    Hex,
+    Binary,
+    Packed
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "trits" => Some(Format::Trits),
+            "base27" => Some(Format::Base27),
+            "decimal" => Some(Format::Decimal),
+            "hex" => Some(Format::Hex),
+            "binary" => Some(Format::Binary),
+            "packed" => Some(Format::Packed),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum ConvertError {
+    Invalid(String),
+    Overflow(String)
+}
+
+impl ConvertError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            ConvertError::Invalid(_) => 2,
+            ConvertError::Overflow(_) => 3
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConvertError::Invalid(value) => write!(f, "invalid number '{}'", value),
+            ConvertError::Overflow(value) => write!(f, "'{}' does not fit in the width", value)
+        }
+    }
+}
+
+/// Parse a value in any supported notation and render it in each format.
+fn convert<const N: usize>(input: &str, formats: &[Format]) -> Result<String, ConvertError> {
+    let number = parse::<N>(input)?;
+    let outputs: Vec<_> = formats.iter().map(|format| render(number, *format)).collect();
+    Ok(outputs.join(" "))
+}
+
+fn parse<const N: usize>(input: &str) -> Result<Number<N>, ConvertError> {
+    let invalid = || ConvertError::Invalid(input.to_string());
+    let overflow = || ConvertError::Overflow(input.to_string());
+
+    if let Some(trits) = input.strip_prefix("0t") {
+        return parse_trits(trits, input);
+    }
+    if let Some(digits) = input.strip_prefix("0z") {
+        let trits = digits.chars()
+            .map(|c| BASE27_DIGITS.find(c).map(|digit| digit_trits(digit as i8 - 13)))
+            .collect::<Option<Vec<_>>>()
+            .filter(|trits| !trits.is_empty())
+            .ok_or_else(invalid)?;
+        let trits: Vec<Trit> = trits.into_iter().flatten().skip_while(|trit| *trit == Trit::Zero).collect();
+        if trits.len() > N {
+            return Err(overflow());
+        }
+        return Ok(Number::<N>::from_rev_iter(trits.into_iter().rev()));
+    }
+
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, input)
+    };
+    let (radix, digits) = if let Some(digits) = unsigned.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = unsigned.strip_prefix("0b") {
+        (2, digits)
+    } else if unsigned.chars().all(|c| c.is_ascii_digit()) && unsigned.contains(|c| ('1'..='9').contains(&c)) {
+        (10, unsigned)
+    } else {
+        return parse_trits(input, input);
+    };
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(invalid());
+    }
+    let magnitude = u128::from_str_radix(digits, radix).map_err(|_| overflow())?;
+    let value = i128::try_from(magnitude).map_err(|_| overflow())?;
+    Number::<N>::try_from(if negative { -value } else { value }).map_err(|_| overflow())
+}
+
+fn parse_trits<const N: usize>(trits: &str, input: &str) -> Result<Number<N>, ConvertError> {
+    trits.parse().map_err(|err| match err {
+        ParseNumberError::OutOfRange => ConvertError::Overflow(input.to_string()),
+        _ => ConvertError::Invalid(input.to_string())
+    })
+}
+
+/// The three trits of a base-27 digit, most significant first.
+fn digit_trits(digit: i8) -> [Trit; 3] {
+    let trits = Number::<3>::from_i64(digit.into());
+    *trits.trits()
+}
+
+fn render<const N: usize>(number: Number<N>, format: Format) -> String {
+    let value = i128::try_from(number).expect("Supported widths fit in an i128");
+    let sign = if value < 0 { "-" } else { "" };
+
+    match format {
+        Format::Trits => {
+            let trits: String = number.trits().iter().map(|trit| trit.to_string()).collect();
+            match trits.trim_start_matches('0') {
+                "" => "0".to_string(),
+                trits => trits.to_string()
+            }
+        },
+        Format::Base27 => {
+            // Pad to a whole number of digits with leading zero trits
+            let padding = (3 - N % 3) % 3;
+            let trits: Vec<Trit> = std::iter::repeat_n(Trit::Zero, padding).chain(number.trits().iter().copied()).collect();
+            let digits: String = trits.chunks(3)
+                .map(|digit| {
+                    let value = Number::<3>::from_trits(digit.try_into().unwrap());
+                    BASE27_DIGITS.as_bytes()[(i32::from(value) + 13) as usize] as char
+                })
+                .collect();
+            match digits.trim_start_matches('9') {
+                "" => "0z9".to_string(),
+                digits => format!("0z{}", digits)
+            }
+        },
+        Format::Decimal => value.to_string(),
+        Format::Hex => format!("{}0x{:x}", sign, value.unsigned_abs()),
+        Format::Binary => format!("{}0b{:b}", sign, value.unsigned_abs()),
+        Format::Packed => number.to_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: bt-convert [--width N] [--to FORMAT,...] [VALUE...]");
+    eprintln!("widths: {:?}", WIDTHS);
+    eprintln!("formats: trits, base27, decimal, hex, binary, packed");
+    ExitCode::from(1)
+}
+
+fn main() -> ExitCode {
+    let mut width = DEFAULT_WIDTH;
+    let mut formats = DEFAULT_FORMATS.to_vec();
+    let mut values = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => match args.next().and_then(|width| width.parse().ok()) {
+                Some(value) if WIDTHS.contains(&value) => width = value,
+                _ => return usage()
+            },
+            "--to" => match args.next().map(|names| names.split(',').map(Format::from_name).collect()) {
+                Some(Some(requested)) => formats = requested,
+                _ => return usage()
+            },
+            "-h" | "--help" => return usage(),
+            _ => values.push(arg)
+        }
+    }
+
+    if values.is_empty() {
+        let mut input = String::new();
+        if io::stdin().read_to_string(&mut input).is_err() {
+            eprintln!("bt-convert: could not read standard input");
+            return ExitCode::from(1);
+        }
+        values = input.split_whitespace().map(str::to_string).collect();
+    }
+
+    let mut exit_code = 0;
+    for value in &values {
+        match with_width!(width, convert(value, &formats)) {
+            Ok(output) => println!("{}", output),
+            Err(error) => {
+                eprintln!("bt-convert: {}", error);
+                if exit_code == 0 {
+                    exit_code = error.exit_code();
+                }
+            }
+        }
+    }
+
+    ExitCode::from(exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FORMATS: [Format; 6] =
+        [Format::Trits, Format::Base27, Format::Decimal, Format::Hex, Format::Binary, Format::Packed];
+
+    #[test]
+    fn converts_between_notations() {
+        let expected = "+-0-- 0zBW 50 0x32 0b110010 ab79";
+        for input in ["+-0--", "0t+-0--", "0zBW", "50", "0x32", "0b110010"] {
+            assert_eq!(convert::<8>(input, &ALL_FORMATS), Ok(expected.to_string()), "converting {}", input);
+        }
+
+        assert_eq!(convert::<8>("-50", &ALL_FORMATS), Ok("-+0++ 0zYD -50 -0x32 -0b110010 4779".to_string()));
+        assert_eq!(convert::<8>("0", &ALL_FORMATS), Ok("0 0z9 0 0x0 0b0 7979".to_string()));
+
+        // Digits that are all zero are trits, since '-' is itself a trit
+        let decimal = |input| convert::<8>(input, &[Format::Decimal]).unwrap();
+        assert_eq!([decimal("-"), decimal("-0"), decimal("-00"), decimal("00")], ["-1", "-3", "-9", "0"]);
+        assert_eq!([decimal("-10"), decimal("-007")], ["-10", "-7"]);
+    }
+
+    #[test]
+    fn rejects_invalid_and_out_of_range_values() {
+        let error = |input| convert::<6>(input, &DEFAULT_FORMATS).unwrap_err();
+
+        assert_eq!(error("12a"), ConvertError::Invalid("12a".to_string()));
+        assert_eq!(error("0x"), ConvertError::Invalid("0x".to_string()));
+        assert_eq!(error("0z1"), ConvertError::Invalid("0z1".to_string()));
+        assert_eq!(error("0b102"), ConvertError::Invalid("0b102".to_string()));
+        assert_eq!(error("365"), ConvertError::Overflow("365".to_string()));
+        assert_eq!(error("+000000"), ConvertError::Overflow("+000000".to_string()));
+        assert_eq!(error("0zAAA"), ConvertError::Overflow("0zAAA".to_string()));
+        assert_eq!(error("0xffffffffffffffffffffffffffffffffff"), ConvertError::Overflow("0xffffffffffffffffffffffffffffffffff".to_string()));
+        assert_eq!(error("365").exit_code(), 3);
+
+        // The largest 6 trit value is 364, which needs a partial leading base-27 digit
+        assert_eq!(convert::<6>("364", &[Format::Base27, Format::Trits]), Ok("0zMM ++++++".to_string()));
+    }
+}
@@ -0,0 +1,26 @@
+//! Helpers shared by the command-line tools.
+
+/// The number sizes that can be selected, all of which fit in an i128.
+pub const WIDTHS: [usize; 10] = [6, 9, 12, 18, 24, 27, 36, 40, 54, 80];
+
+/// Dispatch a call to a function generic over the number width, for each of
+/// the supported widths.
+macro_rules! with_width {
+    ($width:expr, $function:ident($($arg:expr),*)) => {
+        match $width {
+            6 => $function::<6>($($arg),*),
+            9 => $function::<9>($($arg),*),
+            12 => $function::<12>($($arg),*),
+            18 => $function::<18>($($arg),*),
+            24 => $function::<24>($($arg),*),
+            27 => $function::<27>($($arg),*),
+            36 => $function::<36>($($arg),*),
+            40 => $function::<40>($($arg),*),
+            54 => $function::<54>($($arg),*),
+            80 => $function::<80>($($arg),*),
+            _ => unreachable!("Unsupported width")
+        }
+    };
+}
+
+pub(crate) use with_width;
@@ -0,0 +1,365 @@
+//! Integrity checks over sequences of trits.
+//!
+//! Each check consumes trits incrementally through the `TritHasher` trait, so
+//! a stream can be checked without holding it all in memory:
+//!
+//! * `Crc`, a cyclic redundancy check, the remainder of the message divided by
+//!   a generator polynomial over GF(3)
+//! * `CheckTrit`, a single trit like the Luhn check digit, which detects any
+//!   single wrong trit and any swap of adjacent trits
+//! * `ModularChecksum`, a Fletcher-style pair of running sums of `Number<N>`
+//!   words, modulo 3^N
+//!
+//! None of these resist deliberate tampering.
+
+use crate::gf3::{add, mul, sub, Poly3};
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// A hash or checksum that consumes trits one at a time.
+pub trait TritHasher {
+    /// The checksum produced
+    type Output;
+
+    /// Feed a single trit.
+    fn write_trit(&mut self, trit: Trit);
+
+    /// Feed a sequence of trits, in order.
+    fn write_trits(&mut self, trits: &[Trit]) {
+        trits.iter().for_each(|trit| self.write_trit(*trit));
+    }
+
+    /// Feed the trits of a number, most significant first.
+    fn write_number<const N: usize>(&mut self, number: Number<N>) {
+        self.write_trits(number.trits());
+    }
+
+    /// The checksum of every trit written so far. More trits can still be
+    /// written afterwards.
+    fn finish(&self) -> Self::Output;
+}
+
+/// A ternary cyclic redundancy check with a chosen generator polynomial.
+///
+/// The check trits for a message M(x) are the negated remainder of
+/// M(x)·x^r divided by the generator of degree r, so that the message followed
+/// by its check trits is a multiple of the generator. This detects every burst
+/// of errors spanning at most r trits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Crc {
+    // The generator below its leading term, highest degree first
+    taps: Vec<Trit>,
+    // The remainder of the trits so far, times x^r, highest degree first
+    remainder: Vec<Trit>
+}
+
+impl Crc {
+    /// Builds a CRC. The generator is scaled to be monic, which doesn't change
+    /// the codewords it accepts.
+    ///
+    /// **returns** The CRC, or `None` if the generator is constant
+    pub fn new(generator: &Poly3) -> Option<Self> {
+        let degree = generator.degree().filter(|degree| *degree > 0)?;
+        let taps = generator.monic().coefficients()[1..].to_vec();
+        Some(Crc { taps, remainder: vec![Trit::Zero; degree] })
+    }
+
+    /// A CRC using the first primitive polynomial of the given degree, which
+    /// also detects any two wrong trits less than 3^degree - 1 apart.
+    ///
+    /// **returns** The CRC, or `None` if the degree is zero or above 40
+    pub fn primitive(degree: usize) -> Option<Self> {
+        if degree > 40 {
+            return None;
+        }
+        Crc::new(&Poly3::primitive_polynomials(degree).next()?)
+    }
+
+    /// The generator polynomial.
+    pub fn generator(&self) -> Poly3 {
+        let coefficients: Vec<Trit> = [Trit::Pos].into_iter().chain(self.taps.iter().copied()).collect();
+        Poly3::new(&coefficients)
+    }
+
+    /// The check trits for a complete message, without changing this CRC.
+    pub fn checksum(&self, message: &[Trit]) -> Vec<Trit> {
+        let mut crc = self.clone();
+        crc.write_trits(message);
+        crc.finish()
+    }
+
+    /// Test whether a complete message followed by its check trits is intact.
+    pub fn verify(&self, message_and_checksum: &[Trit]) -> bool {
+        let mut crc = self.clone();
+        crc.write_trits(message_and_checksum);
+        crc.remainder.iter().all(|trit| *trit == Trit::Zero)
+    }
+
+    /// Start again from an empty message.
+    pub fn reset(&mut self) {
+        self.remainder.fill(Trit::Zero);
+    }
+}
+
+impl TritHasher for Crc {
+    type Output = Vec<Trit>;
+
+    fn write_trit(&mut self, trit: Trit) {
+        // Multiply by x and add the new trit times x^r, reducing the x^r term
+        // that results by subtracting the generator times its coefficient
+        let feedback = add(self.remainder[0], trit);
+        self.remainder.rotate_left(1);
+        *self.remainder.last_mut().expect("Generators are not constant") = Trit::Zero;
+        for (coefficient, tap) in self.remainder.iter_mut().zip(&self.taps) {
+            *coefficient = sub(*coefficient, mul(feedback, *tap));
+        }
+    }
+
+    fn finish(&self) -> Self::Output {
+        self.remainder.iter().map(|trit| trit.negate()).collect()
+    }
+}
+
+/// A single check trit, the balanced ternary counterpart of the Luhn check
+/// digit.
+///
+/// The trits are weighted alternately by 1 and -1, and the check trit makes the
+/// weighted sum zero modulo 3. As the weights are nonzero and adjacent weights
+/// differ, changing any one trit or swapping two adjacent different trits
+/// changes the sum.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CheckTrit {
+    sum: Trit,
+    // Whether the next trit has a weight of -1
+    odd: bool
+}
+
+impl CheckTrit {
+    /// A check over no trits.
+    pub fn new() -> Self {
+        CheckTrit::default()
+    }
+
+    /// The check trit to append to a complete sequence.
+    pub fn compute(trits: &[Trit]) -> Trit {
+        let mut check = CheckTrit::new();
+        check.write_trits(trits);
+        check.finish()
+    }
+
+    /// Test whether a sequence ending in its check trit is intact.
+    pub fn verify(trits_and_check: &[Trit]) -> bool {
+        let mut check = CheckTrit::new();
+        check.write_trits(trits_and_check);
+        check.sum == Trit::Zero
+    }
+}
+
+impl TritHasher for CheckTrit {
+    type Output = Trit;
+
+    fn write_trit(&mut self, trit: Trit) {
+        self.sum = if self.odd { sub(self.sum, trit) } else { add(self.sum, trit) };
+        self.odd = !self.odd;
+    }
+
+    /// The trit that would bring the weighted sum to zero in the next position.
+    /// Both weights are their own inverses.
+    fn finish(&self) -> Self::Output {
+        if self.odd { self.sum } else { self.sum.negate() }
+    }
+}
+
+/// A Fletcher-style checksum over words of N trits, with all arithmetic
+/// wrapping modulo 3^N.
+///
+/// The first sum adds up the words and the second adds up the first sum after
+/// each word, so it weights each word by its distance from the end. Unlike a
+/// plain sum, this detects words that are reordered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModularChecksum<const N: usize> {
+    sum: Number<N>,
+    weighted: Number<N>,
+    // Trits written that don't yet fill a word, which are the trailing
+    // `pending` trits of `word`
+    word: Number<N>,
+    pending: usize
+}
+
+impl<const N: usize> Default for ModularChecksum<N> {
+    fn default() -> Self {
+        ModularChecksum { sum: Number::ZERO, weighted: Number::ZERO, word: Number::ZERO, pending: 0 }
+    }
+}
+
+impl<const N: usize> ModularChecksum<N> {
+    /// A checksum over no words.
+    pub fn new() -> Self {
+        ModularChecksum::default()
+    }
+
+    /// The checksum of a complete sequence of numbers.
+    pub fn compute(numbers: &[Number<N>]) -> (Number<N>, Number<N>) {
+        let mut checksum = ModularChecksum::new();
+        numbers.iter().for_each(|number| checksum.write_word(*number));
+        checksum.finish()
+    }
+
+    /// Feed a whole word. Any trits written since the last full word are
+    /// padded with zeros to make a word first.
+    pub fn write_word(&mut self, word: Number<N>) {
+        if self.pending > 0 {
+            self.flush();
+        }
+        self.sum += word;
+        self.weighted += self.sum;
+    }
+
+    fn flush(&mut self) {
+        let padded = self.word << (N - self.pending);
+        self.word = Number::ZERO;
+        self.pending = 0;
+        self.write_word(padded);
+    }
+}
+
+impl<const N: usize> TritHasher for ModularChecksum<N> {
+    type Output = (Number<N>, Number<N>);
+
+    /// Feed a trit, which is accumulated most significant first until it
+    /// completes a word.
+    fn write_trit(&mut self, trit: Trit) {
+        self.word <<= 1;
+        self.word += trit;
+        self.pending += 1;
+        if self.pending == N {
+            let word = self.word;
+            self.word = Number::ZERO;
+            self.pending = 0;
+            self.write_word(word);
+        }
+    }
+
+    fn write_number<const M: usize>(&mut self, number: Number<M>) {
+        if M == N && self.pending == 0 {
+            self.write_word(Number::from_rev_iter(number.trits().iter().rev().copied()));
+        } else {
+            self.write_trits(number.trits());
+        }
+    }
+
+    /// The pair of sums, with a final partial word padded with zero trits.
+    fn finish(&self) -> Self::Output {
+        let mut checksum = *self;
+        if checksum.pending > 0 {
+            checksum.flush();
+        }
+        (checksum.sum, checksum.weighted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::TritRng;
+
+    fn trits(encoded: &str) -> Vec<Trit> {
+        encoded.chars().map(|c| Trit::from_char(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn crc_remainder() {
+        // With the generator x^2 + 1, "+-0" is x^2 - x, and times x^2 that is
+        // x^4 - x^3 with remainder x + 1, so the check trits are -x - 1
+        let generator: Poly3 = "+0+".parse().unwrap();
+        let crc = Crc::new(&generator).unwrap();
+        assert_eq!(crc.checksum(&trits("+-0")), trits("--"));
+        assert!(crc.verify(&trits("+-0--")));
+        assert!(!crc.verify(&trits("+-0-+")));
+        assert_eq!(crc.generator(), generator);
+        assert_eq!(Crc::new(&-&generator), Some(crc));
+        assert_eq!(Crc::new(&Poly3::one()), None);
+    }
+
+    #[test]
+    fn crc_detects_bursts() {
+        let crc = Crc::primitive(4).unwrap();
+        let mut message: Vec<Trit> = TritRng::new(5).take(40).collect();
+        message.extend(crc.checksum(&message));
+        assert!(crc.verify(&message));
+
+        // Every nonzero burst of up to 4 trits is detected
+        let mut rng = TritRng::new(6);
+        for start in 0..message.len() - 3 {
+            for _ in 0..20 {
+                let mut burst = [Trit::Zero; 4];
+                rng.fill(&mut burst);
+                burst[0] = Trit::Pos;
+                let mut corrupted = message.clone();
+                for (trit, error) in corrupted[start..].iter_mut().zip(burst) {
+                    *trit = add(*trit, error);
+                }
+                assert!(!crc.verify(&corrupted));
+            }
+        }
+    }
+
+    #[test]
+    fn crc_streaming() {
+        let message: Vec<Trit> = TritRng::new(7).take(25).collect();
+        let mut crc = Crc::primitive(6).unwrap();
+        crc.write_trits(&message[..10]);
+        crc.write_trits(&message[10..]);
+        assert_eq!(crc.finish(), Crc::primitive(6).unwrap().checksum(&message));
+        crc.reset();
+        assert_eq!(crc.finish(), vec![Trit::Zero; 6]);
+    }
+
+    #[test]
+    fn check_trit() {
+        let data = trits("+-00+--+");
+        let check = CheckTrit::compute(&data);
+        let mut full = data.clone();
+        full.push(check);
+        assert!(CheckTrit::verify(&full));
+
+        for idx in 0..full.len() {
+            for error in [Trit::Neg, Trit::Pos] {
+                let mut corrupted = full.clone();
+                corrupted[idx] = add(corrupted[idx], error);
+                assert!(!CheckTrit::verify(&corrupted));
+            }
+            if idx + 1 < full.len() && full[idx] != full[idx + 1] {
+                let mut swapped = full.clone();
+                swapped.swap(idx, idx + 1);
+                assert!(!CheckTrit::verify(&swapped));
+            }
+        }
+        assert_eq!(CheckTrit::compute(&[]), Trit::Zero);
+        assert!(CheckTrit::verify(&[]));
+    }
+
+    #[test]
+    fn modular_checksum() {
+        let numbers = [Number::<4>::from_i64(40), Number::from_i64(-7), Number::from_i64(3)];
+        // The sums are 36 and 40 + 33 + 36 = 109, which wraps to 28 modulo 81
+        assert_eq!(ModularChecksum::compute(&numbers), (Number::from_i64(36), Number::from_i64(28)));
+
+        let reordered = [numbers[1], numbers[0], numbers[2]];
+        assert_ne!(ModularChecksum::compute(&reordered), ModularChecksum::compute(&numbers));
+
+        // Writing the same words as trits, in pieces or whole, gives the same sums
+        let mut streamed = ModularChecksum::<4>::new();
+        streamed.write_trits(&numbers[0].trits()[..3]);
+        streamed.write_trits(&numbers[0].trits()[3..]);
+        streamed.write_number(numbers[1]);
+        streamed.write_number(Number::<2>::ZERO);
+        streamed.write_number(Number::<2>::from_i64(3));
+        assert_eq!(streamed.finish(), ModularChecksum::compute(&numbers));
+
+        // A trailing partial word is padded with zeros
+        let mut partial = ModularChecksum::<4>::new();
+        partial.write_trits(&trits("+-"));
+        assert_eq!(partial.finish(), ModularChecksum::compute(&[Number::from_i64(18)]));
+    }
+}
@@ -0,0 +1,339 @@
+//! Ternary error-correcting codes, for protecting trits stored or sent over a
+//! noisy channel.
+//!
+//! Both codes here are systematic linear codes over GF(3): each block of data
+//! trits is followed by check trits chosen so that a fixed linear combination
+//! of the block, its syndrome, is zero. A corrupted block has a nonzero
+//! syndrome that identifies the errors. The Hamming and Golay codes are
+//! perfect, so every syndrome is explained by some correctable error pattern;
+//! a block with more errors than the code can correct is silently "corrected"
+//! to the wrong data.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::gf3::{add, mul, sub, trit_vectors, Poly3, TritOrder};
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// Error returned when encoded trits cannot be decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EccError {
+    /// The input wasn't this length
+    WrongLength(usize),
+    /// The padding decoded as nonzero, so some block had more errors than the
+    /// code can correct
+    Uncorrectable
+}
+
+impl fmt::Display for EccError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EccError::WrongLength(length) => write!(f, "expected {} trits", length),
+            EccError::Uncorrectable => write!(f, "too many errors to correct")
+        }
+    }
+}
+
+impl Error for EccError {}
+
+/// A systematic linear block code over GF(3).
+///
+/// Codewords hold the data trits followed by the check trits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinearCode {
+    // The syndrome contributed by each data trit being one; the check trits
+    // each contribute a unit vector
+    columns: Vec<Vec<Trit>>,
+    check_length: usize,
+    correctable: usize
+}
+
+impl LinearCode {
+    /// The ternary Hamming code with the given number of check trits r, which
+    /// protects (3^r - 1)/2 - r data trits against any single error. With two
+    /// check trits it is the [4, 2, 3] tetracode.
+    ///
+    /// **returns** The code, or `None` if there are fewer than two check trits
+    pub fn hamming(check_length: usize) -> Option<Self> {
+        if check_length < 2 {
+            return None;
+        }
+
+        // Every nonzero syndrome must be a multiple of exactly one column, so
+        // the columns are the vectors whose first nonzero trit is one. Those
+        // with a single nonzero trit belong to the check trits.
+        let columns = trit_vectors(check_length, TritOrder::LeastSignificantLast)
+            .filter(|column| {
+                let leading = column.iter().find(|trit| **trit != Trit::Zero);
+                let weight = column.iter().filter(|trit| **trit != Trit::Zero).count();
+                leading == Some(&Trit::Pos) && weight > 1
+            })
+            .collect();
+        Some(LinearCode { columns, check_length, correctable: 1 })
+    }
+
+    /// The perfect ternary Golay code, which protects 6 data trits with 5 check
+    /// trits against any two errors.
+    ///
+    /// It is the cyclic code generated by x^5 + x^4 - x^3 + x^2 - 1, a factor of
+    /// x^11 - 1, with the data trits as the coefficients of x^10 to x^5.
+    pub fn golay() -> Self {
+        let generator: Poly3 = "++-+0-".parse().expect("Generator is valid");
+        let columns = (5..11).rev()
+            .map(|degree| {
+                let remainder = &Poly3::monomial(degree) % &generator;
+                (0..5).rev().map(|power| remainder.coefficient(power)).collect()
+            })
+            .collect();
+        LinearCode { columns, check_length: 5, correctable: 2 }
+    }
+
+    /// The number of trits in each codeword.
+    pub fn length(&self) -> usize {
+        self.data_length() + self.check_length
+    }
+
+    /// The number of data trits in each codeword.
+    pub fn data_length(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The number of check trits in each codeword.
+    pub fn check_length(&self) -> usize {
+        self.check_length
+    }
+
+    /// The number of errors in a codeword that are guaranteed to be corrected.
+    pub fn correctable(&self) -> usize {
+        self.correctable
+    }
+
+    /// Encode one block of data.
+    ///
+    /// * `data` The data trits, `data_length()` of them
+    ///
+    /// **returns** The codeword, the data followed by its check trits
+    pub fn encode(&self, data: &[Trit]) -> Result<Vec<Trit>, EccError> {
+        if data.len() != self.data_length() {
+            return Err(EccError::WrongLength(self.data_length()));
+        }
+        let mut codeword = data.to_vec();
+        codeword.extend(self.data_syndrome(data).into_iter().map(Trit::negate));
+        Ok(codeword)
+    }
+
+    /// The syndrome of a codeword, which is all zero when there are no errors.
+    pub fn syndrome(&self, codeword: &[Trit]) -> Result<Vec<Trit>, EccError> {
+        if codeword.len() != self.length() {
+            return Err(EccError::WrongLength(self.length()));
+        }
+        let (data, check) = codeword.split_at(self.data_length());
+        Ok(self.data_syndrome(data).into_iter().zip(check).map(|(lhs, rhs)| add(lhs, *rhs)).collect())
+    }
+
+    fn data_syndrome(&self, data: &[Trit]) -> Vec<Trit> {
+        let mut syndrome = vec![Trit::Zero; self.check_length];
+        for (trit, column) in data.iter().zip(&self.columns) {
+            for (total, entry) in syndrome.iter_mut().zip(column) {
+                *total = add(*total, mul(*trit, *entry));
+            }
+        }
+        syndrome
+    }
+
+    /// The syndrome of a single error, adding `value` to the trit at
+    /// `position`.
+    fn error_syndrome(&self, position: usize, value: Trit) -> Vec<Trit> {
+        match self.columns.get(position) {
+            Some(column) => column.iter().map(|entry| mul(*entry, value)).collect(),
+            None => {
+                let mut syndrome = vec![Trit::Zero; self.check_length];
+                syndrome[position - self.data_length()] = value;
+                syndrome
+            }
+        }
+    }
+
+    /// Decode one codeword, correcting up to `correctable()` errors.
+    ///
+    /// **returns** The data trits and the number of trits that were corrected
+    pub fn decode(&self, codeword: &[Trit]) -> Result<(Vec<Trit>, usize), EccError> {
+        let syndrome = self.syndrome(codeword)?;
+        let mut data = codeword[..self.data_length()].to_vec();
+        if syndrome.iter().all(|trit| *trit == Trit::Zero) {
+            return Ok((data, 0));
+        }
+
+        // Search the error patterns by weight for one with the same syndrome.
+        // Only errors in the data need to be undone.
+        let errors = self.find_errors(&syndrome);
+        for (position, value) in &errors {
+            if let Some(trit) = data.get_mut(*position) {
+                *trit = sub(*trit, *value);
+            }
+        }
+        Ok((data, errors.len()))
+    }
+
+    /// The positions and values of the fewest errors, up to `correctable`,
+    /// that produce a syndrome.
+    fn find_errors(&self, syndrome: &[Trit]) -> Vec<(usize, Trit)> {
+        let singles: Vec<(usize, Trit, Vec<Trit>)> = (0..self.length())
+            .flat_map(|position| [Trit::Neg, Trit::Pos].map(|value| (position, value, self.error_syndrome(position, value))))
+            .collect();
+        if let Some((position, value, _)) = singles.iter().find(|(_, _, single)| single == syndrome) {
+            return vec![(*position, *value)];
+        }
+        if self.correctable < 2 {
+            return Vec::new();
+        }
+        for (idx, (first, first_value, first_syndrome)) in singles.iter().enumerate() {
+            let rest: Vec<Trit> = syndrome.iter().zip(first_syndrome).map(|(total, part)| sub(*total, *part)).collect();
+            let second = singles[idx..].iter().find(|(position, _, single)| position > first && *single == rest);
+            if let Some((second, second_value, _)) = second {
+                return vec![(*first, *first_value), (*second, *second_value)];
+            }
+        }
+        Vec::new()
+    }
+
+    /// Encode a number as a sequence of codewords. Its trits are split into
+    /// blocks of `data_length()`, most significant first, with the last block
+    /// padded with zero trits.
+    pub fn protect<const N: usize>(&self, number: Number<N>) -> Vec<Trit> {
+        number.trits()
+            .chunks(self.data_length())
+            .flat_map(|chunk| {
+                let mut data = chunk.to_vec();
+                data.resize(self.data_length(), Trit::Zero);
+                self.encode(&data).expect("Blocks are padded to the data length")
+            })
+            .collect()
+    }
+
+    /// Decode a number encoded with `protect()`, correcting errors in each
+    /// block.
+    ///
+    /// **returns** The number and the total number of trits corrected, or an
+    /// error if the length is wrong or a block was miscorrected into the
+    /// padding
+    pub fn recover<const N: usize>(&self, codewords: &[Trit]) -> Result<(Number<N>, usize), EccError> {
+        let blocks = N.div_ceil(self.data_length());
+        if codewords.len() != blocks * self.length() {
+            return Err(EccError::WrongLength(blocks * self.length()));
+        }
+
+        let mut data = Vec::with_capacity(blocks * self.data_length());
+        let mut corrected = 0;
+        for codeword in codewords.chunks(self.length()) {
+            let (block, count) = self.decode(codeword)?;
+            data.extend(block);
+            corrected += count;
+        }
+        if data[N..].iter().any(|trit| *trit != Trit::Zero) {
+            return Err(EccError::Uncorrectable);
+        }
+
+        let mut trits = [Trit::Zero; N];
+        trits.copy_from_slice(&data[..N]);
+        Ok((Number::from_trits(trits), corrected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::TritRng;
+
+    /// Every combination of the given number of trits.
+    fn all_words(length: usize) -> Vec<Vec<Trit>> {
+        trit_vectors(length, TritOrder::LeastSignificantLast).collect()
+    }
+
+    fn weight(word: &[Trit]) -> usize {
+        word.iter().filter(|trit| **trit != Trit::Zero).count()
+    }
+
+    #[test]
+    fn parameters() {
+        let tetracode = LinearCode::hamming(2).unwrap();
+        assert_eq!((tetracode.length(), tetracode.data_length(), tetracode.check_length()), (4, 2, 2));
+        let hamming = LinearCode::hamming(3).unwrap();
+        assert_eq!((hamming.length(), hamming.data_length(), hamming.correctable()), (13, 10, 1));
+        assert_eq!(LinearCode::hamming(1), None);
+        let golay = LinearCode::golay();
+        assert_eq!((golay.length(), golay.data_length(), golay.correctable()), (11, 6, 2));
+
+        // The generator divides x^11 - 1
+        let generator: Poly3 = "++-+0-".parse().unwrap();
+        assert!((&(&Poly3::monomial(11) - &Poly3::one()) % &generator).is_zero());
+    }
+
+    #[test]
+    fn minimum_distance() {
+        for (code, distance) in [(LinearCode::hamming(2).unwrap(), 3), (LinearCode::hamming(3).unwrap(), 3), (LinearCode::golay(), 5)] {
+            let minimum = all_words(code.data_length()).iter()
+                .filter(|data| weight(data) > 0)
+                .map(|data| weight(&code.encode(data).unwrap()))
+                .min();
+            assert_eq!(minimum, Some(distance));
+        }
+    }
+
+    #[test]
+    fn corrects_every_single_error() {
+        let code = LinearCode::hamming(3).unwrap();
+        let data: Vec<Trit> = TritRng::new(3).take(10).collect();
+        let codeword = code.encode(&data).unwrap();
+        assert_eq!(code.decode(&codeword), Ok((data.clone(), 0)));
+        for position in 0..code.length() {
+            for value in [Trit::Neg, Trit::Pos] {
+                let mut corrupted = codeword.clone();
+                corrupted[position] = add(corrupted[position], value);
+                assert_eq!(code.decode(&corrupted), Ok((data.clone(), 1)));
+            }
+        }
+    }
+
+    #[test]
+    fn golay_corrects_every_double_error() {
+        let code = LinearCode::golay();
+        let data = [Trit::Pos, Trit::Neg, Trit::Zero, Trit::Pos, Trit::Pos, Trit::Zero];
+        let codeword = code.encode(&data).unwrap();
+        let mut syndromes = vec![];
+        for error in all_words(code.length()).into_iter().filter(|error| weight(error) <= 2) {
+            let corrupted: Vec<Trit> = codeword.iter().zip(&error).map(|(trit, error)| add(*trit, *error)).collect();
+            assert_eq!(code.decode(&corrupted), Ok((data.to_vec(), weight(&error))));
+            syndromes.push(code.syndrome(&corrupted).unwrap());
+        }
+        // The code is perfect: each of the 243 syndromes is used exactly once
+        syndromes.sort();
+        syndromes.dedup();
+        assert_eq!(syndromes.len(), 243);
+    }
+
+    #[test]
+    fn protects_numbers() {
+        let code = LinearCode::golay();
+        let number = Number::<16>::from_i64(-1_234_567);
+        let mut encoded = code.protect(number);
+        assert_eq!(encoded.len(), 33);
+        assert_eq!(code.recover::<16>(&encoded), Ok((number, 0)));
+
+        encoded[0] = encoded[0].negate();
+        encoded[12] = add(encoded[12], Trit::Pos);
+        encoded[20] = Trit::Zero;
+        encoded[32] = add(encoded[32], Trit::Neg);
+        assert_eq!(code.recover::<16>(&encoded).map(|(number, _)| number), Ok(number));
+
+        assert_eq!(code.recover::<16>(&encoded[1..]), Err(EccError::WrongLength(33)));
+        assert_eq!(code.recover::<16>(&[]).unwrap_err().to_string(), "expected 33 trits");
+        assert_eq!(code.encode(&[Trit::Zero]), Err(EccError::WrongLength(6)));
+
+        // Five errors in the padded block turn into a miscorrection
+        let mut garbled = code.protect(Number::<2>::ZERO);
+        garbled[2..7].fill(Trit::Pos);
+        assert_eq!(code.recover::<2>(&garbled), Err(EccError::Uncorrectable));
+    }
+}
@@ -0,0 +1,501 @@
+//! Parsing and evaluation of infix expressions over balanced ternary numbers.
+//!
+//! An expression such as `+-0 * (++ - 7) / 0t12` is parsed by `parse()` into an
+//! `Expr` tree, which can then be evaluated over a `Number<N>` of any size, or
+//! displayed to pretty-print it back into source form. Every node and error
+//! carries the `Span` of source text it came from.
+//!
+//! Numbers can be written as:
+//!
+//! * decimal integers, e.g. `42`
+//! * bare balanced ternary trit strings, e.g. `+-0`
+//! * balanced ternary with a `0t` prefix, e.g. `0t+-0`
+//! * standard unsigned ternary with a `0t` prefix, e.g. `0t12` for 5
+//!
+//! A run of '+', '0' and '-' is read as a trit string unless it is followed by
+//! something else that starts an operand, in which case its first character is
+//! a sign. So `++ - 7` is 4 - 7 while `-7` is negative seven and `-(3)` is
+//! negative three. A run starting with '0' can't be a sign, so instead its last
+//! character is an operator: `0-1` is 0 - 1 and `0+-1` is 1 - 1. Decimal numbers
+//! can't start with a zero.
+//!
+//! Operators, from loosest to tightest binding, are:
+//!
+//! * `|` tritwise OR (maximum)
+//! * `^` tritwise XOR (negated product)
+//! * `&` tritwise AND (minimum)
+//! * `<<` and `>>` shifts by a number of trits, where a negative amount shifts
+//!   the opposite way
+//! * `+` and `-`
+//! * `*`, `/` and `%`, with division rounding towards zero
+//! * unary `-` negation and `!` tritwise NOT
+//!
+//! The functions `pow(x, n)`, `abs(x)`, `sign(x)`, `min(a, b)` and `max(a, b)`
+//! are available, and any other name is a variable.
+
+mod parser;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+pub use parser::parse;
+
+/// A range of byte positions in source text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// The smallest span covering both this span and another.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Render the source with this span underlined by carets, for use in error
+    /// messages. Empty spans, such as the end of input, get a single caret.
+    ///
+    /// * `source` The source text the span refers to
+    ///
+    /// **returns** The source followed by a line marking the span
+    pub fn underline(&self, source: &str) -> String {
+        let indent = source[..self.start.min(source.len())].chars().count();
+        let width = source.get(self.start..self.end).map_or(0, |text| text.chars().count()).max(1);
+        format!("{}\n{}{}", source, " ".repeat(indent), "^".repeat(width))
+    }
+}
+
+/// Operators taking a single operand.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Not
+}
+
+/// Operators taking two operands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Shl,
+    Shr,
+    And,
+    Or,
+    Xor
+}
+
+impl BinaryOp {
+    /// How tightly the operator binds; higher values bind more tightly.
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::Xor => 2,
+            BinaryOp::And => 3,
+            BinaryOp::Shl | BinaryOp::Shr => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6
+        }
+    }
+
+    /// The operator as it is written in source.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::And => "&",
+            BinaryOp::Or => "|",
+            BinaryOp::Xor => "^"
+        }
+    }
+}
+
+/// A number as written in source, kept independent of any number size until
+/// the expression is evaluated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Literal {
+    Decimal(i128),
+    /// Balanced ternary trits, most significant first
+    Trits(Vec<Trit>)
+}
+
+/// The different kinds of expression node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+    Variable(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>)
+}
+
+/// A node of a parsed expression, along with the source it was parsed from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span
+}
+
+/// Error returned when source text is not a valid expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub span: Span,
+    pub kind: ParseErrorKind
+}
+
+/// The reasons that parsing can fail.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// The source ended where an operand was expected
+    UnexpectedEnd,
+    /// A character that can't appear at this point was found
+    UnexpectedChar(char),
+    /// An opening parenthesis was never closed
+    UnclosedParen,
+    /// A number literal is malformed
+    InvalidNumber(String),
+    /// A decimal literal is too large to be represented
+    NumberTooLarge
+}
+
+/// Error returned when an expression cannot be evaluated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvalError {
+    pub span: Span,
+    pub kind: EvalErrorKind
+}
+
+/// The reasons that evaluation can fail.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EvalErrorKind {
+    /// A literal or result does not fit in the number size
+    Overflow,
+    DivideByZero,
+    UnknownVariable(String),
+    UnknownFunction(String),
+    WrongArgumentCount { expected: usize, found: usize },
+    NegativeExponent
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected '{}'", c),
+            ParseErrorKind::UnclosedParen => write!(f, "missing ')'"),
+            ParseErrorKind::InvalidNumber(literal) => write!(f, "invalid number '{}'", literal),
+            ParseErrorKind::NumberTooLarge => write!(f, "number is too large")
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.span.start)
+    }
+}
+
+impl Error for ParseError {}
+
+impl fmt::Display for EvalErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalErrorKind::Overflow => write!(f, "overflow"),
+            EvalErrorKind::DivideByZero => write!(f, "division by zero"),
+            EvalErrorKind::UnknownVariable(name) => write!(f, "unknown variable '{}'", name),
+            EvalErrorKind::UnknownFunction(name) => write!(f, "unknown function '{}'", name),
+            EvalErrorKind::WrongArgumentCount { expected, found } =>
+                write!(f, "expected {} arguments but found {}", expected, found),
+            EvalErrorKind::NegativeExponent => write!(f, "negative exponent")
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.span.start)
+    }
+}
+
+impl Error for EvalError {}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        parse(source)
+    }
+}
+
+impl Expr {
+    /// Evaluate an expression that contains no variables.
+    ///
+    /// **returns** The value of the expression, or the first error found
+    pub fn evaluate<const N: usize>(&self) -> Result<Number<N>, EvalError> {
+        self.evaluate_with(&|_| None)
+    }
+
+    /// Evaluate an expression, looking up the values of variables as needed.
+    ///
+    /// * `variables` Gives the value of a variable, or `None` if it is unknown
+    ///
+    /// **returns** The value of the expression, or the first error found
+    pub fn evaluate_with<const N: usize>(&self, variables: &dyn Fn(&str) -> Option<Number<N>>) -> Result<Number<N>, EvalError> {
+        let error = |kind| EvalError { span: self.span, kind };
+
+        match &self.kind {
+            ExprKind::Literal(Literal::Decimal(value)) => Number::<N>::try_from(*value)
+                .map_err(|_| error(EvalErrorKind::Overflow)),
+            ExprKind::Literal(Literal::Trits(trits)) => {
+                let significant = trits.iter().skip_while(|trit| **trit == Trit::Zero).count();
+                if significant > N {
+                    return Err(error(EvalErrorKind::Overflow));
+                }
+                Ok(Number::<N>::from_rev_iter(trits.iter().rev().copied()))
+            },
+            ExprKind::Variable(name) => variables(name)
+                .ok_or_else(|| error(EvalErrorKind::UnknownVariable(name.clone()))),
+            ExprKind::Unary(op, operand) => {
+                let value = operand.evaluate_with(variables)?;
+                Ok(match op {
+                    UnaryOp::Neg => -value,
+                    UnaryOp::Not => !value
+                })
+            },
+            ExprKind::Binary(op, lhs, rhs) => {
+                let lhs = lhs.evaluate_with(variables)?;
+                let rhs_value = rhs.evaluate_with(variables)?;
+                if matches!(op, BinaryOp::Div | BinaryOp::Rem) && rhs_value.is_zero() {
+                    return Err(EvalError { span: rhs.span, kind: EvalErrorKind::DivideByZero });
+                }
+
+                match op {
+                    BinaryOp::Add => lhs.checked_add(rhs_value),
+                    BinaryOp::Sub => lhs.checked_sub(rhs_value),
+                    BinaryOp::Mul => lhs.checked_mul(rhs_value),
+                    BinaryOp::Div => lhs.checked_div(rhs_value),
+                    BinaryOp::Rem => lhs.checked_rem(rhs_value),
+                    BinaryOp::Shl => Some(shift(lhs, rhs_value, true)),
+                    BinaryOp::Shr => Some(shift(lhs, rhs_value, false)),
+                    BinaryOp::And => Some(lhs & rhs_value),
+                    BinaryOp::Or => Some(lhs | rhs_value),
+                    BinaryOp::Xor => Some(lhs ^ rhs_value)
+                }.ok_or_else(|| error(EvalErrorKind::Overflow))
+            },
+            ExprKind::Call(name, args) => {
+                let expected = match name.as_str() {
+                    "abs" | "sign" => 1,
+                    "pow" | "min" | "max" => 2,
+                    _ => return Err(error(EvalErrorKind::UnknownFunction(name.clone())))
+                };
+                if args.len() != expected {
+                    return Err(error(EvalErrorKind::WrongArgumentCount { expected, found: args.len() }));
+                }
+
+                let values = args.iter()
+                    .map(|arg| arg.evaluate_with(variables))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "abs" => Ok(values[0].abs()),
+                    "sign" => Ok(Number::<N>::from_rev_iter(std::iter::once(values[0].signum()))),
+                    "min" => Ok(values[0].min(values[1])),
+                    "max" => Ok(values[0].max(values[1])),
+                    _ => {
+                        if values[1].is_negative() {
+                            return Err(EvalError { span: args[1].span, kind: EvalErrorKind::NegativeExponent });
+                        }
+                        // Exponents too large for a u32 overflow unless the base is -1, 0 or
+                        // 1, where only their parity matters. A balanced ternary number is
+                        // odd when it has an odd number of non-zero trits.
+                        let exponent = i128::try_from(values[1]).ok()
+                            .and_then(|exponent| u32::try_from(exponent).ok())
+                            .unwrap_or_else(|| {
+                                let nonzero = values[1].trits().iter().filter(|trit| **trit != Trit::Zero).count();
+                                if nonzero % 2 == 1 { u32::MAX } else { u32::MAX - 1 }
+                            });
+                        values[0].checked_pow(exponent).ok_or_else(|| error(EvalErrorKind::Overflow))
+                    }
+                }
+            }
+        }
+    }
+
+    /// The precedence used when deciding whether this expression needs to be
+    /// parenthesised as an operand; atoms and unary expressions bind tightest.
+    fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Binary(op, _, _) => op.precedence(),
+            _ => u8::MAX
+        }
+    }
+}
+
+/// Shift a number left or right, where a negative amount shifts the opposite
+/// way. Amounts of N or more clear every trit.
+fn shift<const N: usize>(value: Number<N>, amount: Number<N>, left: bool) -> Number<N> {
+    let positions = i128::try_from(amount).map_or(N, |amount| amount.unsigned_abs().min(N as u128) as usize);
+    if left == amount.is_negative() {
+        value >> positions
+    } else {
+        value << positions
+    }
+}
+
+impl fmt::Display for Expr {
+    /// Pretty-print the expression in a form that parses back to the same
+    /// tree, using the fewest parentheses needed. Trit literals are written
+    /// with a `0t` prefix.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Literal(Literal::Decimal(value)) => write!(f, "{}", value),
+            ExprKind::Literal(Literal::Trits(trits)) => {
+                write!(f, "0t")?;
+                trits.iter().try_for_each(|trit| write!(f, "{}", trit))
+            },
+            ExprKind::Variable(name) => write!(f, "{}", name),
+            ExprKind::Unary(op, operand) => {
+                let symbol = match op {
+                    UnaryOp::Neg => "-",
+                    UnaryOp::Not => "!"
+                };
+                match operand.kind {
+                    ExprKind::Binary(..) => write!(f, "{}({})", symbol, operand),
+                    _ => write!(f, "{}{}", symbol, operand)
+                }
+            },
+            ExprKind::Binary(op, lhs, rhs) => {
+                // Operators are left associative, so a right operand of equal
+                // precedence needs parentheses but a left one doesn't
+                if lhs.precedence() < op.precedence() {
+                    write!(f, "({})", lhs)?;
+                } else {
+                    write!(f, "{}", lhs)?;
+                }
+                write!(f, " {} ", op.symbol())?;
+                if rhs.precedence() <= op.precedence() {
+                    write!(f, "({})", rhs)
+                } else {
+                    write!(f, "{}", rhs)
+                }
+            },
+            ExprKind::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval<const N: usize>(source: &str) -> Result<Number<N>, EvalError> {
+        parse(source).unwrap().evaluate()
+    }
+
+    #[test]
+    fn evaluates_mixed_notations() {
+        // 6 * (4 - 7) / 5 = -18 / 5, rounded towards zero
+        assert_eq!(eval::<9>("+-0 * (++ - 7) / 0t12"), Ok(Number::<9>::from_i64(-3)));
+        assert_eq!(eval::<9>("0t+-0 - 0t102"), Ok(Number::<9>::from_i64(-5)));
+        assert_eq!(eval::<9>("-(3) + -7 - -"), Ok(Number::<9>::from_i64(-9)));
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(eval::<9>("2 + 3 * 4"), Ok(Number::<9>::from_i64(14)));
+        assert_eq!(eval::<9>("20 - 5 - 3"), Ok(Number::<9>::from_i64(12)));
+        assert_eq!(eval::<9>("1 + 2 << 2"), Ok(Number::<9>::from_i64(27)));
+        assert_eq!(eval::<9>("100 >> 2 % 3"), Ok(Number::<9>::from_i64(11)));
+        assert_eq!(eval::<9>("1 << -1"), Ok(Number::<9>::ZERO));
+        // & binds tighter than ^, which binds tighter than |
+        assert_eq!(eval::<6>("+-0 | 0+- ^ --- & 0"), Ok(Number::<6>::from("++0")));
+        assert_eq!(eval::<6>("!+-0"), Ok(Number::<6>::from("-+0")));
+    }
+
+    #[test]
+    fn functions_and_variables() {
+        let variables = |name: &str| match name {
+            "x" => Some(Number::<9>::from_i64(-4)),
+            _ => None
+        };
+        let eval_with = |source| parse(source).unwrap().evaluate_with(&variables);
+
+        assert_eq!(eval_with("pow(x, 3) + abs(x) * sign(x)"), Ok(Number::<9>::from_i64(-68)));
+        assert_eq!(eval_with("max(x, 2) - min(x, 2)"), Ok(Number::<9>::from_i64(6)));
+        assert_eq!(eval_with("y + 1"), Err(EvalError {
+            span: Span::new(0, 1), kind: EvalErrorKind::UnknownVariable("y".to_string())
+        }));
+        assert_eq!(eval_with("max(1)").unwrap_err().kind, EvalErrorKind::WrongArgumentCount { expected: 2, found: 1 });
+        assert_eq!(eval_with("root(x)").unwrap_err().kind, EvalErrorKind::UnknownFunction("root".to_string()));
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        assert_eq!(eval::<6>("7 / (3 - 3)"), Err(EvalError {
+            span: Span::new(4, 11), kind: EvalErrorKind::DivideByZero
+        }));
+        assert_eq!(eval::<6>("300 + 100").unwrap_err().kind, EvalErrorKind::Overflow);
+        assert_eq!(eval::<6>("1000").unwrap_err().kind, EvalErrorKind::Overflow);
+        assert_eq!(eval::<3>("0t+000").unwrap_err().kind, EvalErrorKind::Overflow);
+        assert_eq!(eval::<3>("0t000+"), Ok(Number::<3>::ONE));
+        assert_eq!(eval::<6>("pow(2, 0 - 1)").unwrap_err().span, Span::new(7, 12));
+        assert_eq!(eval::<6>("pow(1, 300)"), Ok(Number::<6>::ONE));
+        assert_eq!(eval::<30>("pow(1, 999999999999)"), Ok(Number::<30>::ONE));
+        assert_eq!(eval::<30>("pow(-1, 999999999999)"), Ok(-Number::<30>::ONE));
+        assert_eq!(eval::<30>("pow(-1, 1000000000000)"), Ok(Number::<30>::ONE));
+        assert_eq!(eval::<30>("pow(0, 999999999999)"), Ok(Number::<30>::ZERO));
+        assert_eq!(eval::<30>("pow(2, 999999999999)").unwrap_err().kind, EvalErrorKind::Overflow);
+    }
+
+    #[test]
+    fn pretty_printing() {
+        let cases = [
+            ("1+2*3", "1 + 2 * 3"),
+            ("(1+2)*3", "(1 + 2) * 3"),
+            ("1-(2-3)", "1 - (2 - 3)"),
+            ("(1-2)-3", "1 - 2 - 3"),
+            ("-(a+b) & !c", "-(a + b) & !c"),
+            ("+-0 * 0t12", "0t+-0 * 0t+--"),
+            ("pow( x ,2 ) << (1 | 2)", "pow(x, 2) << (1 | 2)"),
+            ("--5", "--5")
+        ];
+        for (source, pretty) in cases {
+            let expr = parse(source).unwrap();
+            assert_eq!(expr.to_string(), pretty);
+            assert_eq!(parse(pretty).unwrap().to_string(), pretty);
+        }
+    }
+
+    #[test]
+    fn error_underlining() {
+        let source = "2 * (3 + 4";
+        let error = parse(source).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnclosedParen);
+        assert_eq!(error.span.underline(source), "2 * (3 + 4\n    ^^^^^^");
+        assert_eq!(error.to_string(), "missing ')' at position 4");
+    }
+}
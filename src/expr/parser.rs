@@ -0,0 +1,329 @@
+use crate::expr::{BinaryOp, Expr, ExprKind, Literal, ParseError, ParseErrorKind, Span, UnaryOp};
+use crate::trit::Trit;
+
+/// Binary operators grouped by precedence, from loosest to tightest binding.
+const LEVELS: [&[BinaryOp]; 6] = [
+    &[BinaryOp::Or],
+    &[BinaryOp::Xor],
+    &[BinaryOp::And],
+    &[BinaryOp::Shl, BinaryOp::Shr],
+    &[BinaryOp::Add, BinaryOp::Sub],
+    &[BinaryOp::Mul, BinaryOp::Div, BinaryOp::Rem]
+];
+
+/// Parse an infix expression.
+///
+/// * `source` The expression text
+///
+/// **returns** The expression tree, or the first error found
+pub fn parse(source: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser { source, position: 0 };
+    let expr = parser.binary(0)?;
+
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(expr),
+        Some(c) => Err(parser.error_here(ParseErrorKind::UnexpectedChar(c)))
+    }
+}
+
+/// A recursive descent parser working directly on the characters of the
+/// source, as whether a '+' or '-' is an operator, a sign or a trit depends on
+/// where it appears.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize
+}
+
+impl<'a> Parser<'a> {
+    /// Parse a chain of left associative binary operators at the given
+    /// precedence level, with operands from the next level up.
+    fn binary(&mut self, level: usize) -> Result<Expr, ParseError> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut lhs = self.binary(level + 1)?;
+        loop {
+            self.skip_whitespace();
+            let Some(op) = LEVELS[level].iter().find(|op| self.rest().starts_with(op.symbol())) else {
+                return Ok(lhs);
+            };
+            self.position += op.symbol().len();
+
+            let rhs = self.binary(level + 1)?;
+            let span = lhs.span.to(rhs.span);
+            lhs = Expr { kind: ExprKind::Binary(*op, Box::new(lhs), Box::new(rhs)), span };
+        }
+    }
+
+    /// unary := ('-' | '+' | '!') unary | primary
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let op = match self.peek() {
+            Some('!') => UnaryOp::Not,
+            Some('-') if self.starts_signed_operand() => UnaryOp::Neg,
+            Some('+') if self.starts_signed_operand() => {
+                self.position += 1;
+                return self.unary();
+            },
+            _ => return self.primary()
+        };
+        self.position += 1;
+
+        let operand = self.unary()?;
+        let span = Span::new(start, operand.span.end);
+        Ok(Expr { kind: ExprKind::Unary(op, Box::new(operand)), span })
+    }
+
+    /// primary := number | name | name '(' arguments ')' | '(' expression ')'
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.position;
+        let kind = match self.peek() {
+            None => return Err(self.error_here(ParseErrorKind::UnexpectedEnd)),
+            Some('(') => {
+                self.position += 1;
+                let mut expr = self.binary(0)?;
+                self.expect_close(start)?;
+                expr.span = Span::new(start, self.position);
+                return Ok(expr);
+            },
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_').to_string();
+                let end = self.position;
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.position += 1;
+                    ExprKind::Call(name, self.arguments(start)?)
+                } else {
+                    self.position = end;
+                    ExprKind::Variable(name)
+                }
+            },
+            Some(_) if self.rest().starts_with("0t") => {
+                self.position += 2;
+                let digits = self.take_while(|c| c.is_ascii_digit() || is_trit(c));
+                let trits = ternary_digits(digits)
+                    .ok_or_else(|| self.error_from(start, ParseErrorKind::InvalidNumber(self.source[start..self.position].to_string())))?;
+                ExprKind::Literal(Literal::Trits(trits))
+            },
+            Some('0') if self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                return Err(self.error_from(start, ParseErrorKind::InvalidNumber(digits.to_string())));
+            },
+            Some('1'..='9') => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                let value = digits.parse().map_err(|_| self.error_from(start, ParseErrorKind::NumberTooLarge))?;
+                ExprKind::Literal(Literal::Decimal(value))
+            },
+            Some(c) if is_trit(c) => {
+                // A run starting with '0' can't be a sign, so when an operand
+                // follows it the run's last character is a binary operator
+                let split = c == '0' && self.starts_signed_operand();
+                let mut run = self.take_while(is_trit);
+                if split && run.ends_with(['+', '-']) {
+                    run = &run[..run.len() - 1];
+                    self.position -= 1;
+                }
+                let trits = run.chars().filter_map(Trit::from_char).collect();
+                ExprKind::Literal(Literal::Trits(trits))
+            },
+            Some(c) => return Err(self.error_here(ParseErrorKind::UnexpectedChar(c)))
+        };
+
+        Ok(Expr { kind, span: Span::new(start, self.position) })
+    }
+
+    /// Parse the comma separated arguments of a call, after its opening
+    /// parenthesis.
+    fn arguments(&mut self, start: usize) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.position += 1;
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.binary(0)?);
+            self.skip_whitespace();
+            if self.peek() == Some(',') {
+                self.position += 1;
+            } else {
+                self.expect_close(start)?;
+                return Ok(args);
+            }
+        }
+    }
+
+    /// Consume a closing parenthesis, reporting an unclosed one from `start`
+    /// if it isn't there.
+    fn expect_close(&mut self, start: usize) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(')') => {
+                self.position += 1;
+                Ok(())
+            },
+            None => Err(self.error_from(start, ParseErrorKind::UnclosedParen)),
+            Some(c) => Err(self.error_here(ParseErrorKind::UnexpectedChar(c)))
+        }
+    }
+
+    /// Whether the run of trit characters at the current position is followed
+    /// by the start of an operand, so that the leading '+' or '-' is a sign.
+    fn starts_signed_operand(&self) -> bool {
+        let rest = self.rest().trim_start_matches(is_trit).trim_start();
+        rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '(' || c == '!')
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.source[self.position..];
+        let length = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// An error covering the character at the current position.
+    fn error_here(&self, kind: ParseErrorKind) -> ParseError {
+        let end = self.position + self.peek().map_or(0, char::len_utf8);
+        ParseError { span: Span::new(self.position, end), kind }
+    }
+
+    /// An error covering the source from `start` to the current position.
+    fn error_from(&self, start: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { span: Span::new(start, self.position), kind }
+    }
+}
+
+fn is_trit(c: char) -> bool {
+    matches!(c, '+' | '0' | '-')
+}
+
+/// Convert the digits after a `0t` prefix into balanced ternary trits. The
+/// digits are either balanced trits, or the digits 0, 1 and 2 of standard
+/// unsigned ternary.
+///
+/// **returns** The trits, or `None` if the digits are empty or mix notations
+fn ternary_digits(digits: &str) -> Option<Vec<Trit>> {
+    if digits.is_empty() {
+        return None;
+    }
+    if digits.chars().all(is_trit) {
+        return digits.chars().map(Trit::from_char).collect();
+    }
+
+    // Convert from the least significant digit, where a 2 becomes -1 with a
+    // carry into the next position
+    let mut trits = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0;
+    for c in digits.chars().rev() {
+        let digit = match c {
+            '0' => 0,
+            '1' => 1,
+            '2' => 2,
+            _ => return None
+        } + carry;
+        carry = (digit >= 2) as u8;
+        trits.push(match digit % 3 {
+            0 => Trit::Zero,
+            1 => Trit::Pos,
+            _ => Trit::Neg
+        });
+    }
+    if carry == 1 {
+        trits.push(Trit::Pos);
+    }
+    trits.reverse();
+    Some(trits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(expr: &Expr) -> &Literal {
+        match &expr.kind {
+            ExprKind::Literal(literal) => literal,
+            kind => panic!("expected literal, found {:?}", kind)
+        }
+    }
+
+    #[test]
+    fn trit_runs_and_signs() {
+        let expr = parse("++ - 7").unwrap();
+        let ExprKind::Binary(BinaryOp::Sub, lhs, rhs) = &expr.kind else { panic!() };
+        assert_eq!(literal(lhs), &Literal::Trits(vec![Trit::Pos, Trit::Pos]));
+        assert_eq!(literal(rhs), &Literal::Decimal(7));
+
+        let expr = parse("-7").unwrap();
+        assert!(matches!(expr.kind, ExprKind::Unary(UnaryOp::Neg, _)));
+        assert_eq!(expr.span, Span::new(0, 2));
+
+        assert_eq!(literal(&parse("-0").unwrap()), &Literal::Trits(vec![Trit::Neg, Trit::Zero]));
+    }
+
+    #[test]
+    fn trit_runs_starting_with_zero() {
+        // The last character of the run is an operator rather than a sign
+        for (source, op) in [("0-1", BinaryOp::Sub), ("0+1", BinaryOp::Add), ("+0-1", BinaryOp::Sub)] {
+            let expr = parse(source).unwrap();
+            let ExprKind::Binary(found, lhs, rhs) = &expr.kind else { panic!("{}", source) };
+            assert_eq!(*found, op);
+            assert_eq!(literal(lhs), &Literal::Trits(vec![Trit::Zero]));
+            assert_eq!(literal(rhs), &Literal::Decimal(1));
+            assert_eq!(expr.span, Span::new(source.len() - 3, source.len()));
+        }
+
+        let expr = parse("0+-x").unwrap();
+        let ExprKind::Binary(BinaryOp::Sub, lhs, _) = &expr.kind else { panic!() };
+        assert_eq!(literal(lhs), &Literal::Trits(vec![Trit::Zero, Trit::Pos]));
+    }
+
+    #[test]
+    fn unsigned_ternary_literals() {
+        // 12 is 5 in standard ternary, and 222 is 26
+        assert_eq!(literal(&parse("0t12").unwrap()), &Literal::Trits(vec![Trit::Pos, Trit::Neg, Trit::Neg]));
+        assert_eq!(literal(&parse("0t222").unwrap()), &Literal::Trits(vec![Trit::Pos, Trit::Zero, Trit::Zero, Trit::Neg]));
+        assert_eq!(ternary_digits(""), None);
+        assert_eq!(ternary_digits("1+"), None);
+    }
+
+    #[test]
+    fn spans() {
+        let expr = parse("foo(1, (2))").unwrap();
+        let ExprKind::Call(name, args) = &expr.kind else { panic!() };
+        assert_eq!(name, "foo");
+        assert_eq!(expr.span, Span::new(0, 11));
+        assert_eq!(args[0].span, Span::new(4, 5));
+        assert_eq!(args[1].span, Span::new(7, 10));
+
+        assert_eq!(parse("1 +  2").unwrap().span, Span::new(0, 6));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |source| parse(source).unwrap_err();
+
+        assert_eq!(error("1 + "), ParseError { span: Span::new(4, 4), kind: ParseErrorKind::UnexpectedEnd });
+        assert_eq!(error("1 $ 2"), ParseError { span: Span::new(2, 3), kind: ParseErrorKind::UnexpectedChar('$') });
+        assert_eq!(error("min(1, 2"), ParseError { span: Span::new(0, 8), kind: ParseErrorKind::UnclosedParen });
+        assert_eq!(error("(1 2)"), ParseError { span: Span::new(3, 4), kind: ParseErrorKind::UnexpectedChar('2') });
+        assert_eq!(error("007"), ParseError { span: Span::new(0, 3), kind: ParseErrorKind::InvalidNumber("007".to_string()) });
+        assert_eq!(error("0t13").kind, ParseErrorKind::InvalidNumber("0t13".to_string()));
+        assert_eq!(error("99999999999999999999999999999999999999999").kind, ParseErrorKind::NumberTooLarge);
+    }
+}
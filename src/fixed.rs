@@ -0,0 +1,433 @@
+//! Balanced ternary fixed-point numbers.
+//!
+//! A `Fixed<I, F>` holds I integer trits and F fractional trits, written with a
+//! radix point between them, e.g. `+-.0+` for 2 + 1/9. As every trit is
+//! balanced around zero the fractional part always lies strictly between -1/2
+//! and 1/2, so the integer part alone is the value rounded to the nearest
+//! integer, and dropping fractional trits rounds to the nearest value with
+//! fewer trits.
+//!
+//! Arithmetic is carried out on the value scaled by 3^F as an `i128`, which
+//! limits I + F to at most 40 trits so that products always fit. Like the
+//! `Number` operators, the arithmetic operators wrap around on overflow, and
+//! `checked_*` variants are available to detect it.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::number::{Number, OutOfRangeError, ParseNumberError};
+use crate::trit::Trit;
+
+/// The largest total number of trits supported by `Fixed`.
+pub const MAX_TRITS: usize = 40;
+
+/// A balanced ternary fixed-point number with I integer trits and F fractional
+/// trits.
+///
+/// * `I` The number of trits before the radix point
+/// * `F` The number of trits after the radix point
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Fixed<const I: usize, const F: usize> {
+    // The integer part is compared first, which gives numeric ordering as the
+    // fractional part can never outweigh a difference in the integer part
+    integer: Number<I>,
+    fraction: Number<F>
+}
+
+/// Error returned when a fixed-point number cannot be parsed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseFixedError {
+    /// The integer or fractional trits were invalid
+    InvalidTrits(ParseNumberError),
+    /// There were more fractional trits than the number can hold
+    TooManyFractionalTrits
+}
+
+impl fmt::Display for ParseFixedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseFixedError::InvalidTrits(err) => write!(f, "{}", err),
+            ParseFixedError::TooManyFractionalTrits => write!(f, "too many fractional trits for number size")
+        }
+    }
+}
+
+impl Error for ParseFixedError {}
+
+impl<const I: usize, const F: usize> Fixed<I, F> {
+    /// Zero, with every trit zero.
+    pub const ZERO: Self = Fixed { integer: Number::<I>::ZERO, fraction: Number::<F>::ZERO };
+
+    /// The largest representable value, with every trit positive.
+    pub const MAX: Self = Fixed { integer: Number::<I>::MAX, fraction: Number::<F>::MAX };
+
+    /// The smallest representable value, with every trit negative.
+    pub const MIN: Self = Fixed { integer: Number::<I>::MIN, fraction: Number::<F>::MIN };
+
+    /// The smallest positive value, 3^-F.
+    pub const EPSILON: Self = Fixed { integer: Number::<I>::ZERO, fraction: Number::<F>::ONE };
+
+    /// Rejects sizes whose products would not fit in the `i128` used for
+    /// arithmetic. Referenced by every conversion to and from that form.
+    const SIZE_CHECK: () = assert!(I + F <= MAX_TRITS, "Fixed-point numbers are limited to 40 trits");
+
+    /// Builds a fixed-point number from its integer and fractional trits.
+    ///
+    /// * `integer` The trits before the radix point
+    /// * `fraction` The trits after the radix point
+    ///
+    /// **returns** The fixed-point number
+    pub const fn new(integer: Number<I>, fraction: Number<F>) -> Self {
+        Fixed { integer, fraction }
+    }
+
+    /// Builds a fixed-point number with no fractional part.
+    pub const fn from_integer(integer: Number<I>) -> Self {
+        Fixed { integer, fraction: Number::<F>::ZERO }
+    }
+
+    /// The trits before the radix point. As the fractional part is always less
+    /// than a half in magnitude, this is also the value rounded to the nearest
+    /// integer.
+    pub fn integer(&self) -> Number<I> {
+        self.integer
+    }
+
+    /// The trits after the radix point.
+    pub fn fraction(&self) -> Number<F> {
+        self.fraction
+    }
+
+    /// Round to the nearest value with fewer fractional trits, by dropping
+    /// the trits beyond that point.
+    ///
+    /// * `trits` The number of fractional trits to keep
+    ///
+    /// **returns** The rounded value, which is unchanged if `trits` is F or more
+    pub fn round_to(self, trits: usize) -> Self {
+        let dropped = F.saturating_sub(trits);
+        Fixed { integer: self.integer, fraction: (self.fraction >> dropped) << dropped }
+    }
+
+    /// Add exactly.
+    ///
+    /// **returns** The sum, or `None` if it is out of range
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Self::checked_from_raw(self.raw() + rhs.raw())
+    }
+
+    /// Subtract exactly.
+    ///
+    /// **returns** The difference, or `None` if it is out of range
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Self::checked_from_raw(self.raw() - rhs.raw())
+    }
+
+    /// Multiply, rounding the product to the nearest representable value.
+    ///
+    /// **returns** The product, or `None` if it is out of range
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Self::checked_from_raw(div_nearest(self.raw() * rhs.raw(), scale(F)))
+    }
+
+    /// Divide, rounding the quotient to the nearest representable value.
+    ///
+    /// **returns** The quotient, or `None` if the divisor is zero or the
+    /// quotient is out of range
+    pub fn checked_div(self, divisor: Self) -> Option<Self> {
+        if divisor.raw() == 0 {
+            return None;
+        }
+        Self::checked_from_raw(div_nearest(self.raw() * scale(F), divisor.raw()))
+    }
+
+    /// Convert to the nearest `f64`.
+    pub fn to_f64(self) -> f64 {
+        self.raw() as f64 / scale(F) as f64
+    }
+
+    /// The value scaled by 3^F, so that it is an integer.
+    fn raw(self) -> i128 {
+        let () = Self::SIZE_CHECK;
+        let integer = i128::try_from(self.integer).expect("Fixed-point parts fit in an i128");
+        let fraction = i128::try_from(self.fraction).expect("Fixed-point parts fit in an i128");
+        integer * scale(F) + fraction
+    }
+
+    /// The fixed-point number with the given value scaled by 3^F, or `None` if
+    /// it is out of range.
+    fn checked_from_raw(raw: i128) -> Option<Self> {
+        let () = Self::SIZE_CHECK;
+        let fraction = balanced_rem(raw, scale(F));
+        let integer = (raw - fraction) / scale(F);
+        Some(Fixed {
+            integer: Number::<I>::try_from(integer).ok()?,
+            fraction: Number::<F>::try_from(fraction).expect("Balanced remainder fits in F trits")
+        })
+    }
+
+    /// The fixed-point number with the given value scaled by 3^F, wrapping
+    /// around if it is out of range.
+    fn wrapping_from_raw(raw: i128) -> Self {
+        Self::checked_from_raw(balanced_rem(raw, scale(I + F)))
+            .expect("Wrapped value is in range")
+    }
+}
+
+/// 3^trits as an `i128`.
+const fn scale(trits: usize) -> i128 {
+    3_i128.pow(trits as u32)
+}
+
+/// The remainder of dividing by `modulus` (a power of three), brought into the
+/// balanced range from -(modulus - 1) / 2 to (modulus - 1) / 2.
+fn balanced_rem(value: i128, modulus: i128) -> i128 {
+    let half = (modulus - 1) / 2;
+    (value + half).rem_euclid(modulus) - half
+}
+
+/// Integer division rounded to the nearest result, with ties rounded away from
+/// zero. Ties can only arise when dividing by a multiple of two.
+pub(crate) fn div_nearest(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if 2 * remainder.unsigned_abs() >= denominator.unsigned_abs() {
+        quotient + numerator.signum() * denominator.signum()
+    } else {
+        quotient
+    }
+}
+
+impl<const I: usize, const F: usize> Add for Fixed<I, F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::wrapping_from_raw(self.raw() + rhs.raw())
+    }
+}
+
+impl<const I: usize, const F: usize> AddAssign for Fixed<I, F> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const I: usize, const F: usize> Sub for Fixed<I, F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::wrapping_from_raw(self.raw() - rhs.raw())
+    }
+}
+
+impl<const I: usize, const F: usize> SubAssign for Fixed<I, F> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const I: usize, const F: usize> Mul for Fixed<I, F> {
+    type Output = Self;
+
+    /// Multiply, rounding the product to the nearest representable value and
+    /// wrapping around on overflow.
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::wrapping_from_raw(div_nearest(self.raw() * rhs.raw(), scale(F)))
+    }
+}
+
+impl<const I: usize, const F: usize> MulAssign for Fixed<I, F> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const I: usize, const F: usize> Div for Fixed<I, F> {
+    type Output = Self;
+
+    /// Divide, rounding the quotient to the nearest representable value and
+    /// wrapping around on overflow.
+    ///
+    /// If the divisor is zero then the program will exit with an error message.
+    fn div(self, divisor: Self) -> Self::Output {
+        if divisor.raw() == 0 {
+            panic!("Attempt to divide by zero")
+        }
+        Self::wrapping_from_raw(div_nearest(self.raw() * scale(F), divisor.raw()))
+    }
+}
+
+impl<const I: usize, const F: usize> DivAssign for Fixed<I, F> {
+    fn div_assign(&mut self, divisor: Self) {
+        *self = *self / divisor;
+    }
+}
+
+impl<const I: usize, const F: usize> Neg for Fixed<I, F> {
+    type Output = Self;
+
+    /// Negation flips every trit, so can never overflow.
+    fn neg(self) -> Self::Output {
+        Fixed { integer: -self.integer, fraction: -self.fraction }
+    }
+}
+
+impl<const I: usize, const F: usize> TryFrom<f64> for Fixed<I, F> {
+    type Error = OutOfRangeError;
+
+    /// Convert a float to the nearest fixed-point value.
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        let scaled = (value * scale(F) as f64).round();
+        if !scaled.is_finite() || scaled.abs() >= i128::MAX as f64 {
+            return Err(OutOfRangeError);
+        }
+        Self::checked_from_raw(scaled as i128).ok_or(OutOfRangeError)
+    }
+}
+
+impl<const I: usize, const F: usize> From<Fixed<I, F>> for f64 {
+    fn from(value: Fixed<I, F>) -> Self {
+        value.to_f64()
+    }
+}
+
+impl<const I: usize, const F: usize> FromStr for Fixed<I, F> {
+    type Err = ParseFixedError;
+
+    /// Parse trits with an optional radix point, e.g. "+-.0+". Fewer trits
+    /// than the number holds may be given on either side of the point.
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        let (integer, fraction) = encoded.split_once('.').unwrap_or((encoded, ""));
+        if fraction.chars().count() > F {
+            return Err(ParseFixedError::TooManyFractionalTrits);
+        }
+
+        let integer = match integer {
+            "" if !fraction.is_empty() => Number::<I>::ZERO,
+            integer => integer.parse().map_err(ParseFixedError::InvalidTrits)?
+        };
+        let fraction_trits = fraction.chars()
+            .map(|c| Trit::from_char(c).ok_or(ParseFixedError::InvalidTrits(ParseNumberError::InvalidTrit(c))))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Fractional trits are aligned to the radix point, so pad on the right
+        let padding = F - fraction_trits.len();
+        let fraction = Number::<F>::from_rev_iter(std::iter::repeat_n(Trit::Zero, padding).chain(fraction_trits.into_iter().rev()));
+
+        Ok(Fixed { integer, fraction })
+    }
+}
+
+impl<const I: usize, const F: usize> fmt::Display for Fixed<I, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trit in self.integer.trits() {
+            write!(f, "{}", trit)?;
+        }
+        write!(f, ".")?;
+        for trit in self.fraction.trits() {
+            write!(f, "{}", trit)?;
+        }
+        write!(f, " ({})", self.to_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Q = Fixed<4, 3>;
+
+    fn q(encoded: &str) -> Q {
+        encoded.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing_and_display() {
+        // 2 + 1/9
+        let value = q("+-.0+");
+        assert_eq!(value.integer(), Number::<4>::from("+-"));
+        assert_eq!(value.fraction(), Number::<3>::from("0+0"));
+        assert_eq!(value.to_string(), "00+-.0+0 (2.111111111111111)");
+
+        assert_eq!(q(".+").to_string(), "0000.+00 (0.3333333333333333)");
+        assert_eq!(q("-"), Q::from_integer(Number::<4>::NEG_ONE));
+        assert_eq!("+.+000".parse::<Q>(), Err(ParseFixedError::TooManyFractionalTrits));
+        assert_eq!("+.x".parse::<Q>(), Err(ParseFixedError::InvalidTrits(ParseNumberError::InvalidTrit('x'))));
+    }
+
+    #[test]
+    fn ordering() {
+        // +.- is 2/3, which is more than 0.+ at 1/3 despite its negative trit
+        assert!(q("+.-") > q("0.+"));
+        assert!(q("-.+") < q("0.-"));
+        assert!(Q::MIN < Q::ZERO && Q::ZERO < Q::EPSILON && Q::EPSILON < Q::MAX);
+    }
+
+    #[test]
+    fn addition_and_subtraction() {
+        // 2/9 + 2/9 = 4/9, and 1/3 + 1/3 = 2/3 carries out of the fractional part
+        assert_eq!(q(".+-") + q(".+-"), q(".++"));
+        assert_eq!(q(".+") + q(".+"), q("+.-"));
+        // 4/3 - 19/9 = -7/9
+        assert_eq!(q("+.+") - q("+-.0+"), q("-.+-"));
+        assert_eq!(Q::MAX.checked_add(Q::EPSILON), None);
+        assert_eq!(Q::MAX + Q::EPSILON, Q::MIN);
+    }
+
+    #[test]
+    fn multiplication_rounds_to_nearest() {
+        // 1/3 * 1/3 = 1/9, exactly representable
+        assert_eq!(q(".+") * q(".+"), q(".0+"));
+        // 1/27 * 1/3 = 1/81, which rounds to zero, while 13/27 * 13/27 = 169/729
+        // rounds to 6/27
+        assert_eq!(q(".00+") * q(".+"), Q::ZERO);
+        assert_eq!(q(".+++") * q(".+++"), q(".+-0"));
+        assert_eq!((-q(".+++")) * q(".+++"), q(".-+0"));
+        assert_eq!(q("++++").checked_mul(q("+-")), None);
+    }
+
+    #[test]
+    fn division_rounds_to_nearest() {
+        let one = q("+");
+        let three = q("+0");
+        // 1/3 is exact, 1/2 = 13.5/27 is a tie rounded away from zero
+        assert_eq!(one / three, q(".+"));
+        assert_eq!(one / q("+-"), q("+.---"));
+        assert_eq!((-one) / q("+-"), q("-.+++"));
+        // 1/4 = 6.75/27 rounds to 7/27
+        assert_eq!(one / q("++"), q(".+-+"));
+        assert_eq!(one.checked_div(Q::ZERO), None);
+        assert_eq!(Q::MAX.checked_div(Q::EPSILON), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempt to divide by zero")]
+    fn division_by_zero() {
+        let _ = q("+") / Q::ZERO;
+    }
+
+    #[test]
+    fn float_conversions() {
+        assert_eq!(Q::try_from(2.111), Ok(q("+-.0+")));
+        assert_eq!(Q::try_from(-0.02), Ok(Q::new(Number::<4>::ZERO, Number::<3>::from("00-"))));
+        assert_eq!(f64::from(q("-+.-")), -2.0 - 1.0 / 3.0);
+        assert_eq!(Q::try_from(41.0), Err(OutOfRangeError));
+        assert_eq!(Q::try_from(f64::NAN), Err(OutOfRangeError));
+        assert_eq!(Q::try_from(f64::INFINITY), Err(OutOfRangeError));
+
+        for value in [-12.4, -0.75, 0.0, 0.1, 3.3, 40.4] {
+            let fixed = Q::try_from(value).unwrap();
+            assert!((fixed.to_f64() - value).abs() <= 0.5 / 27.0, "{} converted to {}", value, fixed);
+        }
+    }
+
+    #[test]
+    fn rounding_to_fewer_trits() {
+        let value = q("+-.+-+");
+        assert_eq!(value.round_to(2), q("+-.+-"));
+        assert_eq!(value.round_to(0), q("+-"));
+        assert_eq!(value.round_to(5), value);
+        assert_eq!(value.integer(), Number::<4>::from("+-"));
+    }
+}
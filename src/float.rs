@@ -0,0 +1,623 @@
+//! Balanced ternary floating-point numbers.
+//!
+//! A `TernaryFloat<E, M>` holds an E trit exponent and an M trit mantissa, and
+//! represents `mantissa × 3^exponent` with a radix point after the leading
+//! mantissa trit. The mantissa is normalised so that its leading trit is
+//! nonzero, which can be either sign, so mantissas range in magnitude from just
+//! over a half to just under three halves and carry the sign of the number.
+//!
+//! As with IEEE floats the extreme exponents are reserved: the all-negative
+//! exponent with a zero mantissa is zero, and the all-positive exponent holds
+//! the infinities (with a positive or negative mantissa) and NaN (with a zero
+//! mantissa). There are no subnormal numbers, so results too small to be
+//! normalised become zero, and as zero has no sign there is no negative zero.
+//!
+//! Every operation rounds its exact result to the nearest representable value.
+//! Balanced ternary can't represent the halfway point between two values with
+//! a finite number of trits, so only division can produce a tie, which is
+//! rounded away from zero.
+//!
+//! Arithmetic is carried out on `i128` mantissas, which limits E to at most 40
+//! trits and M to at most 38 trits.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::fixed::div_nearest;
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// The largest number of exponent trits supported by `TernaryFloat`.
+pub const MAX_EXPONENT_TRITS: usize = 40;
+
+/// The largest number of mantissa trits supported by `TernaryFloat`.
+pub const MAX_MANTISSA_TRITS: usize = 38;
+
+/// A balanced ternary floating-point number with E exponent trits and M
+/// mantissa trits.
+///
+/// * `E` The number of trits in the exponent
+/// * `M` The number of trits in the mantissa, including the leading trit
+#[derive(Clone, Copy, Debug)]
+pub struct TernaryFloat<const E: usize, const M: usize> {
+    exponent: Number<E>,
+    mantissa: Number<M>
+}
+
+impl<const E: usize, const M: usize> TernaryFloat<E, M> {
+    /// Zero, which is the only value with the smallest exponent.
+    pub const ZERO: Self = TernaryFloat { exponent: Number::<E>::MIN, mantissa: Number::<M>::ZERO };
+
+    /// One, with a mantissa of `+.00…` and a zero exponent.
+    pub const ONE: Self = TernaryFloat { exponent: Number::<E>::ZERO, mantissa: leading_one() };
+
+    /// Positive infinity, the result of overflow or dividing a positive
+    /// value by zero.
+    pub const INFINITY: Self = TernaryFloat { exponent: Number::<E>::MAX, mantissa: Number::<M>::ONE };
+
+    /// Negative infinity, the negation of `INFINITY`.
+    pub const NEG_INFINITY: Self = TernaryFloat { exponent: Number::<E>::MAX, mantissa: Number::<M>::NEG_ONE };
+
+    /// Not a number, the result of operations such as 0/0 or ∞ - ∞.
+    pub const NAN: Self = TernaryFloat { exponent: Number::<E>::MAX, mantissa: Number::<M>::ZERO };
+
+    /// The largest finite value, with every mantissa trit positive and the
+    /// largest unreserved exponent.
+    pub const MAX: Self = TernaryFloat { exponent: largest_exponent(Trit::Pos), mantissa: Number::<M>::MAX };
+
+    /// The smallest finite value, the negation of `MAX`.
+    pub const MIN: Self = TernaryFloat { exponent: largest_exponent(Trit::Pos), mantissa: Number::<M>::MIN };
+
+    /// The smallest positive value, with a mantissa of `+.00…` and the smallest
+    /// unreserved exponent.
+    pub const MIN_POSITIVE: Self = TernaryFloat { exponent: largest_exponent(Trit::Neg), mantissa: leading_one() };
+
+    /// The difference between one and the next larger value, 3^-(M-1).
+    pub const EPSILON: Self = TernaryFloat { exponent: Number::<E>::from_i64(1 - M as i64), mantissa: leading_one() };
+
+    /// Rejects sizes that can't be handled with `i128` arithmetic. Referenced
+    /// by every conversion to and from that form.
+    const SIZE_CHECK: () = assert!(
+        E >= 1 && E <= MAX_EXPONENT_TRITS && M >= 1 && M <= MAX_MANTISSA_TRITS,
+        "Ternary floats are limited to 40 exponent and 38 mantissa trits"
+    );
+
+    /// Builds a finite, nonzero float from its exponent and mantissa.
+    ///
+    /// * `exponent` The power of three the mantissa is multiplied by
+    /// * `mantissa` The trits of the mantissa, with the radix point after the
+    ///   leading trit
+    ///
+    /// **returns** The float, or `None` if the mantissa isn't normalised or
+    /// the exponent is one of the reserved extremes
+    pub fn from_parts(exponent: Number<E>, mantissa: Number<M>) -> Option<Self> {
+        let reserved = exponent == Number::<E>::MIN || exponent == Number::<E>::MAX;
+        if reserved || mantissa.trits()[0] == Trit::Zero {
+            return None;
+        }
+        Some(TernaryFloat { exponent, mantissa })
+    }
+
+    /// The power of three the mantissa is multiplied by. Zero, infinities and
+    /// NaN use the reserved extremes.
+    pub fn exponent(&self) -> Number<E> {
+        self.exponent
+    }
+
+    /// The mantissa trits, with the radix point after the leading trit.
+    pub fn mantissa(&self) -> Number<M> {
+        self.mantissa
+    }
+
+    /// Whether the value is not a number.
+    pub fn is_nan(&self) -> bool {
+        self.exponent == Number::<E>::MAX && self.mantissa.is_zero()
+    }
+
+    /// Whether the value is positive or negative infinity.
+    pub fn is_infinite(&self) -> bool {
+        self.exponent == Number::<E>::MAX && !self.mantissa.is_zero()
+    }
+
+    /// Whether the value is neither infinite nor NaN.
+    pub fn is_finite(&self) -> bool {
+        self.exponent != Number::<E>::MAX
+    }
+
+    /// Whether the value is zero, which has no sign.
+    pub fn is_zero(&self) -> bool {
+        self.exponent == Number::<E>::MIN
+    }
+
+    /// The sign of the value, which is zero for both zero and NaN.
+    pub fn signum(&self) -> Trit {
+        self.mantissa.signum()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.signum() == Trit::Neg
+    }
+
+    /// The absolute value, which negates a negative mantissa so can't round.
+    pub fn abs(self) -> Self {
+        if self.is_negative() { -self } else { self }
+    }
+
+    /// Round to the nearest integer, by dropping the mantissa trits after the
+    /// radix point. Balanced ternary can never be exactly halfway between two
+    /// integers.
+    pub fn round(self) -> Self {
+        let Some((mantissa, scale)) = self.parts() else {
+            return self;
+        };
+        if scale >= 0 {
+            return self;
+        }
+        match u32::try_from(-scale) {
+            Ok(dropped) if dropped <= M as u32 => Self::from_ratio(div_nearest(mantissa, 3_i128.pow(dropped)), 1, 0),
+            // Everything is after the radix point, and less than a half
+            _ => Self::ZERO
+        }
+    }
+
+    /// Round to the nearest value with fewer mantissa trits, to study the
+    /// effect of precision.
+    ///
+    /// * `trits` The number of mantissa trits to keep
+    ///
+    /// **returns** The rounded value, which is unchanged if `trits` is M or more
+    pub fn round_to_precision(self, trits: usize) -> Self {
+        match self.parts() {
+            Some((mantissa, scale)) if trits < M => {
+                let dropped = (M - trits) as u32;
+                Self::from_ratio(div_nearest(mantissa, 3_i128.pow(dropped)), 1, scale + dropped as i128)
+            },
+            _ => self
+        }
+    }
+
+    /// Convert to an `f64`, which is exact if the mantissa and the power of
+    /// three it is scaled by both fit in the precision of an `f64`.
+    pub fn to_f64(self) -> f64 {
+        if self.is_nan() {
+            return f64::NAN;
+        }
+        let Some((mantissa, _)) = self.parts() else {
+            return match self.signum() {
+                Trit::Pos => f64::INFINITY,
+                Trit::Neg => f64::NEG_INFINITY,
+                Trit::Zero => 0.0
+            };
+        };
+        // Exponents beyond the range of an i32 are well beyond that of an f64
+        let exponent = i128::try_from(self.exponent).expect("Exponent fits in an i128");
+        let exponent = exponent.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+        mantissa as f64 / 3_f64.powi(M as i32 - 1) * 3_f64.powi(exponent)
+    }
+
+    /// Convert from an `f64`, rounding to the nearest value up to the
+    /// precision of the `f64` arithmetic used to scale it. Magnitudes too
+    /// large to represent become infinities, and those too small become zero.
+    pub fn from_f64(value: f64) -> Self {
+        if value.is_nan() {
+            return Self::NAN;
+        }
+        if value.is_infinite() {
+            return Self::infinity(value < 0.0);
+        }
+        if value == 0.0 {
+            return Self::ZERO;
+        }
+
+        // Scale into the range of a normalised mantissa, 3^(M-1)/2 to 3^M/2,
+        // in two steps so that the power of three doesn't overflow for
+        // subnormal values
+        let lower = 3_f64.powi(M as i32 - 1) / 2.0;
+        let mut exponent = value.abs().log(3.0).round() as i32;
+        let scale = |exponent: i32| value * 3_f64.powi(-exponent / 2) * 3_f64.powi(M as i32 - 1 - (exponent - exponent / 2));
+        while scale(exponent).abs() >= 3.0 * lower {
+            exponent += 1;
+        }
+        while scale(exponent).abs() < lower {
+            exponent -= 1;
+        }
+        Self::from_ratio(scale(exponent).round() as i128, 1, exponent as i128 - (M as i128 - 1))
+    }
+
+    fn infinity(negative: bool) -> Self {
+        if negative { Self::NEG_INFINITY } else { Self::INFINITY }
+    }
+
+    /// The integer mantissa and the power of three it is scaled by, so that the
+    /// value is `mantissa × 3^scale`, or `None` for zero, infinities and NaN.
+    fn parts(self) -> Option<(i128, i128)> {
+        let () = Self::SIZE_CHECK;
+        if self.is_zero() || !self.is_finite() {
+            return None;
+        }
+        let mantissa = i128::try_from(self.mantissa).expect("Mantissa fits in an i128");
+        let exponent = i128::try_from(self.exponent).expect("Exponent fits in an i128");
+        Some((mantissa, exponent - (M as i128 - 1)))
+    }
+
+    /// The float nearest to `numerator / denominator × 3^scale`.
+    ///
+    /// * `numerator` The numerator, which must be within 2M+4 trits
+    /// * `denominator` The positive denominator, which must be within M trits
+    /// * `scale` The power of three to multiply by
+    ///
+    /// **returns** The rounded value, or zero or an infinity if it is out of
+    /// the exponent range
+    fn from_ratio(numerator: i128, denominator: i128, scale: i128) -> Self {
+        let () = Self::SIZE_CHECK;
+        if numerator == 0 {
+            return Self::ZERO;
+        }
+
+        // Scale the ratio by 3^shift so that it rounds to an M trit mantissa
+        // with a nonzero leading trit, which is when its magnitude is in the
+        // range 3^(M-1)/2 to 3^M/2
+        let lower = 3_i128.pow(M as u32 - 1);
+        let (mut numerator, mut denominator, mut shift) = (numerator, denominator, 0);
+        loop {
+            if 2 * numerator.abs() < lower * denominator {
+                numerator *= 3;
+                shift += 1;
+            } else if 2 * numerator.abs() >= 3 * lower * denominator {
+                denominator *= 3;
+                shift -= 1;
+            } else {
+                break;
+            }
+        }
+
+        let mantissa = div_nearest(numerator, denominator);
+        let exponent = scale - shift + (M as i128 - 1);
+        let largest = i128::try_from(Number::<E>::MAX).expect("Exponent fits in an i128");
+        if exponent >= largest {
+            return Self::infinity(mantissa < 0);
+        }
+        if exponent <= -largest {
+            return Self::ZERO;
+        }
+
+        TernaryFloat {
+            exponent: Number::<E>::try_from(exponent).expect("Exponent is in range"),
+            mantissa: Number::<M>::try_from(mantissa).expect("Mantissa is normalised")
+        }
+    }
+}
+
+/// A mantissa of `+.00…`, representing one.
+const fn leading_one<const M: usize>() -> Number<M> {
+    let mut trits = [Trit::Zero; M];
+    trits[0] = Trit::Pos;
+    Number::<M>::from_trits(trits)
+}
+
+/// The largest unreserved exponent with the given sign, with every trit but
+/// the last equal to that sign.
+const fn largest_exponent<const E: usize>(sign: Trit) -> Number<E> {
+    let mut trits = [sign; E];
+    trits[E - 1] = Trit::Zero;
+    Number::<E>::from_trits(trits)
+}
+
+impl<const E: usize, const M: usize> PartialEq for TernaryFloat<E, M> {
+    /// Values are equal when their encodings are, as every value but NaN has
+    /// a single encoding, except that NaN is never equal to anything.
+    fn eq(&self, other: &Self) -> bool {
+        !self.is_nan() && self.exponent == other.exponent && self.mantissa == other.mantissa
+    }
+}
+
+impl<const E: usize, const M: usize> PartialOrd for TernaryFloat<E, M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        // A normalised mantissa is less than three times the smallest one, so
+        // magnitudes are ordered by exponent before mantissa, and infinities
+        // have the largest exponent
+        Some(match (self.signum(), other.signum()) {
+            (Trit::Neg, Trit::Neg) => other.exponent.cmp(&self.exponent).then(self.mantissa.cmp(&other.mantissa)),
+            (sign, other_sign) if sign == other_sign => self.exponent.cmp(&other.exponent).then(self.mantissa.cmp(&other.mantissa)),
+            (sign, other_sign) => sign.cmp(&other_sign)
+        })
+    }
+}
+
+impl<const E: usize, const M: usize> Add for TernaryFloat<E, M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        if self.is_nan() || rhs.is_nan() || (self.is_infinite() && rhs == -self) {
+            return Self::NAN;
+        }
+        let (Some((lhs_mantissa, lhs_scale)), Some((rhs_mantissa, rhs_scale))) = (self.parts(), rhs.parts()) else {
+            // An infinity dominates, and zero is the identity
+            return if self.is_infinite() || rhs.is_zero() { self } else { rhs };
+        };
+
+        let (larger, (large_mantissa, large_scale), (small_mantissa, small_scale)) = if lhs_scale >= rhs_scale {
+            (self, (lhs_mantissa, lhs_scale), (rhs_mantissa, rhs_scale))
+        } else {
+            (rhs, (rhs_mantissa, rhs_scale), (lhs_mantissa, lhs_scale))
+        };
+        // Beyond this the smaller value is under a tenth of half a unit in the
+        // last place of the larger, so can't affect its rounding
+        let difference = large_scale - small_scale;
+        if difference > M as i128 + 3 {
+            return larger;
+        }
+
+        let sum = large_mantissa * 3_i128.pow(difference as u32) + small_mantissa;
+        Self::from_ratio(sum, 1, small_scale)
+    }
+}
+
+impl<const E: usize, const M: usize> AddAssign for TernaryFloat<E, M> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const E: usize, const M: usize> Sub for TernaryFloat<E, M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<const E: usize, const M: usize> SubAssign for TernaryFloat<E, M> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const E: usize, const M: usize> Mul for TernaryFloat<E, M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self.parts(), rhs.parts()) {
+            (Some((lhs_mantissa, lhs_scale)), Some((rhs_mantissa, rhs_scale))) =>
+                Self::from_ratio(lhs_mantissa * rhs_mantissa, 1, lhs_scale + rhs_scale),
+            _ if self.is_nan() || rhs.is_nan() => Self::NAN,
+            // ∞ × 0 is undefined, otherwise the sign of an infinite result is
+            // the product of the signs
+            _ if (self.is_infinite() && rhs.is_zero()) || (self.is_zero() && rhs.is_infinite()) => Self::NAN,
+            _ if self.is_infinite() || rhs.is_infinite() => Self::infinity(self.is_negative() != rhs.is_negative()),
+            _ => Self::ZERO
+        }
+    }
+}
+
+impl<const E: usize, const M: usize> MulAssign for TernaryFloat<E, M> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const E: usize, const M: usize> Div for TernaryFloat<E, M> {
+    type Output = Self;
+
+    /// Divide, rounding to the nearest value. As zero is unsigned, dividing a
+    /// nonzero value by zero gives an infinity with the sign of the dividend.
+    fn div(self, divisor: Self) -> Self::Output {
+        match (self.parts(), divisor.parts()) {
+            (Some((lhs_mantissa, lhs_scale)), Some((rhs_mantissa, rhs_scale))) => {
+                let sign = rhs_mantissa.signum();
+                Self::from_ratio(sign * lhs_mantissa, sign * rhs_mantissa, lhs_scale - rhs_scale)
+            },
+            _ if self.is_nan() || divisor.is_nan() => Self::NAN,
+            _ if self.is_infinite() && divisor.is_infinite() => Self::NAN,
+            _ if self.is_zero() && divisor.is_zero() => Self::NAN,
+            _ if divisor.is_zero() => Self::infinity(self.is_negative()),
+            _ if self.is_infinite() => Self::infinity(self.is_negative() != divisor.is_negative()),
+            _ => Self::ZERO
+        }
+    }
+}
+
+impl<const E: usize, const M: usize> DivAssign for TernaryFloat<E, M> {
+    fn div_assign(&mut self, divisor: Self) {
+        *self = *self / divisor;
+    }
+}
+
+impl<const E: usize, const M: usize> Neg for TernaryFloat<E, M> {
+    type Output = Self;
+
+    /// Negation flips the mantissa trits, so is exact.
+    fn neg(self) -> Self::Output {
+        TernaryFloat { exponent: self.exponent, mantissa: -self.mantissa }
+    }
+}
+
+impl<const E: usize, const M: usize> From<f64> for TernaryFloat<E, M> {
+    fn from(value: f64) -> Self {
+        Self::from_f64(value)
+    }
+}
+
+impl<const E: usize, const M: usize> From<TernaryFloat<E, M>> for f64 {
+    fn from(value: TernaryFloat<E, M>) -> Self {
+        value.to_f64()
+    }
+}
+
+impl<const E: usize, const M: usize> fmt::Display for TernaryFloat<E, M> {
+    /// Displays the mantissa with its radix point and the exponent in trits,
+    /// e.g. `+.-00e00+ (2)` for two, followed by the value in decimal.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_finite() {
+            return write!(f, "{}", self.to_f64());
+        }
+
+        let exponent = if self.is_zero() { Number::<E>::ZERO } else { self.exponent };
+        let (leading, rest) = self.mantissa.trits().split_first().expect("Mantissa has a trit");
+        write!(f, "{}.", leading)?;
+        for trit in rest {
+            write!(f, "{}", trit)?;
+        }
+        write!(f, "e")?;
+        for trit in exponent.trits() {
+            write!(f, "{}", trit)?;
+        }
+        write!(f, " ({})", self.to_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type T = TernaryFloat<4, 9>;
+
+    fn float(value: f64) -> T {
+        T::from_f64(value)
+    }
+
+    #[test]
+    fn normalised_mantissas() {
+        // Two is 2/3 × 3, with a negative second trit
+        let two = float(2.0);
+        assert_eq!(two.exponent(), Number::<4>::ONE);
+        assert_eq!(two.mantissa(), Number::<9>::from("+-0000000"));
+        assert_eq!(two.to_string(), "+.-0000000e000+ (2)");
+
+        // The leading trit can be negative
+        assert_eq!(float(-2.0).mantissa(), Number::<9>::from("-+0000000"));
+        assert_eq!(float(1.4).exponent(), Number::<4>::ZERO);
+        assert_eq!(float(1.6).exponent(), Number::<4>::ONE);
+
+        assert_eq!(T::from_parts(Number::<4>::ZERO, Number::<9>::from("0+")), None);
+        assert_eq!(T::from_parts(Number::<4>::MIN, Number::<9>::from("+00000000")), None);
+        assert_eq!(T::from_parts(Number::<4>::ZERO, Number::<9>::from("+00000000")), Some(T::ONE));
+    }
+
+    #[test]
+    fn special_values() {
+        let one = T::ONE;
+        assert!(T::NAN.is_nan() && !T::NAN.is_finite());
+        assert!(T::INFINITY.is_infinite() && T::NEG_INFINITY.is_infinite());
+        assert!(T::ZERO.is_zero() && T::ZERO.is_finite());
+        assert_ne!(T::NAN, T::NAN);
+
+        assert_eq!(one / T::ZERO, T::INFINITY);
+        assert_eq!(-one / T::ZERO, T::NEG_INFINITY);
+        assert!((T::ZERO / T::ZERO).is_nan());
+        assert!((T::INFINITY - T::INFINITY).is_nan());
+        assert!((T::INFINITY * T::ZERO).is_nan());
+        assert!((T::ZERO * T::NEG_INFINITY).is_nan());
+        // Only ∞ × 0 is undefined, other products with zero are zero
+        assert_eq!(T::ZERO * one, T::ZERO);
+        assert_eq!(float(-2.5) * T::ZERO, T::ZERO);
+        assert_eq!(T::ZERO * T::ZERO, T::ZERO);
+        let mut value = T::ZERO;
+        value *= float(2.0);
+        assert_eq!(value, T::ZERO);
+        assert!((T::INFINITY / T::NEG_INFINITY).is_nan());
+        assert_eq!(T::INFINITY + one, T::INFINITY);
+        assert_eq!(T::NEG_INFINITY * -one, T::INFINITY);
+        assert_eq!(one / T::NEG_INFINITY, T::ZERO);
+        assert_eq!(-T::ZERO, T::ZERO);
+
+        assert_eq!(T::INFINITY.to_string(), "inf");
+        assert_eq!(T::ZERO.to_string(), "0.00000000e0000 (0)");
+        assert!(float(f64::NAN).is_nan());
+        assert_eq!(float(f64::NEG_INFINITY), T::NEG_INFINITY);
+    }
+
+    #[test]
+    fn overflow_and_underflow() {
+        // Exponents of 4 trits range up to 39, with 40 reserved
+        assert_eq!(T::MAX.exponent(), Number::<4>::from("+++0"));
+        assert_eq!(T::MAX * float(3.0), T::INFINITY);
+        assert_eq!(T::MIN - T::MAX, T::NEG_INFINITY);
+        assert_eq!(T::MIN_POSITIVE / float(3.0), T::ZERO);
+        assert_eq!(float(1e30), T::INFINITY);
+        assert_eq!(float(-1e-30), T::ZERO);
+    }
+
+    #[test]
+    fn exact_thirds() {
+        let third = T::ONE / float(3.0);
+        assert_eq!(third.mantissa(), Number::<9>::from("+00000000"));
+        assert_eq!(third.exponent(), Number::<4>::NEG_ONE);
+        assert_eq!(third + third + third, T::ONE);
+        assert_eq!(T::ONE + T::EPSILON - T::ONE, T::EPSILON);
+    }
+
+    #[test]
+    fn rounds_to_nearest() {
+        // A half is +.---…, so its nearest representation is just over a half
+        // and, unlike in binary, doubling it overshoots one by a unit in the
+        // last place
+        let half = T::ONE / float(2.0);
+        assert_eq!(half.mantissa(), Number::<9>::from("+--------"));
+        assert!(half.to_f64() > 0.5);
+        assert_eq!(float(0.5), half);
+        assert_eq!(half + half, T::ONE + T::EPSILON);
+
+        // Ties only arise in division and round away from zero: 1/2 with 2
+        // mantissa trits lies halfway between 1/3 and 2/3
+        type Narrow = TernaryFloat<3, 2>;
+        let narrow = |value| Narrow::from_f64(value);
+        assert_eq!(narrow(1.0) / narrow(2.0), narrow(2.0 / 3.0));
+        assert_eq!(narrow(-1.0) / narrow(2.0), narrow(-2.0 / 3.0));
+
+        // Small addends beyond half a unit in the last place are absorbed
+        assert_eq!(T::ONE + T::EPSILON / float(3.0), T::ONE);
+        assert_eq!(T::ONE + T::EPSILON / float(2.0), T::ONE + T::EPSILON);
+    }
+
+    #[test]
+    fn integer_and_precision_rounding() {
+        assert_eq!(float(2.4).round(), float(2.0));
+        assert_eq!(float(2.6).round(), float(3.0));
+        assert_eq!(float(-2.6).round(), float(-3.0));
+        assert_eq!(float(0.4).round(), T::ZERO);
+        assert_eq!(float(1e-10).round(), T::ZERO);
+        assert_eq!(float(1234.0).round(), float(1234.0));
+
+        let value = float(2.0) + T::EPSILON * float(3.0);
+        assert_eq!(value.round_to_precision(3), float(2.0));
+        assert_eq!(value.round_to_precision(9), value);
+        assert_eq!(value.round_to_precision(0), T::ZERO);
+    }
+
+    #[test]
+    fn ordering() {
+        let values = [T::NEG_INFINITY, T::MIN, float(-2.0), float(-0.6), T::ZERO, T::MIN_POSITIVE, float(0.6), float(2.0), T::MAX, T::INFINITY];
+        for pair in values.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(T::NAN.partial_cmp(&T::ONE), None);
+        assert!(!T::NAN.lt(&T::ONE) && !T::NAN.ge(&T::ONE));
+    }
+
+    #[test]
+    fn f64_round_trips() {
+        // Converting back is exact, and within half a unit in the last place
+        for value in [1.0, -7.25, 0.1, std::f64::consts::PI, 1e-15, -123456.0, 1e18] {
+            let converted = float(value).to_f64();
+            let exponent = float(value).exponent();
+            let ulp = 3_f64.powi(i32::from(exponent) - 8);
+            assert!((converted - value).abs() <= ulp / 2.0, "{} converted to {}", value, converted);
+            assert_eq!(float(converted), float(value));
+        }
+    }
+
+    #[test]
+    fn arithmetic_error_against_f64() {
+        let (a, b) = (0.1, 0.2);
+        let sum = (float(a) + float(b)).to_f64();
+        assert!((sum - 0.3).abs() < 1e-4);
+        let product = (float(3.0) * float(7.0) / float(7.0)).to_f64();
+        assert_eq!(product, 3.0);
+        assert_eq!((float(81.0) * float(-27.0)).to_f64(), -2187.0);
+    }
+}
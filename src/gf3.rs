@@ -0,0 +1,508 @@
+//! Finite field arithmetic over GF(3) and its extension fields GF(3^k).
+//!
+//! The balanced trits -1, 0 and 1 are exactly the elements of GF(3), where
+//! arithmetic is done modulo 3. Unlike `Trit::add`, field addition has no
+//! carry: 1 + 1 is -1 (2 modulo 3), and the carry that `Trit::add` would
+//! produce is simply dropped.
+//!
+//! Elements of GF(3^k) are polynomials of degree less than k with trit
+//! coefficients, multiplied modulo an irreducible polynomial of degree k. The
+//! coefficients are stored highest degree first, matching the trit order of
+//! `Number`.
+//!
+//! `Poly3` provides general polynomials over GF(3), with factorisation and
+//! the primitive polynomials used for linear feedback shift registers.
+
+mod poly;
+
+pub use poly::Poly3;
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::trit::Trit;
+
+/// Add two trits modulo 3.
+pub fn add(lhs: Trit, rhs: Trit) -> Trit {
+    lhs.add(&rhs).result
+}
+
+/// Subtract one trit from another modulo 3.
+pub fn sub(lhs: Trit, rhs: Trit) -> Trit {
+    add(lhs, rhs.negate())
+}
+
+/// Multiply two trits, which is the same modulo 3 as over the integers.
+pub fn mul(lhs: Trit, rhs: Trit) -> Trit {
+    // XOR is the negated product
+    !(lhs ^ rhs)
+}
+
+/// The multiplicative inverse of a trit. Both nonzero trits are their own
+/// inverses, as 1 × 1 and -1 × -1 are both 1.
+///
+/// **returns** The inverse, or `None` for zero
+pub fn inverse(trit: Trit) -> Option<Trit> {
+    (trit != Trit::Zero).then_some(trit)
+}
+
+/// Divide one trit by another modulo 3.
+///
+/// **returns** The quotient, or `None` if the divisor is zero
+pub fn div(lhs: Trit, rhs: Trit) -> Option<Trit> {
+    inverse(rhs).map(|inverse| mul(lhs, inverse))
+}
+
+/// An irreducible monic polynomial of degree K over GF(3), defining the field
+/// GF(3^K). The leading coefficient is always one, so only the K lower
+/// coefficients are stored, highest degree first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Modulus<const K: usize>([Trit; K]);
+
+/// Error returned when a polynomial cannot be used as a modulus.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReducibleError;
+
+impl fmt::Display for ReducibleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "polynomial is reducible so does not define a field")
+    }
+}
+
+impl Error for ReducibleError {}
+
+impl<const K: usize> Modulus<K> {
+    /// Builds the modulus `x^K + c[0]x^(K-1) + … + c[K-1]`, checking that it is
+    /// irreducible.
+    ///
+    /// * `coefficients` The coefficients below the leading x^K, highest degree
+    ///   first
+    ///
+    /// **returns** The modulus, or an error if the polynomial has a factor
+    pub fn new(coefficients: [Trit; K]) -> Result<Self, ReducibleError> {
+        let modulus = Modulus(coefficients);
+        if modulus.polynomial().is_irreducible() {
+            Ok(modulus)
+        } else {
+            Err(ReducibleError)
+        }
+    }
+
+    /// The first irreducible polynomial of degree K, counting up through the
+    /// lower coefficients in balanced ternary from all negative.
+    pub fn first() -> Self {
+        let polynomial = Poly3::monic_polynomials(K)
+            .find(Poly3::is_irreducible)
+            .expect("There are irreducible polynomials of every degree");
+        let mut coefficients = [Trit::Zero; K];
+        coefficients.iter_mut().enumerate().for_each(|(idx, coefficient)| *coefficient = polynomial.coefficient(K - 1 - idx));
+        Modulus(coefficients)
+    }
+
+    /// The coefficients below the leading x^K, highest degree first.
+    pub fn coefficients(&self) -> &[Trit; K] {
+        &self.0
+    }
+
+    /// The full polynomial, including the leading x^K.
+    pub fn polynomial(&self) -> Poly3 {
+        let coefficients: Vec<Trit> = [Trit::Pos].into_iter().chain(self.0).collect();
+        Poly3::new(&coefficients)
+    }
+}
+
+/// An element of the field GF(3^K), a polynomial of degree less than K with
+/// trit coefficients, together with the modulus that defines the field.
+///
+/// Combining elements with different moduli panics, as they belong to
+/// different fields.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GF3k<const K: usize> {
+    coefficients: [Trit; K],
+    modulus: Modulus<K>
+}
+
+impl<const K: usize> GF3k<K> {
+    /// Builds a field element from its coefficients.
+    ///
+    /// * `coefficients` The coefficients from x^(K-1) down to the constant term
+    /// * `modulus` The irreducible polynomial defining the field
+    ///
+    /// **returns** The field element
+    pub fn new(coefficients: [Trit; K], modulus: Modulus<K>) -> Self {
+        GF3k { coefficients, modulus }
+    }
+
+    /// The additive identity, with every coefficient zero.
+    pub fn zero(modulus: Modulus<K>) -> Self {
+        GF3k { coefficients: [Trit::Zero; K], modulus }
+    }
+
+    /// The multiplicative identity, the constant polynomial 1.
+    pub fn one(modulus: Modulus<K>) -> Self {
+        let mut coefficients = [Trit::Zero; K];
+        coefficients[K - 1] = Trit::Pos;
+        GF3k { coefficients, modulus }
+    }
+
+    /// The element x, which generates the whole multiplicative group when the
+    /// modulus is a primitive polynomial.
+    pub fn x(modulus: Modulus<K>) -> Self {
+        GF3k::one(modulus).times_x()
+    }
+
+    /// The coefficients from x^(K-1) down to the constant term.
+    pub fn coefficients(&self) -> &[Trit; K] {
+        &self.coefficients
+    }
+
+    /// The irreducible polynomial defining the field this element belongs to.
+    pub fn modulus(&self) -> Modulus<K> {
+        self.modulus
+    }
+
+    /// Whether this is the additive identity.
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.iter().all(|trit| *trit == Trit::Zero)
+    }
+
+    /// Raise to a power by repeated squaring.
+    pub fn pow(self, exponent: u64) -> Self {
+        let mut result = GF3k::one(self.modulus);
+        let mut base = self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// The multiplicative inverse, a^(3^K - 2). In base 3 that exponent is a
+    /// one followed by K - 1 twos, so it is built from successive cubes of the
+    /// element without needing to hold 3^K.
+    ///
+    /// **returns** The inverse, or `None` for zero
+    pub fn inverse(self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut power = self;
+        let mut result = self;
+        for _ in 1..K {
+            power = power * power * power;
+            result *= power * power;
+        }
+        Some(result)
+    }
+
+    /// Divide by multiplying with the inverse of the divisor.
+    ///
+    /// **returns** The quotient, or `None` if the divisor is zero
+    pub fn checked_div(self, divisor: Self) -> Option<Self> {
+        divisor.inverse().map(|inverse| self * inverse)
+    }
+
+    /// Multiply by x, reducing modulo the field's polynomial: the coefficient
+    /// shifted up to x^K is replaced by the negated lower coefficients of the
+    /// modulus, as x^K is congruent to them.
+    fn times_x(self) -> Self {
+        let mut coefficients = [Trit::Zero; K];
+        coefficients[..K - 1].copy_from_slice(&self.coefficients[1..]);
+        let overflow = self.coefficients[0];
+        for (coefficient, modulus) in coefficients.iter_mut().zip(self.modulus.0) {
+            *coefficient = sub(*coefficient, mul(overflow, modulus));
+        }
+        GF3k { coefficients, modulus: self.modulus }
+    }
+
+    fn check_field(&self, other: &Self) {
+        if self.modulus != other.modulus {
+            panic!("Cannot combine elements of different fields")
+        }
+    }
+}
+
+impl<const K: usize> Add for GF3k<K> {
+    type Output = Self;
+
+    /// Add coefficient by coefficient, modulo 3.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.check_field(&rhs);
+        let mut sum = self;
+        sum.coefficients.iter_mut().zip(rhs.coefficients).for_each(|(lhs, rhs)| *lhs = add(*lhs, rhs));
+        sum
+    }
+}
+
+impl<const K: usize> AddAssign for GF3k<K> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const K: usize> Sub for GF3k<K> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<const K: usize> SubAssign for GF3k<K> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const K: usize> Mul for GF3k<K> {
+    type Output = Self;
+
+    /// Multiply with Horner's method, working down the coefficients of the
+    /// right hand side and multiplying by x modulo the field's polynomial at
+    /// each step.
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.check_field(&rhs);
+        let mut product = GF3k::zero(self.modulus);
+        for coefficient in rhs.coefficients {
+            product = product.times_x();
+            match coefficient {
+                Trit::Pos => product += self,
+                Trit::Neg => product -= self,
+                Trit::Zero => ()
+            }
+        }
+        product
+    }
+}
+
+impl<const K: usize> MulAssign for GF3k<K> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const K: usize> Div for GF3k<K> {
+    type Output = Self;
+
+    /// If the divisor is zero then the program will exit with an error message.
+    fn div(self, divisor: Self) -> Self::Output {
+        match self.checked_div(divisor) {
+            Some(quotient) => quotient,
+            None => panic!("Attempt to divide by zero")
+        }
+    }
+}
+
+impl<const K: usize> DivAssign for GF3k<K> {
+    fn div_assign(&mut self, divisor: Self) {
+        *self = *self / divisor;
+    }
+}
+
+impl<const K: usize> Neg for GF3k<K> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        GF3k { coefficients: self.coefficients.map(Trit::negate), modulus: self.modulus }
+    }
+}
+
+impl<const K: usize> fmt::Display for GF3k<K> {
+    /// Displays the element as a polynomial, e.g. `x^2 - x + 1`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_polynomial(f, &self.coefficients)
+    }
+}
+
+/// Which end of a trit slice a counter treats as least significant.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TritOrder {
+    LeastSignificantFirst,
+    LeastSignificantLast
+}
+
+/// Step a slice of trits to the next combination, treating it as an unsigned
+/// counter from all negative to all positive.
+///
+/// **returns** `false` once the counter wraps back around to all negative
+fn increment(trits: &mut [Trit], order: TritOrder) -> bool {
+    // Each positive trit wraps around and carries into the next one, and the
+    // first trit that doesn't wrap ends the step
+    let step = |trit: &mut Trit| {
+        *trit = match trit {
+            Trit::Neg => Trit::Zero,
+            Trit::Zero => Trit::Pos,
+            Trit::Pos => Trit::Neg
+        };
+        *trit != Trit::Neg
+    };
+    match order {
+        TritOrder::LeastSignificantFirst => trits.iter_mut().any(step),
+        TritOrder::LeastSignificantLast => trits.iter_mut().rev().any(step)
+    }
+}
+
+/// Every combination of `length` trits, counting up from all negative.
+pub(crate) fn trit_vectors(length: usize, order: TritOrder) -> impl Iterator<Item = Vec<Trit>> {
+    let mut next = Some(vec![Trit::Neg; length]);
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        let mut following = current.clone();
+        if increment(&mut following, order) {
+            next = Some(following);
+        }
+        Some(current)
+    })
+}
+
+/// Write a polynomial in x, given its coefficients highest degree first.
+fn write_polynomial(f: &mut fmt::Formatter, coefficients: &[Trit]) -> fmt::Result {
+    let terms: Vec<(usize, Trit)> = coefficients.iter().enumerate()
+        .map(|(idx, coefficient)| (coefficients.len() - 1 - idx, *coefficient))
+        .filter(|(_, coefficient)| *coefficient != Trit::Zero)
+        .collect();
+    if terms.is_empty() {
+        return write!(f, "0");
+    }
+
+    for (idx, (degree, coefficient)) in terms.into_iter().enumerate() {
+        match (idx, coefficient) {
+            (0, Trit::Neg) => write!(f, "-")?,
+            (0, _) => (),
+            (_, Trit::Neg) => write!(f, " - ")?,
+            (_, _) => write!(f, " + ")?
+        }
+        match degree {
+            0 => write!(f, "1")?,
+            1 => write!(f, "x")?,
+            degree => write!(f, "x^{}", degree)?
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number::Number;
+
+    const ALL: [Trit; 3] = [Trit::Neg, Trit::Zero, Trit::Pos];
+
+    fn element<const K: usize>(trits: &str, modulus: Modulus<K>) -> GF3k<K> {
+        GF3k::new(*Number::<K>::from(trits).trits(), modulus)
+    }
+
+    #[test]
+    fn counting_trit_vectors() {
+        let strings = |order| -> Vec<String> {
+            trit_vectors(2, order).map(|trits| trits.iter().map(|trit| trit.to_string()).collect()).collect()
+        };
+        assert_eq!(strings(TritOrder::LeastSignificantLast), ["--", "-0", "-+", "0-", "00", "0+", "+-", "+0", "++"]);
+        assert_eq!(strings(TritOrder::LeastSignificantFirst), ["--", "0-", "+-", "-0", "00", "+0", "-+", "0+", "++"]);
+        assert_eq!(trit_vectors(0, TritOrder::LeastSignificantFirst).count(), 1);
+    }
+
+    #[test]
+    fn trit_field_arithmetic() {
+        let value = |trit: Trit| i8::from(trit).rem_euclid(3);
+        for lhs in ALL {
+            for rhs in ALL {
+                assert_eq!(value(add(lhs, rhs)), (value(lhs) + value(rhs)) % 3);
+                assert_eq!(value(sub(lhs, rhs)), (value(lhs) - value(rhs)).rem_euclid(3));
+                assert_eq!(value(mul(lhs, rhs)), (value(lhs) * value(rhs)) % 3);
+                if rhs != Trit::Zero {
+                    assert_eq!(mul(div(lhs, rhs).unwrap(), rhs), lhs);
+                }
+            }
+        }
+        // Unlike Trit::add there is no carry
+        assert_eq!(add(Trit::Pos, Trit::Pos), Trit::Neg);
+        assert_eq!(inverse(Trit::Neg), Some(Trit::Neg));
+        assert_eq!(inverse(Trit::Zero), None);
+        assert_eq!(div(Trit::Pos, Trit::Zero), None);
+    }
+
+    #[test]
+    fn irreducible_moduli() {
+        // x^2 + 1 has no roots modulo 3, but x^2 - 1 and x^2 + x + 1 = (x - 1)^2
+        // factor
+        assert!(Modulus::new([Trit::Zero, Trit::Pos]).is_ok());
+        assert_eq!(Modulus::new([Trit::Zero, Trit::Neg]), Err(ReducibleError));
+        assert_eq!(Modulus::new([Trit::Pos, Trit::Pos]), Err(ReducibleError));
+        // x^4 + 1 = (x^2 + x - 1)(x^2 - x - 1) has no roots but still factors
+        assert_eq!(Modulus::new([Trit::Zero, Trit::Zero, Trit::Zero, Trit::Pos]), Err(ReducibleError));
+        assert!(Modulus::new([Trit::Zero, Trit::Neg, Trit::Pos]).is_ok());
+
+        let first = Modulus::<3>::first();
+        assert_eq!(first.coefficients(), &[Trit::Neg, Trit::Neg, Trit::Neg]);
+        assert!(Modulus::new(*first.coefficients()).is_ok());
+    }
+
+    #[test]
+    fn gf9_arithmetic() {
+        // GF(9) as polynomials modulo x^2 + 1, so x^2 = -1
+        let modulus = Modulus::new([Trit::Zero, Trit::Pos]).unwrap();
+        let x = GF3k::x(modulus);
+        assert_eq!(x * x, element("-", modulus));
+        assert_eq!(element("++", modulus) * element("+-", modulus), element("+", modulus));
+        assert_eq!(element("++", modulus) + element("+-", modulus), element("-0", modulus));
+        assert_eq!(element("++", modulus) - element("+-", modulus), element("-", modulus));
+        assert_eq!(x.pow(4), GF3k::one(modulus));
+        assert_eq!(x.pow(0), GF3k::one(modulus));
+        assert_eq!(element("+-", modulus).to_string(), "x - 1");
+    }
+
+    #[test]
+    fn gf27_is_a_field() {
+        // GF(27) modulo x^3 - x + 1
+        let modulus = Modulus::new([Trit::Zero, Trit::Neg, Trit::Pos]).unwrap();
+        let one = GF3k::one(modulus);
+        let elements: Vec<GF3k<3>> = (-13..=13)
+            .map(|value| GF3k::new(*Number::<3>::from_i64(value).trits(), modulus))
+            .collect();
+
+        for a in &elements {
+            if a.is_zero() {
+                assert_eq!(a.inverse(), None);
+                continue;
+            }
+            let inverse = a.inverse().unwrap();
+            assert_eq!(*a * inverse, one, "{} × {}", a, inverse);
+            assert_eq!(a.pow(26), one);
+            for b in &elements {
+                assert_eq!(*a * *b, *b * *a);
+                assert_eq!((*a * *b) / *a, *b);
+                for c in elements.iter().step_by(5) {
+                    assert_eq!(*a * (*b + *c), *a * *b + *a * *c);
+                }
+            }
+        }
+
+        // x is primitive, generating all 26 nonzero elements
+        let x = GF3k::x(modulus);
+        assert_eq!(x.pow(13), -one);
+        assert_eq!(x.pow(3), element("+-", modulus));
+        assert_eq!(element("-0+", modulus).to_string(), "-x^2 + 1");
+        assert_eq!(GF3k::zero(modulus).to_string(), "0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine elements of different fields")]
+    fn mixing_fields() {
+        let first = Modulus::new([Trit::Zero, Trit::Pos]).unwrap();
+        let second = Modulus::new([Trit::Pos, Trit::Neg]).unwrap();
+        let _ = GF3k::one(first) + GF3k::one(second);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempt to divide by zero")]
+    fn division_by_zero() {
+        let modulus = Modulus::new([Trit::Zero, Trit::Pos]).unwrap();
+        let _ = GF3k::one(modulus) / GF3k::zero(modulus);
+    }
+}
@@ -0,0 +1,644 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+use crate::gf3::{add, inverse, mul, sub, trit_vectors, write_polynomial, TritOrder};
+use crate::number::ParseNumberError;
+use crate::trit::Trit;
+
+/// A polynomial with coefficients in GF(3).
+///
+/// Coefficients are given and displayed highest degree first, like the trits
+/// of a `Number`, so "+0-" is x^2 - 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Poly3 {
+    // Lowest degree first with no trailing zeros, so that the degree is one
+    // less than the length and every polynomial has one representation
+    coefficients: Vec<Trit>
+}
+
+impl Poly3 {
+    /// Builds a polynomial from its coefficients, highest degree first.
+    pub fn new(coefficients: &[Trit]) -> Self {
+        Poly3::from_low_first(coefficients.iter().rev().copied().collect())
+    }
+
+    /// The zero polynomial, which has no degree.
+    pub fn zero() -> Self {
+        Poly3 { coefficients: Vec::new() }
+    }
+
+    /// The constant polynomial 1.
+    pub fn one() -> Self {
+        Poly3 { coefficients: vec![Trit::Pos] }
+    }
+
+    /// The polynomial x.
+    pub fn x() -> Self {
+        Poly3::monomial(1)
+    }
+
+    /// The polynomial x^degree.
+    pub fn monomial(degree: usize) -> Self {
+        let mut coefficients = vec![Trit::Zero; degree];
+        coefficients.push(Trit::Pos);
+        Poly3 { coefficients }
+    }
+
+    fn from_low_first(mut coefficients: Vec<Trit>) -> Self {
+        while coefficients.last() == Some(&Trit::Zero) {
+            coefficients.pop();
+        }
+        Poly3 { coefficients }
+    }
+
+    /// The coefficients, highest degree first, which is empty for zero.
+    pub fn coefficients(&self) -> Vec<Trit> {
+        self.coefficients.iter().rev().copied().collect()
+    }
+
+    /// The coefficient of x^degree.
+    pub fn coefficient(&self, degree: usize) -> Trit {
+        self.coefficients.get(degree).copied().unwrap_or(Trit::Zero)
+    }
+
+    /// The degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// The coefficient of the highest degree term, which is zero only for the
+    /// zero polynomial.
+    pub fn leading_coefficient(&self) -> Trit {
+        self.coefficients.last().copied().unwrap_or(Trit::Zero)
+    }
+
+    /// Whether this is the zero polynomial.
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Whether this is the constant polynomial 1.
+    pub fn is_one(&self) -> bool {
+        self.coefficients == [Trit::Pos]
+    }
+
+    /// Whether the leading coefficient is one, which is never true of zero.
+    pub fn is_monic(&self) -> bool {
+        self.leading_coefficient() == Trit::Pos
+    }
+
+    /// Scale so that the leading coefficient is one. Both nonzero trits are
+    /// their own inverses, so this multiplies by the leading coefficient.
+    pub fn monic(&self) -> Self {
+        self.scale(self.leading_coefficient())
+    }
+
+    fn scale(&self, factor: Trit) -> Self {
+        Poly3::from_low_first(self.coefficients.iter().map(|coefficient| mul(*coefficient, factor)).collect())
+    }
+
+    /// Evaluate at a point using Horner's method.
+    pub fn evaluate(&self, x: Trit) -> Trit {
+        self.coefficients.iter().rev().fold(Trit::Zero, |value, coefficient| add(mul(value, x), *coefficient))
+    }
+
+    /// The formal derivative. The coefficient of x^i becomes i times it, so
+    /// every term whose degree is a multiple of 3 vanishes.
+    pub fn derivative(&self) -> Self {
+        let coefficients = self.coefficients.iter().enumerate().skip(1)
+            .map(|(degree, coefficient)| match degree % 3 {
+                0 => Trit::Zero,
+                1 => *coefficient,
+                _ => coefficient.negate()
+            })
+            .collect();
+        Poly3::from_low_first(coefficients)
+    }
+
+    /// Divide by another polynomial.
+    ///
+    /// **returns** The quotient and remainder, where the remainder has a lower
+    /// degree than the divisor, or `None` if the divisor is zero
+    pub fn div_rem(&self, divisor: &Poly3) -> Option<(Poly3, Poly3)> {
+        let divisor_degree = divisor.degree()?;
+        let leading_inverse = inverse(divisor.leading_coefficient()).expect("Leading coefficient is nonzero");
+
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Trit::Zero; remainder.len().saturating_sub(divisor_degree)];
+        while remainder.len() > divisor_degree {
+            let leading = remainder.pop().expect("Remainder is longer than the divisor");
+            let offset = remainder.len() - divisor_degree;
+            let factor = mul(leading, leading_inverse);
+            quotient[offset] = factor;
+            for (coefficient, divisor_coefficient) in remainder[offset..].iter_mut().zip(&divisor.coefficients) {
+                *coefficient = sub(*coefficient, mul(factor, *divisor_coefficient));
+            }
+        }
+        Some((Poly3::from_low_first(quotient), Poly3::from_low_first(remainder)))
+    }
+
+    /// The monic greatest common divisor, which is zero only if both
+    /// polynomials are zero.
+    pub fn gcd(&self, other: &Poly3) -> Self {
+        let (mut lhs, mut rhs) = (self.clone(), other.clone());
+        while !rhs.is_zero() {
+            let remainder = &lhs % &rhs;
+            (lhs, rhs) = (rhs, remainder);
+        }
+        lhs.monic()
+    }
+
+    /// Raise to a power modulo another polynomial, by repeated squaring.
+    ///
+    /// * `exponent` The power to raise to
+    /// * `modulus` The nonzero polynomial to reduce by
+    ///
+    /// **returns** The remainder of the power
+    pub fn pow_mod(&self, exponent: u64, modulus: &Poly3) -> Self {
+        let mut result = &Poly3::one() % modulus;
+        let mut base = self % modulus;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &(&result * &base) % modulus;
+            }
+            base = &(&base * &base) % modulus;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Raise to the power 3^count modulo another polynomial, by cubing
+    /// repeatedly so that large powers of three never need to be held.
+    fn frobenius_mod(&self, count: usize, modulus: &Poly3) -> Self {
+        (0..count).fold(self % modulus, |power, _| power.pow_mod(3, modulus))
+    }
+
+    /// Test for irreducibility with Rabin's test: a polynomial f of degree n is
+    /// irreducible when x^(3^n) = x modulo f, and x^(3^(n/p)) - x shares no
+    /// factor with f for each prime p dividing n. Constants are not
+    /// irreducible, and every polynomial of degree one is.
+    pub fn is_irreducible(&self) -> bool {
+        let Some(degree) = self.degree().filter(|degree| *degree > 0) else {
+            return false;
+        };
+
+        let x = Poly3::x();
+        if x.frobenius_mod(degree, self) != &x % self {
+            return false;
+        }
+        prime_factors(degree as u64).into_iter().all(|prime| {
+            let power = x.frobenius_mod(degree / prime as usize, self);
+            self.gcd(&(&power - &x)).is_one()
+        })
+    }
+
+    /// Test whether this is a primitive polynomial: irreducible, with x
+    /// generating all 3^n - 1 nonzero elements of the field it defines. These
+    /// give maximal length linear feedback shift registers. The group order
+    /// must fit in a u64, so degrees above 40 always give `false`.
+    pub fn is_primitive(&self) -> bool {
+        if !self.is_monic() || !self.is_irreducible() || self.coefficient(0) == Trit::Zero {
+            return false;
+        }
+
+        // The order of x divides 3^n - 1, so it is the full group order unless
+        // it divides (3^n - 1)/p for some prime p
+        let degree = self.degree().expect("Irreducible polynomials are nonzero");
+        let Some(order) = u32::try_from(degree).ok().and_then(|degree| 3_u64.checked_pow(degree)) else {
+            return false;
+        };
+        let order = order - 1;
+        let x = Poly3::x();
+        prime_factors(order).into_iter().all(|prime| !x.pow_mod(order / prime, self).is_one())
+    }
+
+    /// Every monic polynomial of the given degree, counting up through the
+    /// lower coefficients as a balanced ternary number from all negative.
+    pub fn monic_polynomials(degree: usize) -> impl Iterator<Item = Poly3> {
+        trit_vectors(degree, TritOrder::LeastSignificantFirst).map(|mut coefficients| {
+            coefficients.push(Trit::Pos);
+            Poly3 { coefficients }
+        })
+    }
+
+    /// The primitive polynomials of the given degree, in the order of
+    /// `monic_polynomials()`. There are φ(3^n - 1)/n of them.
+    pub fn primitive_polynomials(degree: usize) -> impl Iterator<Item = Poly3> {
+        Poly3::monic_polynomials(degree).filter(Poly3::is_primitive)
+    }
+
+    /// Factor into monic irreducible polynomials with Berlekamp's algorithm,
+    /// after first splitting into square-free parts.
+    ///
+    /// **returns** Each distinct irreducible factor with its multiplicity,
+    /// ordered by degree and then coefficients. Their product times the
+    /// leading coefficient is the original polynomial, and constants have no
+    /// factors.
+    pub fn factor(&self) -> Vec<(Poly3, usize)> {
+        if self.is_zero() {
+            return Vec::new();
+        }
+        let mut factors: Vec<(Poly3, usize)> = square_free_factors(&self.monic())
+            .into_iter()
+            .flat_map(|(part, multiplicity)| berlekamp(&part).into_iter().map(move |factor| (factor, multiplicity)))
+            .collect();
+        factors.sort_by_key(|(factor, _)| (factor.degree(), factor.coefficients()));
+        factors
+    }
+
+    /// Substitute x^(1/3) for x, which undoes cubing: as 3 is the
+    /// characteristic, (Σ aᵢxⁱ)³ = Σ aᵢx³ⁱ. Only valid when every term has a
+    /// degree that is a multiple of 3.
+    fn cube_root(&self) -> Self {
+        Poly3::from_low_first(self.coefficients.iter().step_by(3).copied().collect())
+    }
+}
+
+/// The distinct prime factors of a number, by trial division.
+fn prime_factors(mut value: u64) -> Vec<u64> {
+    let mut primes = Vec::new();
+    let mut candidate = 2;
+    while candidate * candidate <= value {
+        if value.is_multiple_of(candidate) {
+            primes.push(candidate);
+            while value.is_multiple_of(candidate) {
+                value /= candidate;
+            }
+        }
+        candidate += 1;
+    }
+    if value > 1 {
+        primes.push(value);
+    }
+    primes
+}
+
+/// Split a monic polynomial into square-free parts, each paired with the
+/// multiplicity that its factors have. The derivative removes one copy of
+/// each repeated factor, except those whose multiplicity is a multiple of 3,
+/// which are recovered by taking cube roots.
+fn square_free_factors(polynomial: &Poly3) -> Vec<(Poly3, usize)> {
+    let mut parts = Vec::new();
+    let mut repeated = polynomial.gcd(&polynomial.derivative());
+    let mut remaining = polynomial / &repeated;
+    let mut multiplicity = 1;
+    while !remaining.is_one() {
+        let common = remaining.gcd(&repeated);
+        let part = &remaining / &common;
+        if !part.is_one() {
+            parts.push((part, multiplicity));
+        }
+        repeated = &repeated / &common;
+        remaining = common;
+        multiplicity += 1;
+    }
+
+    if repeated.degree().is_some_and(|degree| degree > 0) {
+        for (part, multiplicity) in square_free_factors(&repeated.cube_root()) {
+            parts.push((part, multiplicity * 3));
+        }
+    }
+    parts
+}
+
+/// Factor a monic square-free polynomial with Berlekamp's algorithm. The
+/// polynomials g with g³ = g modulo f form a vector space whose dimension is
+/// the number of irreducible factors of f, and for any of them that isn't
+/// constant the gcds of f with g - s for each trit s split f.
+fn berlekamp(polynomial: &Poly3) -> Vec<Poly3> {
+    let degree = polynomial.degree().expect("Square-free parts are nonzero");
+    if degree <= 1 {
+        return vec![polynomial.clone()];
+    }
+
+    // Column j holds x^(3j) modulo f, minus x^j, so the null space of the
+    // matrix is the coefficients of every g with g³ = g
+    let x_cubed = &Poly3::monomial(3) % polynomial;
+    let mut power = Poly3::one();
+    let mut matrix = vec![vec![Trit::Zero; degree]; degree];
+    for column in 0..degree {
+        for (row, entries) in matrix.iter_mut().enumerate() {
+            entries[column] = power.coefficient(row);
+        }
+        matrix[column][column] = sub(matrix[column][column], Trit::Pos);
+        power = &(&power * &x_cubed) % polynomial;
+    }
+
+    let basis = null_space(matrix);
+    let count = basis.len();
+    let mut factors = vec![polynomial.clone()];
+    for vector in basis {
+        if factors.len() == count {
+            break;
+        }
+        let splitter = Poly3::from_low_first(vector);
+        if splitter.degree().is_some_and(|degree| degree > 0) {
+            factors = factors.into_iter()
+                .flat_map(|factor| split(&factor, &splitter))
+                .collect();
+        }
+    }
+    factors
+}
+
+/// Split a polynomial into its gcds with g - s for each trit s, which are
+/// coprime and multiply back to it when g³ = g modulo the polynomial.
+fn split(polynomial: &Poly3, splitter: &Poly3) -> Vec<Poly3> {
+    if polynomial.degree() == Some(1) {
+        return vec![polynomial.clone()];
+    }
+    [Trit::Neg, Trit::Zero, Trit::Pos].into_iter()
+        .map(|shift| polynomial.gcd(&(splitter - &Poly3::from(shift))))
+        .filter(|factor| !factor.is_one())
+        .collect()
+}
+
+/// A basis for the vectors v with Av = 0, by Gaussian elimination over GF(3).
+fn null_space(mut matrix: Vec<Vec<Trit>>) -> Vec<Vec<Trit>> {
+    let columns = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+    let mut row = 0;
+    for column in 0..columns {
+        let Some(pivot) = (row..matrix.len()).find(|idx| matrix[*idx][column] != Trit::Zero) else {
+            continue;
+        };
+        matrix.swap(row, pivot);
+        let scale = inverse(matrix[row][column]).expect("Pivot is nonzero");
+        matrix[row].iter_mut().for_each(|entry| *entry = mul(*entry, scale));
+        let pivot_row = matrix[row].clone();
+        for (other, entries) in matrix.iter_mut().enumerate() {
+            let factor = entries[column];
+            if other != row && factor != Trit::Zero {
+                for (entry, pivot_entry) in entries.iter_mut().zip(&pivot_row) {
+                    *entry = sub(*entry, mul(factor, *pivot_entry));
+                }
+            }
+        }
+        pivots.push(column);
+        row += 1;
+    }
+
+    // Each free column gives a basis vector, with the pivot variables set to
+    // cancel it out
+    (0..columns)
+        .filter(|column| !pivots.contains(column))
+        .map(|free| {
+            let mut vector = vec![Trit::Zero; columns];
+            vector[free] = Trit::Pos;
+            for (row, pivot) in pivots.iter().enumerate() {
+                vector[*pivot] = matrix[row][free].negate();
+            }
+            vector
+        })
+        .collect()
+}
+
+impl From<Trit> for Poly3 {
+    /// The constant polynomial.
+    fn from(constant: Trit) -> Self {
+        Poly3::from_low_first(vec![constant])
+    }
+}
+
+impl Add for &Poly3 {
+    type Output = Poly3;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let length = self.coefficients.len().max(rhs.coefficients.len());
+        Poly3::from_low_first((0..length).map(|degree| add(self.coefficient(degree), rhs.coefficient(degree))).collect())
+    }
+}
+
+impl Sub for &Poly3 {
+    type Output = Poly3;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Mul for &Poly3 {
+    type Output = Poly3;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.is_zero() || rhs.is_zero() {
+            return Poly3::zero();
+        }
+        let mut product = vec![Trit::Zero; self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (lhs_degree, lhs) in self.coefficients.iter().enumerate() {
+            for (rhs_degree, rhs) in rhs.coefficients.iter().enumerate() {
+                let term = &mut product[lhs_degree + rhs_degree];
+                *term = add(*term, mul(*lhs, *rhs));
+            }
+        }
+        Poly3::from_low_first(product)
+    }
+}
+
+impl Div for &Poly3 {
+    type Output = Poly3;
+
+    /// Polynomial division with the remainder discarded.
+    ///
+    /// If the divisor is zero then the program will exit with an error message.
+    fn div(self, divisor: Self) -> Self::Output {
+        match self.div_rem(divisor) {
+            Some((quotient, _)) => quotient,
+            None => panic!("Attempt to divide by zero")
+        }
+    }
+}
+
+impl Rem for &Poly3 {
+    type Output = Poly3;
+
+    /// If the divisor is zero then the program will exit with an error message.
+    fn rem(self, divisor: Self) -> Self::Output {
+        match self.div_rem(divisor) {
+            Some((_, remainder)) => remainder,
+            None => panic!("Attempt to divide by zero")
+        }
+    }
+}
+
+impl Neg for &Poly3 {
+    type Output = Poly3;
+
+    fn neg(self) -> Self::Output {
+        self.scale(Trit::Neg)
+    }
+}
+
+/// Implement an operator for owned polynomials by borrowing them.
+macro_rules! forward_owned {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait for Poly3 {
+            type Output = Poly3;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl $trait<&Poly3> for Poly3 {
+            type Output = Poly3;
+
+            fn $method(self, rhs: &Poly3) -> Self::Output {
+                (&self).$method(rhs)
+            }
+        }
+    )*};
+}
+
+forward_owned!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+impl Neg for Poly3 {
+    type Output = Poly3;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl FromStr for Poly3 {
+    type Err = ParseNumberError;
+
+    /// Parse the coefficients as a trit string, highest degree first, e.g.
+    /// "+0-" for x^2 - 1.
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        if encoded.is_empty() {
+            return Err(ParseNumberError::Empty);
+        }
+        let coefficients = encoded.chars()
+            .map(|c| Trit::from_char(c).ok_or(ParseNumberError::InvalidTrit(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Poly3::new(&coefficients))
+    }
+}
+
+impl fmt::Display for Poly3 {
+    /// Displays the polynomial in x, e.g. `x^2 - x + 1`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_polynomial(f, &self.coefficients())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(encoded: &str) -> Poly3 {
+        encoded.parse().unwrap()
+    }
+
+    /// Every polynomial with up to the given number of coefficients.
+    fn all_polynomials(length: usize) -> Vec<Poly3> {
+        trit_vectors(length, TritOrder::LeastSignificantFirst).map(|coefficients| Poly3::new(&coefficients)).collect()
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = poly("+0-");
+        let b = poly("++");
+        assert_eq!(a.to_string(), "x^2 - 1");
+        assert_eq!(&a + &b, poly("++0"));
+        assert_eq!(&a - &b, poly("+-+"));
+        assert_eq!(&a * &b, poly("++--"));
+        assert_eq!(-&a, poly("-0+"));
+        // x^2 - 1 = (x + 1)(x - 1)
+        assert_eq!(a.div_rem(&b), Some((poly("+-"), Poly3::zero())));
+        assert_eq!(poly("+00").div_rem(&poly("-+")), Some((poly("--"), poly("+"))));
+        assert_eq!(a.div_rem(&Poly3::zero()), None);
+        assert_eq!(poly("000+-").degree(), Some(1));
+        assert_eq!(Poly3::zero().degree(), None);
+        assert_eq!(Poly3::zero().to_string(), "0");
+        assert_eq!("+x".parse::<Poly3>(), Err(ParseNumberError::InvalidTrit('x')));
+    }
+
+    #[test]
+    fn division_identity() {
+        for dividend in all_polynomials(5) {
+            for divisor in all_polynomials(3).into_iter().filter(|divisor| !divisor.is_zero()) {
+                let (quotient, remainder) = dividend.div_rem(&divisor).unwrap();
+                assert_eq!(&(&quotient * &divisor) + &remainder, dividend);
+                assert!(remainder.degree() < divisor.degree());
+            }
+        }
+    }
+
+    #[test]
+    fn gcd_derivative_and_evaluation() {
+        // (x + 1)(x - 1) and (x + 1)(x^2 + 1) share x + 1
+        let lhs = poly("+0-");
+        let rhs = &poly("++") * &poly("+0+");
+        assert_eq!(lhs.gcd(&rhs), poly("++"));
+        assert_eq!((-&lhs).gcd(&Poly3::zero()), lhs);
+
+        assert_eq!(poly("++++").derivative(), poly("-+"));
+        assert_eq!(poly("+000+").derivative(), poly("+000"));
+        assert_eq!(Poly3::monomial(3).derivative(), Poly3::zero());
+
+        let cubic = poly("+0-+");
+        assert_eq!(cubic.evaluate(Trit::Zero), Trit::Pos);
+        assert_eq!(cubic.evaluate(Trit::Pos), Trit::Pos);
+        assert_eq!(cubic.evaluate(Trit::Neg), Trit::Pos);
+    }
+
+    #[test]
+    fn counts_irreducible_polynomials() {
+        // The number of monic irreducible polynomials of each degree over GF(3)
+        for (degree, count) in [(1, 3), (2, 3), (3, 8), (4, 18), (5, 48)] {
+            assert_eq!(Poly3::monic_polynomials(degree).filter(Poly3::is_irreducible).count(), count, "degree {}", degree);
+        }
+        assert!(!Poly3::one().is_irreducible());
+        assert!(!Poly3::zero().is_irreducible());
+        // x^4 + 1 has no roots but factors into quadratics
+        assert!(!poly("+000+").is_irreducible());
+    }
+
+    #[test]
+    fn counts_primitive_polynomials() {
+        // φ(3^n - 1)/n primitive polynomials of each degree
+        for (degree, count) in [(1, 1), (2, 2), (3, 4), (4, 8), (5, 22)] {
+            assert_eq!(Poly3::primitive_polynomials(degree).count(), count, "degree {}", degree);
+        }
+        assert_eq!(Poly3::primitive_polynomials(1).next(), Some(poly("++")));
+        // x^2 + 1 is irreducible, but x has order 4 rather than 8
+        assert!(poly("+0+").is_irreducible());
+        assert!(!poly("+0+").is_primitive());
+        // x^3 - x + 1 is the modulus of GF(27) in which x has order 26
+        assert!(poly("+0-+").is_primitive());
+
+        // The group order of larger fields doesn't fit in a u64
+        let large = Poly3::monic_polynomials(41).find(Poly3::is_irreducible).unwrap();
+        assert!(!large.is_primitive());
+    }
+
+    #[test]
+    fn factorisation() {
+        assert_eq!(poly("+000+").factor(), vec![(poly("+--"), 1), (poly("++-"), 1)]);
+        assert_eq!(poly("+0-0").factor(), vec![(poly("+-"), 1), (poly("+0"), 1), (poly("++"), 1)]);
+
+        // -(x + 1)^3 (x^2 + 1)^2 x^4 needs the cube root step
+        let repeated = &(&(&poly("-") * &poly("++").pow_mod(3, &Poly3::monomial(20))) * &poly("+0+").pow_mod(2, &Poly3::monomial(20)))
+            * &Poly3::monomial(4);
+        assert_eq!(repeated.factor(), vec![(poly("+0"), 4), (poly("++"), 3), (poly("+0+"), 2)]);
+        assert_eq!(Poly3::one().factor(), vec![]);
+        assert_eq!(poly("-").factor(), vec![]);
+        assert_eq!(Poly3::zero().factor(), vec![]);
+    }
+
+    #[test]
+    fn factors_multiply_back() {
+        for polynomial in all_polynomials(8).into_iter().filter(|polynomial| polynomial.degree() > Some(0)).step_by(7) {
+            let factors = polynomial.factor();
+            let product = factors.iter().fold(Poly3::from(polynomial.leading_coefficient()), |product, (factor, multiplicity)| {
+                assert!(factor.is_irreducible() && factor.is_monic(), "{} is a factor of {}", factor, polynomial);
+                (0..*multiplicity).fold(product, |product, _| &product * factor)
+            });
+            assert_eq!(product, polynomial);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Reflected ternary Gray code, in which consecutive numbers differ in a
+//! single trit, and by only one level.
+//!
+//! In the reflected code each trit runs up and then back down, reversing
+//! direction whenever the trits above it change. In balanced ternary reversing
+//! a trit is just negating it, and it happens when an odd number of the trits
+//! above it are zero: counting up, each zero trit is the middle of a run from
+//! `-` to `+`, after which the trits below must run back the other way.
+//!
+//! Negating leaves zero trits alone, so the trits above any position have the
+//! same zeros in a number and in its Gray code. Converting to Gray code and
+//! back are therefore the same operation.
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// Negate each trit that has an odd number of zero trits above it.
+fn reflect<const N: usize>(number: Number<N>) -> Number<N> {
+    let mut trits = *number.trits();
+    let mut reflected = false;
+    for trit in trits.iter_mut() {
+        let zero = *trit == Trit::Zero;
+        if reflected {
+            *trit = trit.negate();
+        }
+        reflected ^= zero;
+    }
+    Number::from_trits(trits)
+}
+
+/// Convert a number to its reflected ternary Gray code. Counting from `MIN`
+/// to `MAX`, each Gray code differs from the previous one in a single trit,
+/// which changes by one.
+pub fn to_gray<const N: usize>(number: Number<N>) -> Number<N> {
+    reflect(number)
+}
+
+/// Convert a reflected ternary Gray code back to the number it encodes.
+pub fn from_gray<const N: usize>(gray: Number<N>) -> Number<N> {
+    reflect(gray)
+}
+
+/// An iterator over the Gray codes of every number of N trits, from `MIN` to
+/// `MAX`.
+#[derive(Clone, Debug)]
+pub struct GraySequence<const N: usize> {
+    next: Option<Number<N>>
+}
+
+/// The full Gray code sequence for N trits, 3^N codes that each differ from
+/// the previous in one trit. It starts and ends at the same numbers as
+/// counting, all negative trits and all positive trits.
+pub fn gray_sequence<const N: usize>() -> GraySequence<N> {
+    GraySequence { next: Some(Number::MIN) }
+}
+
+impl<const N: usize> Iterator for GraySequence<N> {
+    type Item = Number<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = (current != Number::MAX).then(|| {
+            let mut next = current;
+            next.inc();
+            next
+        });
+        Some(to_gray(current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The positions where two numbers differ, and by how much.
+    fn differences<const N: usize>(lhs: Number<N>, rhs: Number<N>) -> Vec<(usize, i8)> {
+        lhs.trits().iter().zip(rhs.trits()).enumerate()
+            .filter(|(_, (lhs, rhs))| lhs != rhs)
+            .map(|(idx, (lhs, rhs))| (idx, i8::from(*rhs) - i8::from(*lhs)))
+            .collect()
+    }
+
+    fn one_trit_steps<const N: usize>() {
+        let codes: Vec<Number<N>> = gray_sequence::<N>().collect();
+        assert_eq!(codes.len(), 3_usize.pow(N as u32));
+        for pair in codes.windows(2) {
+            let changed = differences(pair[0], pair[1]);
+            assert_eq!(changed.len(), 1, "{:?} to {:?}", pair[0], pair[1]);
+            assert_eq!(changed[0].1.abs(), 1, "{:?} to {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn each_step_changes_one_trit() {
+        one_trit_steps::<1>();
+        one_trit_steps::<2>();
+        one_trit_steps::<3>();
+        one_trit_steps::<5>();
+    }
+
+    #[test]
+    fn two_trit_sequence() {
+        let expected = ["--", "-0", "-+", "0+", "00", "0-", "+-", "+0", "++"].map(Number::<2>::from);
+        assert_eq!(gray_sequence::<2>().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut codes: Vec<Number<4>> = Vec::new();
+        for value in -40..=40 {
+            let number = Number::<4>::from_i64(value);
+            let gray = to_gray(number);
+            assert_eq!(from_gray(gray), number);
+            codes.push(gray);
+        }
+        // Every code is used exactly once
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), 81);
+
+        assert_eq!(to_gray(Number::<6>::MIN), Number::MIN);
+        assert_eq!(to_gray(Number::<6>::MAX), Number::MAX);
+        assert_eq!(to_gray(Number::<6>::ZERO), Number::ZERO);
+        // 0+0- has one zero above the + and two above the -
+        assert_eq!(to_gray(Number::<4>::from("0+0-")), Number::from("0-0-"));
+    }
+}
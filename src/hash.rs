@@ -0,0 +1,234 @@
+//! A non-cryptographic hash function working natively on trits.
+//!
+//! `TritHash` is a sponge in the spirit of Curl and Troika: trits are added
+//! into part of a 243 trit state, which is scrambled by a tritwise permutation
+//! after every block, and the digest is then read back out of the state. It
+//! mixes well enough for hash tables and test vectors but makes no claim to
+//! resist attack.
+//!
+//! `TernaryHasher` adapts it to `std::hash::Hasher`, so that numbers and trits
+//! can be hashed trit by trit in a `HashMap` or `HashSet` built with
+//! `BuildTernaryHasher`.
+
+use std::hash::{BuildHasher, Hasher};
+
+use crate::checksum::TritHasher;
+use crate::gf3::add;
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// The number of trits in the sponge state.
+const STATE_TRITS: usize = 243;
+/// The number of trits absorbed or squeezed between permutations. The other
+/// 162 trits are never directly touched by input.
+const RATE_TRITS: usize = 81;
+/// The number of times the mixing step is repeated in each permutation.
+const ROUNDS: usize = 27;
+
+/// The nonlinear step from Curl, combining two trits a and b into
+/// `SUBSTITUTION[a + 3b + 4]`.
+const SUBSTITUTION: [Trit; 9] = [
+    Trit::Pos, Trit::Zero, Trit::Neg,
+    Trit::Pos, Trit::Neg, Trit::Zero,
+    Trit::Neg, Trit::Pos, Trit::Zero
+];
+
+/// A sponge hash over trits, producing a digest of N trits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TritHash<const N: usize> {
+    state: [Trit; STATE_TRITS],
+    // The number of trits absorbed into the current block
+    position: usize
+}
+
+impl<const N: usize> Default for TritHash<N> {
+    fn default() -> Self {
+        TritHash { state: [Trit::Zero; STATE_TRITS], position: 0 }
+    }
+}
+
+impl<const N: usize> TritHash<N> {
+    /// A hash that has absorbed no trits.
+    pub fn new() -> Self {
+        TritHash::default()
+    }
+
+    /// Hash a complete sequence of trits.
+    pub fn digest(trits: &[Trit]) -> Number<N> {
+        let mut hash = TritHash::new();
+        hash.write_trits(trits);
+        hash.finish()
+    }
+}
+
+/// Scramble the state. Each round sets trit k to a nonlinear function of the
+/// previous trits at positions 121k and 121(k + 1), modulo 243. As 121 is
+/// coprime to 243 these walk through every position, so every trit feeds into
+/// two others.
+fn permute(state: &mut [Trit; STATE_TRITS]) {
+    for _ in 0..ROUNDS {
+        let previous = *state;
+        let mut idx = 0;
+        for trit in state.iter_mut() {
+            let next = (idx + STATE_TRITS / 2) % STATE_TRITS;
+            let lookup = i8::from(previous[idx]) + 3 * i8::from(previous[next]) + 4;
+            *trit = SUBSTITUTION[lookup as usize];
+            idx = next;
+        }
+    }
+}
+
+impl<const N: usize> TritHasher for TritHash<N> {
+    type Output = Number<N>;
+
+    fn write_trit(&mut self, trit: Trit) {
+        self.state[self.position] = add(self.state[self.position], trit);
+        self.position += 1;
+        if self.position == RATE_TRITS {
+            permute(&mut self.state);
+            self.position = 0;
+        }
+    }
+
+    /// The digest of the trits so far. The input is padded with a positive
+    /// trit, so that inputs differing only in trailing zeros hash differently,
+    /// and then digest trits are squeezed out a block at a time.
+    fn finish(&self) -> Self::Output {
+        let mut state = self.state;
+        state[self.position] = add(state[self.position], Trit::Pos);
+        permute(&mut state);
+
+        let mut digest = [Trit::Zero; N];
+        for (idx, chunk) in digest.chunks_mut(RATE_TRITS).enumerate() {
+            if idx > 0 {
+                permute(&mut state);
+            }
+            chunk.copy_from_slice(&state[..chunk.len()]);
+        }
+        Number::from_trits(digest)
+    }
+}
+
+/// A `std::hash::Hasher` built on `TritHash`.
+///
+/// Trits and numbers are written one trit at a time, through `write_i8`. Other
+/// values arrive as bytes, which are written as six trits each.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TernaryHasher(TritHash<40>);
+
+impl Hasher for TernaryHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0.write_number(Number::<6>::from_i64(i64::from(*byte)));
+        }
+    }
+
+    fn write_i8(&mut self, value: i8) {
+        match value {
+            -1 => self.0.write_trit(Trit::Neg),
+            0 => self.0.write_trit(Trit::Zero),
+            1 => self.0.write_trit(Trit::Pos),
+            _ => self.write(&value.to_le_bytes())
+        }
+    }
+
+    /// The 40 trit digest, which fits in 64 bits.
+    fn finish(&self) -> u64 {
+        let digest = i64::try_from(self.0.finish()).expect("40 trits fit in an i64");
+        digest as u64
+    }
+}
+
+/// Builds `TernaryHasher`s, for use as the hasher of a `HashMap` or `HashSet`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BuildTernaryHasher;
+
+impl BuildHasher for BuildTernaryHasher {
+    type Hasher = TernaryHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        TernaryHasher::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::hash::Hash;
+
+    use super::*;
+    use crate::random::TritRng;
+
+    #[test]
+    fn permutation_is_a_cycle() {
+        // Stepping by 121 visits every position before returning to the start
+        let mut idx = 0;
+        let mut seen = HashSet::new();
+        while seen.insert(idx) {
+            idx = (idx + STATE_TRITS / 2) % STATE_TRITS;
+        }
+        assert_eq!(seen.len(), STATE_TRITS);
+    }
+
+    #[test]
+    fn deterministic_and_length_sensitive() {
+        let message: Vec<Trit> = TritRng::new(8).take(200).collect();
+        let digest = TritHash::<27>::digest(&message);
+        assert_eq!(digest, TritHash::<27>::digest(&message));
+
+        // Streaming in pieces gives the same digest
+        let mut hash = TritHash::<27>::new();
+        hash.write_trits(&message[..50]);
+        hash.write_trits(&message[50..]);
+        assert_eq!(hash.finish(), digest);
+
+        // Trailing zeros and the empty input all differ
+        let mut padded = message.clone();
+        padded.push(Trit::Zero);
+        assert_ne!(TritHash::<27>::digest(&padded), digest);
+        assert_ne!(TritHash::<27>::digest(&[]), TritHash::<27>::digest(&[Trit::Zero]));
+
+        // Longer digests extend shorter ones, squeezing beyond one block
+        let long = TritHash::<100>::digest(&message);
+        assert_eq!(long.trits()[..27], digest.trits()[..]);
+    }
+
+    #[test]
+    fn avalanche() {
+        // Changing one input trit changes about two thirds of the digest
+        let message: Vec<Trit> = TritRng::new(9).take(81).collect();
+        let digest = TritHash::<81>::digest(&message);
+        for idx in [0, 40, 80] {
+            let mut changed = message.clone();
+            changed[idx] = add(changed[idx], Trit::Pos);
+            let other = TritHash::<81>::digest(&changed);
+            let differences = digest.trits().iter().zip(other.trits()).filter(|(lhs, rhs)| lhs != rhs).count();
+            assert!((40..=68).contains(&differences), "{} trits changed", differences);
+        }
+    }
+
+    #[test]
+    fn trit_native_hashing() {
+        // Numbers hash as their trits, so equal values hash equally
+        let hash = |number: Number<8>| BuildTernaryHasher.hash_one(number);
+        assert_eq!(hash(Number::from_i64(100)), hash(Number::from("000++-0+")));
+        assert_ne!(hash(Number::from_i64(100)), hash(Number::from_i64(-100)));
+
+        let mut hasher = TernaryHasher::default();
+        Trit::Pos.hash(&mut hasher);
+        assert_eq!(hasher.finish(), i64::try_from(TritHash::<40>::digest(&[Trit::Pos])).unwrap() as u64);
+    }
+
+    #[test]
+    fn hash_map() {
+        let mut map: HashMap<Number<8>, &str, BuildTernaryHasher> = HashMap::default();
+        map.insert(Number::from_i64(5), "five");
+        map.insert(Number::from_i64(-5), "minus five");
+        map.insert(Number::ZERO, "zero");
+        assert_eq!(map.get(&Number::from_i64(-5)), Some(&"minus five"));
+        assert_eq!(map.get(&Number::from_i64(6)), None);
+
+        let strings: HashSet<String, BuildTernaryHasher> = ["a", "b", "ab"].map(String::from).into_iter().collect();
+        assert!(strings.contains("ab") && !strings.contains("ba"));
+    }
+}
@@ -1,3 +1,20 @@
+pub mod checksum;
+pub mod ecc;
+pub mod expr;
+pub mod fixed;
+pub mod float;
+pub mod gf3;
+pub mod gray;
+pub mod hash;
+pub mod linecode;
+mod macros;
 pub mod number;
+pub mod packing;
+pub mod random;
+pub mod ratio;
 pub mod trit;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod vm;
+pub mod weights;
 mod sum_result;
\ No newline at end of file
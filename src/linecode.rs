@@ -0,0 +1,161 @@
+//! Line codes that carry binary data as ternary signal levels.
+//!
+//! Several real links signal with three voltage levels, each symbol being a
+//! trit. This module maps byte streams onto trit symbol sequences and back
+//! for some of them:
+//!
+//! * `mlt3`, the MLT-3 code of 100BASE-TX Ethernet, which cycles through the
+//!   levels to signal each one bit
+//! * `pam3`, plain three-level amplitude modulation, packing 3 bits into 2
+//!   trits as GDDR7 memory does, or 11 bits into 7 trits as USB4 does
+//! * `mms43`, the MMS43 variant of 4B3T used by ISDN, sending each 4 bits as
+//!   3 trits chosen to keep the running disparity bounded
+//!
+//! Bits are taken from each byte most significant first. `DcBalance`
+//! measures how far a symbol sequence drifts from a zero average, which a
+//! transformer-coupled line can't carry.
+
+pub mod mlt3;
+pub mod mms43;
+pub mod pam3;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::trit::Trit;
+
+/// Error returned when a symbol sequence cannot be decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineCodeError {
+    /// The number of symbols wasn't this length
+    WrongLength(usize),
+    /// The number of symbols wasn't a multiple of this group size
+    NotMultiple(usize),
+    /// The symbol or group of symbols starting at this index isn't valid for
+    /// the code
+    InvalidSymbol(usize)
+}
+
+impl fmt::Display for LineCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineCodeError::WrongLength(length) => write!(f, "wrong number of symbols, expected {}", length),
+            LineCodeError::NotMultiple(size) => write!(f, "wrong number of symbols, expected a multiple of {}", size),
+            LineCodeError::InvalidSymbol(idx) => write!(f, "invalid symbol at index {}", idx)
+        }
+    }
+}
+
+impl Error for LineCodeError {}
+
+/// Statistics on how well a symbol sequence is balanced around zero.
+///
+/// The running disparity, or running digital sum, is the total of the
+/// symbols so far. A DC-balanced code keeps it within a small range, and its
+/// final value shows any overall offset.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DcBalance {
+    counts: [usize; 3],
+    disparity: i64,
+    min_disparity: i64,
+    max_disparity: i64
+}
+
+impl DcBalance {
+    /// Measure a sequence of symbols, starting from a disparity of zero.
+    pub fn measure(symbols: &[Trit]) -> Self {
+        let mut balance = DcBalance::default();
+        for symbol in symbols {
+            balance.counts[(i8::from(*symbol) + 1) as usize] += 1;
+            balance.disparity += i64::from(i8::from(*symbol));
+            balance.min_disparity = balance.min_disparity.min(balance.disparity);
+            balance.max_disparity = balance.max_disparity.max(balance.disparity);
+        }
+        balance
+    }
+
+    /// The number of symbols measured.
+    pub fn symbols(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The number of symbols at the given level.
+    pub fn count(&self, level: Trit) -> usize {
+        self.counts[(i8::from(level) + 1) as usize]
+    }
+
+    /// The running disparity after the last symbol.
+    pub fn disparity(&self) -> i64 {
+        self.disparity
+    }
+
+    /// The lowest running disparity reached, which is never above zero.
+    pub fn min_disparity(&self) -> i64 {
+        self.min_disparity
+    }
+
+    /// The highest running disparity reached, which is never below zero.
+    pub fn max_disparity(&self) -> i64 {
+        self.max_disparity
+    }
+
+    /// The digital sum variation, the width of the range that the running
+    /// disparity stayed within.
+    pub fn variation(&self) -> i64 {
+        self.max_disparity - self.min_disparity
+    }
+
+    /// The average symbol level, or zero if there were no symbols.
+    pub fn mean(&self) -> f64 {
+        match self.symbols() {
+            0 => 0.0,
+            symbols => self.disparity as f64 / symbols as f64
+        }
+    }
+}
+
+/// The bits of some bytes, most significant first.
+fn bits(bytes: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    bytes.iter().flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1))
+}
+
+/// Pack bits into bytes, most significant first, dropping a final partial
+/// byte.
+fn bytes(bits: impl IntoIterator<Item = bool>) -> Vec<u8> {
+    let bits: Vec<bool> = bits.into_iter().collect();
+    bits.chunks_exact(8)
+        .map(|chunk| chunk.iter().fold(0, |byte, bit| byte << 1 | u8::from(*bit)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_order() {
+        let data = [0b1010_0001, 0xff];
+        let unpacked: Vec<bool> = bits(&data).collect();
+        assert_eq!(unpacked[..8], [true, false, true, false, false, false, false, true]);
+        assert_eq!(bytes(unpacked), data);
+        assert!(bytes([true; 7]).is_empty());
+    }
+
+    #[test]
+    fn dc_balance() {
+        let symbols: Vec<Trit> = "++0-+---".chars().map(Trit::from).collect();
+        let balance = DcBalance::measure(&symbols);
+        assert_eq!(balance.symbols(), 8);
+        assert_eq!((balance.count(Trit::Neg), balance.count(Trit::Zero), balance.count(Trit::Pos)), (4, 1, 3));
+        assert_eq!(balance.disparity(), -1);
+        assert_eq!((balance.min_disparity(), balance.max_disparity(), balance.variation()), (-1, 2, 3));
+        assert_eq!(balance.mean(), -0.125);
+        assert_eq!(DcBalance::measure(&[]).mean(), 0.0);
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(LineCodeError::WrongLength(21).to_string(), "wrong number of symbols, expected 21");
+        assert_eq!(LineCodeError::NotMultiple(6).to_string(), "wrong number of symbols, expected a multiple of 6");
+    }
+}
@@ -0,0 +1,119 @@
+//! MLT-3, the multi-level transmit code of 100BASE-TX Ethernet.
+//!
+//! The line cycles through the levels 0, +, 0, - and back to 0. A one bit
+//! moves to the next level in the cycle and a zero bit stays at the current
+//! level, so a run of ones takes four symbols per full cycle, a quarter of
+//! the bit rate. The line starts at level 0, about to move up.
+
+use crate::linecode::{bits, bytes, LineCodeError};
+use crate::trit::Trit;
+
+/// The state of an MLT-3 line: its current level and the direction it will
+/// next move from zero.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Encoder {
+    level: Trit,
+    // The level that the line last left zero towards
+    last_peak: Trit
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Encoder { level: Trit::Zero, last_peak: Trit::Neg }
+    }
+}
+
+impl Encoder {
+    /// An encoder at level 0, about to move up.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// The current line level.
+    pub fn level(&self) -> Trit {
+        self.level
+    }
+
+    /// Encode one bit.
+    ///
+    /// **returns** The level sent for it
+    pub fn push_bit(&mut self, bit: bool) -> Trit {
+        if bit {
+            self.level = match self.level {
+                Trit::Zero => {
+                    self.last_peak = self.last_peak.negate();
+                    self.last_peak
+                }
+                _ => Trit::Zero
+            };
+        }
+        self.level
+    }
+}
+
+/// Encode bytes as MLT-3 symbols, eight per byte, starting from level 0.
+pub fn encode(data: &[u8]) -> Vec<Trit> {
+    let mut encoder = Encoder::new();
+    bits(data).map(|bit| encoder.push_bit(bit)).collect()
+}
+
+/// Decode MLT-3 symbols sent from level 0, each change of level being a one
+/// bit.
+///
+/// **returns** The bytes, or an error if the number of symbols isn't a
+/// multiple of 8 or a change of level breaks the cycle
+pub fn decode(symbols: &[Trit]) -> Result<Vec<u8>, LineCodeError> {
+    if !symbols.len().is_multiple_of(8) {
+        return Err(LineCodeError::NotMultiple(8));
+    }
+
+    let mut encoder = Encoder::new();
+    let mut decoded = Vec::with_capacity(symbols.len());
+    for (idx, symbol) in symbols.iter().enumerate() {
+        let bit = *symbol != encoder.level();
+        if encoder.push_bit(bit) != *symbol {
+            return Err(LineCodeError::InvalidSymbol(idx));
+        }
+        decoded.push(bit);
+    }
+    Ok(bytes(decoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linecode::DcBalance;
+
+    fn symbols(encoded: &str) -> Vec<Trit> {
+        encoded.chars().map(Trit::from).collect()
+    }
+
+    #[test]
+    fn cycles_through_levels() {
+        assert_eq!(encode(&[0xff]), symbols("+0-0+0-0"));
+        assert_eq!(encode(&[0b1011_0010]), symbols("++0---00"));
+        assert_eq!(encode(&[0]), symbols("00000000"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = encode(&data);
+        assert_eq!(decode(&encoded), Ok(data));
+
+        // Long runs of ones stay balanced, as the cycle spends as long at each
+        // peak
+        let balance = DcBalance::measure(&encode(&[0xff; 16]));
+        assert_eq!(balance.disparity(), 0);
+        assert_eq!(balance.variation(), 1);
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        assert_eq!(decode(&symbols("+0-")), Err(LineCodeError::NotMultiple(8)));
+        // Jumping between the peaks, or leaving zero in the wrong direction
+        assert_eq!(decode(&symbols("+-000000")), Err(LineCodeError::InvalidSymbol(1)));
+        assert_eq!(decode(&symbols("-0000000")), Err(LineCodeError::InvalidSymbol(0)));
+        assert_eq!(decode(&symbols("+0+00000")), Err(LineCodeError::InvalidSymbol(2)));
+    }
+}
@@ -0,0 +1,191 @@
+//! MMS43, the 4B3T code used on ISDN basic rate lines.
+//!
+//! Each 4 bits are sent as 3 trits. Of the 27 trit triples, 000 is never
+//! used, the 6 whose trits sum to zero always stand for the same 4 bits, and
+//! the other 20 are paired up so that each remaining group of 4 bits has a
+//! triple that raises the running disparity and one that lowers it. The
+//! encoder is a state machine over the disparity, choosing whichever triple
+//! keeps it within a range of four values, which bounds the DC offset.
+//!
+//! Every triple decodes to a single group of bits whatever the state, so a
+//! decoder only needs to follow the state to detect errors.
+
+use crate::linecode::LineCodeError;
+use crate::trit::Trit;
+
+/// The triple sent for each group of 4 bits from each state, where state
+/// `S` is a running disparity of `S - 1` relative to its lowest value.
+const CODES: [[&str; 4]; 16] = [
+    ["+0+", "0-0", "0-0", "0-0"],
+    ["0-+", "0-+", "0-+", "0-+"],
+    ["+-0", "+-0", "+-0", "+-0"],
+    ["00+", "00+", "00+", "--0"],
+    ["-+0", "-+0", "-+0", "-+0"],
+    ["0++", "-00", "-00", "-00"],
+    ["-++", "-++", "--+", "--+"],
+    ["-0+", "-0+", "-0+", "-0+"],
+    ["+00", "+00", "+00", "0--"],
+    ["+-+", "+-+", "+-+", "---"],
+    ["++-", "++-", "+--", "+--"],
+    ["+0-", "+0-", "+0-", "+0-"],
+    ["+++", "-+-", "-+-", "-+-"],
+    ["0+0", "0+0", "0+0", "-0-"],
+    ["0+-", "0+-", "0+-", "0+-"],
+    ["++0", "00-", "00-", "00-"]
+];
+
+/// The number of encoder states.
+const STATES: usize = 4;
+
+fn triple(code: &str) -> [Trit; 3] {
+    let mut trits = [Trit::Zero; 3];
+    trits.iter_mut().zip(code.chars()).for_each(|(trit, c)| *trit = Trit::from(c));
+    trits
+}
+
+fn weight(trits: &[Trit]) -> i8 {
+    trits.iter().map(|trit| i8::from(*trit)).sum()
+}
+
+/// The state after sending a triple, or `None` if it would leave the range
+/// of states.
+fn next_state(state: usize, trits: &[Trit]) -> Option<usize> {
+    let next = state as i8 + weight(trits);
+    (0..STATES as i8).contains(&next).then_some(next as usize)
+}
+
+/// An MMS43 encoder, tracking the running disparity between groups.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Encoder {
+    // The state, counting from zero for the lowest disparity
+    state: usize
+}
+
+impl Encoder {
+    /// An encoder in the first state.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// The current state, from 1 to 4 as in the published code table.
+    pub fn state(&self) -> usize {
+        self.state + 1
+    }
+
+    /// Encode the low 4 bits of a value.
+    ///
+    /// **returns** The triple sent for them
+    pub fn push_nibble(&mut self, nibble: u8) -> [Trit; 3] {
+        let trits = triple(CODES[usize::from(nibble & 0xf)][self.state]);
+        self.state = next_state(self.state, &trits).expect("Codes keep the disparity in range");
+        trits
+    }
+
+    /// Encode a byte, high nibble first.
+    ///
+    /// **returns** The two triples sent for it
+    pub fn push_byte(&mut self, byte: u8) -> [Trit; 6] {
+        let mut trits = [Trit::Zero; 6];
+        trits[..3].copy_from_slice(&self.push_nibble(byte >> 4));
+        trits[3..].copy_from_slice(&self.push_nibble(byte));
+        trits
+    }
+}
+
+/// Encode bytes as MMS43 symbols, six per byte, starting from the first state.
+pub fn encode(data: &[u8]) -> Vec<Trit> {
+    let mut encoder = Encoder::new();
+    data.iter().flat_map(|byte| encoder.push_byte(*byte)).collect()
+}
+
+/// Decode MMS43 symbols, sent starting from the first state.
+///
+/// **returns** The bytes, or an error if the number of symbols isn't a
+/// multiple of 6 or a triple is 000 or takes the disparity out of range
+pub fn decode(symbols: &[Trit]) -> Result<Vec<u8>, LineCodeError> {
+    if !symbols.len().is_multiple_of(6) {
+        return Err(LineCodeError::NotMultiple(6));
+    }
+
+    let mut state = 0;
+    let mut nibbles = Vec::with_capacity(symbols.len() / 3);
+    for (idx, trits) in symbols.chunks(3).enumerate() {
+        let invalid = LineCodeError::InvalidSymbol(idx * 3);
+        let nibble = CODES.iter()
+            .position(|codes| codes.iter().any(|code| triple(code) == trits))
+            .ok_or(invalid)?;
+        state = next_state(state, trits).ok_or(invalid)?;
+        nibbles.push(nibble as u8);
+    }
+    Ok(nibbles.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linecode::DcBalance;
+    use crate::random::TritRng;
+
+    fn symbols(encoded: &str) -> Vec<Trit> {
+        encoded.chars().map(Trit::from).collect()
+    }
+
+    #[test]
+    fn code_table() {
+        // Every triple but 000 is used for exactly one group of bits, and
+        // every entry keeps the disparity in range
+        let mut used = Vec::new();
+        for (nibble, codes) in CODES.iter().enumerate() {
+            for (state, code) in codes.iter().enumerate() {
+                assert!(next_state(state, &triple(code)).is_some(), "{:04b} from state {}", nibble, state + 1);
+                if !used.contains(code) {
+                    used.push(*code);
+                }
+                let owner = CODES.iter().position(|codes| codes.contains(code));
+                assert_eq!(owner, Some(nibble));
+            }
+        }
+        assert_eq!(used.len(), 26);
+        assert!(!used.contains(&"000"));
+    }
+
+    #[test]
+    fn state_machine() {
+        let mut encoder = Encoder::new();
+        assert_eq!(encoder.state(), 1);
+        assert_eq!(encoder.push_nibble(0b1100), triple("+++"));
+        assert_eq!(encoder.state(), 4);
+        assert_eq!(encoder.push_nibble(0b1100), triple("-+-"));
+        assert_eq!(encoder.state(), 3);
+        assert_eq!(encoder.push_nibble(0b1001), triple("+-+"));
+        assert_eq!(encoder.push_nibble(0b1001), triple("---"));
+        assert_eq!(encoder.state(), 1);
+        assert_eq!(encoder.push_byte(0x1b), [triple("0-+"), triple("+0-")].concat()[..]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = encode(&data);
+        assert_eq!(encoded.len(), 6 * 256);
+        assert_eq!(decode(&encoded), Ok(data));
+
+        // Between triples the disparity is one of the four states, and within
+        // a triple it can stray at most one further either way
+        let mut rng = TritRng::new(10);
+        let random: Vec<u8> = (0..500).flat_map(|_| rng.next_u64().to_le_bytes()).collect();
+        let balance = DcBalance::measure(&encode(&random));
+        assert!((-1..=0).contains(&balance.min_disparity()) && (3..=4).contains(&balance.max_disparity()));
+        assert!((0..=3).contains(&balance.disparity()));
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        assert_eq!(decode(&symbols("+++")), Err(LineCodeError::NotMultiple(6)));
+        assert_eq!(decode(&symbols("0-+000")), Err(LineCodeError::InvalidSymbol(3)));
+        // +++ twice would raise the disparity beyond the highest state
+        assert_eq!(decode(&symbols("+++++0")), Err(LineCodeError::InvalidSymbol(3)));
+        // --- is only sent from the highest state
+        assert_eq!(decode(&symbols("---0+-")), Err(LineCodeError::InvalidSymbol(0)));
+    }
+}
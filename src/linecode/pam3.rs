@@ -0,0 +1,159 @@
+//! PAM-3, three-level pulse amplitude modulation.
+//!
+//! Each symbol is simply a trit, and groups of bits are packed into groups of
+//! trits as balanced ternary numbers. Three trits would carry almost 4.75
+//! bits, but whole bits must fit into whole trits, so practical links choose a
+//! group size that wastes little:
+//!
+//! * GDDR7 sends 3 bits as 2 trits, leaving one of the 9 trit pairs unused.
+//!   Here that is 00, so every pair has at least one level away from zero.
+//! * USB4 sends 11 bits as 7 trits, using 2048 of the 2187 groups.
+//!
+//! The final group of bits is padded with zero bits.
+
+use crate::linecode::{bits, bytes, LineCodeError};
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// How bits are grouped into trits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pam3Mapping {
+    /// 3 bits to 2 trits, as used by GDDR7
+    ThreeBitsTwoTrits,
+    /// 11 bits to 7 trits, as used by USB4
+    ElevenBitsSevenTrits
+}
+
+impl Pam3Mapping {
+    /// The number of bits in each group.
+    pub fn bits(&self) -> usize {
+        match self {
+            Pam3Mapping::ThreeBitsTwoTrits => 3,
+            Pam3Mapping::ElevenBitsSevenTrits => 11
+        }
+    }
+
+    /// The number of trits each group of bits is sent as.
+    pub fn trits(&self) -> usize {
+        match self {
+            Pam3Mapping::ThreeBitsTwoTrits => 2,
+            Pam3Mapping::ElevenBitsSevenTrits => 7
+        }
+    }
+
+    /// The number of symbols needed to send some number of bytes.
+    pub fn symbols_for(&self, bytes: usize) -> usize {
+        (bytes * 8).div_ceil(self.bits()) * self.trits()
+    }
+
+    /// The balanced value of the trits sent for a group of bits. Groups are
+    /// centred on zero, skipping zero itself for 3 bits to 2 trits so that the
+    /// 00 pair is never sent.
+    fn level(self, group: i64) -> i64 {
+        match self {
+            Pam3Mapping::ThreeBitsTwoTrits if group < 4 => group - 4,
+            Pam3Mapping::ThreeBitsTwoTrits => group - 3,
+            Pam3Mapping::ElevenBitsSevenTrits => group - 1024
+        }
+    }
+
+    /// The group of bits sent as a balanced value, if there is one.
+    fn group(self, level: i64) -> Option<i64> {
+        let group = match self {
+            Pam3Mapping::ThreeBitsTwoTrits if level < 0 => level + 4,
+            Pam3Mapping::ThreeBitsTwoTrits if level == 0 => return None,
+            Pam3Mapping::ThreeBitsTwoTrits => level + 3,
+            Pam3Mapping::ElevenBitsSevenTrits => level + 1024
+        };
+        (0..1 << self.bits()).contains(&group).then_some(group)
+    }
+}
+
+/// Encode bytes as PAM-3 symbols.
+pub fn encode(mapping: Pam3Mapping, data: &[u8]) -> Vec<Trit> {
+    let bits: Vec<bool> = bits(data).collect();
+    bits.chunks(mapping.bits())
+        .flat_map(|chunk| {
+            // Pad the final group, shifting its bits up into place
+            let group = chunk.iter().fold(0, |group, bit| group << 1 | i64::from(*bit)) << (mapping.bits() - chunk.len());
+            let trits = Number::<7>::from_i64(mapping.level(group)).trits().to_owned();
+            trits.into_iter().skip(7 - mapping.trits())
+        })
+        .collect()
+}
+
+/// Decode PAM-3 symbols.
+///
+/// * `mapping` The grouping of bits into trits that was used
+/// * `symbols` The received symbols
+/// * `length` The number of bytes sent, as padding can be longer than a byte
+///
+/// **returns** The bytes, or an error if there are the wrong number of
+/// symbols or a group of symbols doesn't stand for any group of bits
+pub fn decode(mapping: Pam3Mapping, symbols: &[Trit], length: usize) -> Result<Vec<u8>, LineCodeError> {
+    if symbols.len() != mapping.symbols_for(length) {
+        return Err(LineCodeError::WrongLength(mapping.symbols_for(length)));
+    }
+
+    let mut decoded = Vec::with_capacity(symbols.len() * 2);
+    for (idx, chunk) in symbols.chunks(mapping.trits()).enumerate() {
+        let level = i64::from(i32::from(Number::<7>::from_rev_iter(chunk.iter().rev().copied())));
+        let group = mapping.group(level).ok_or(LineCodeError::InvalidSymbol(idx * mapping.trits()))?;
+        decoded.extend((0..mapping.bits()).rev().map(|bit| group >> bit & 1 == 1));
+    }
+    let mut decoded = bytes(decoded);
+    decoded.truncate(length);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linecode::DcBalance;
+
+    fn symbols(encoded: &str) -> Vec<Trit> {
+        encoded.chars().map(Trit::from).collect()
+    }
+
+    #[test]
+    fn three_bits_two_trits() {
+        // 101, 110 and 01 padded to 010 are sent as 5 - 3, 6 - 3 and 2 - 4
+        let encoded = encode(Pam3Mapping::ThreeBitsTwoTrits, &[0b1011_1001]);
+        assert_eq!(encoded, symbols("+-+0-+"));
+        assert_eq!(decode(Pam3Mapping::ThreeBitsTwoTrits, &encoded, 1), Ok(vec![0b1011_1001]));
+
+        // The pair 00 is never sent
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = encode(Pam3Mapping::ThreeBitsTwoTrits, &data);
+        assert!(encoded.chunks(2).all(|pair| pair != [Trit::Zero, Trit::Zero]));
+        assert_eq!(decode(Pam3Mapping::ThreeBitsTwoTrits, &encoded, data.len()), Ok(data));
+        assert_eq!(decode(Pam3Mapping::ThreeBitsTwoTrits, &symbols("+-+000"), 1), Err(LineCodeError::InvalidSymbol(4)));
+    }
+
+    #[test]
+    fn eleven_bits_seven_trits() {
+        let data: Vec<u8> = (0..=255).rev().collect();
+        for length in [0, 1, 3, 4, 11, 256] {
+            let encoded = encode(Pam3Mapping::ElevenBitsSevenTrits, &data[..length]);
+            assert_eq!(encoded.len(), Pam3Mapping::ElevenBitsSevenTrits.symbols_for(length));
+            assert_eq!(decode(Pam3Mapping::ElevenBitsSevenTrits, &encoded, length), Ok(data[..length].to_vec()));
+        }
+        // Three and four bytes both take three groups
+        assert_eq!(Pam3Mapping::ElevenBitsSevenTrits.symbols_for(3), 21);
+        assert_eq!(Pam3Mapping::ElevenBitsSevenTrits.symbols_for(4), 21);
+
+        // +++++++ is 1093, beyond the 1023 used
+        assert_eq!(decode(Pam3Mapping::ElevenBitsSevenTrits, &symbols("+++++++"), 1), Err(LineCodeError::InvalidSymbol(0)));
+        assert_eq!(decode(Pam3Mapping::ElevenBitsSevenTrits, &symbols("++"), 1), Err(LineCodeError::WrongLength(7)));
+    }
+
+    #[test]
+    fn centred_levels() {
+        // Uniform data averages close to zero
+        let data: Vec<u8> = (0..=255).collect();
+        for mapping in [Pam3Mapping::ThreeBitsTwoTrits, Pam3Mapping::ElevenBitsSevenTrits] {
+            let balance = DcBalance::measure(&encode(mapping, &data));
+            assert!(balance.mean().abs() < 0.05, "{:?} has mean {}", mapping, balance.mean());
+        }
+    }
+}
@@ -0,0 +1,38 @@
+/// Builds a balanced ternary `Number` from a literal, validated at compile
+/// time. The literal can either be a trit string using '-', '0' and '+', or a
+/// decimal integer. The number of trits is inferred from the context, and a
+/// literal that is invalid or does not fit is a compile error rather than a
+/// panic at runtime.
+///
+/// ```
+/// use balanced_ternary::bt;
+/// use balanced_ternary::number::Number;
+///
+/// const FIFTY: Number<8> = bt!("+-0--");
+/// assert_eq!(FIFTY, bt!(50));
+/// assert_eq!(-FIFTY, bt!(-50));
+/// ```
+///
+/// Typos in trit strings are caught when compiling:
+///
+/// ```compile_fail
+/// use balanced_ternary::bt;
+/// use balanced_ternary::number::Number;
+///
+/// const TYPO: Number<8> = bt!("+-0=-");
+/// ```
+///
+/// As are values that need more trits than are available:
+///
+/// ```compile_fail
+/// use balanced_ternary::bt;
+/// use balanced_ternary::number::Number;
+///
+/// let too_big: Number<4> = bt!(41);
+/// ```
+#[macro_export]
+macro_rules! bt {
+    ($value:literal) => {
+        const { $crate::number::Number::from_literal(stringify!($value)) }
+    };
+}
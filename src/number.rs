@@ -1,8 +1,16 @@
 mod conversions;
 mod binary_ops;
+mod tritwise_ops;
+#[cfg(feature = "num-traits")]
+mod numeric_traits;
 
+pub use conversions::{OutOfRangeError, ParseNumberError};
+#[cfg(feature = "num-traits")]
+pub use numeric_traits::FromStrRadixError;
+
+use std::hash::{Hash, Hasher};
 use std::iter::Sum;
-use std::ops::{Neg, Shl, ShlAssign};
+use std::ops::{Neg, Shl, ShlAssign, Shr, ShrAssign};
 
 use crate::trit::Trit;
 
@@ -16,12 +24,169 @@ use crate::trit::Trit;
 /// 
 /// * `N` The number of trits to use in the number.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
-pub struct Number<const N: usize> ([Trit; N]);
+pub struct Number<const N: usize> (pub(crate) [Trit; N]);
 
 impl<const N: usize> Number<N> {
     /// A balanced ternary representing zero by having all trits
     /// set to their zero values.
-    const ZERO: Number<N> = Number::<N>([Trit::Zero; N]);
+    pub const ZERO: Number<N> = Number::<N>([Trit::Zero; N]);
+
+    /// The number one, which only has its least significant trit set.
+    pub const ONE: Number<N> = Number::<N>::from_i64(1);
+
+    /// The number negative one, which only has its least significant trit set
+    /// to the negative trit.
+    pub const NEG_ONE: Number<N> = Number::<N>::from_i64(-1);
+
+    /// The largest representable number, with all trits set positive. This is
+    /// (3^N - 1) / 2.
+    pub const MAX: Number<N> = Number::<N>([Trit::Pos; N]);
+
+    /// The smallest representable number, with all trits set negative. As
+    /// balanced ternary is symmetric this is exactly the negation of `MAX`.
+    pub const MIN: Number<N> = Number::<N>([Trit::Neg; N]);
+
+    /// The number of trits used to represent the number.
+    pub const TRITS: usize = N;
+
+    /// Builds a balanced ternary number directly from an array of trits, ordered
+    /// from most- to least-significant as they would be written.
+    /// 
+    /// * `trits` - The trits of the number
+    /// 
+    /// **returns** A balanced ternary number holding the supplied trits
+    pub const fn from_trits(trits: [Trit; N]) -> Self {
+        Number::<N>(trits)
+    }
+
+    /// The trits of this number, ordered from most- to least-significant as
+    /// they would be written.
+    ///
+    /// **returns** The number's trits
+    pub const fn trits(&self) -> &[Trit; N] {
+        &self.0
+    }
+
+    /// Builds a balanced ternary number from a binary integer at compile time.
+    /// Unlike `TryFrom<i64>` this can be used to define constants, with the
+    /// trade-off that an out of range value causes a panic (or a compile error
+    /// when evaluated in a const context).
+    /// 
+    /// * `value` - The integer to convert
+    /// 
+    /// **returns** The equivalent ternary number
+    pub const fn from_i64(value: i64) -> Self {
+        let mut output = Number::<N>::ZERO;
+        let mut remaining = value;
+        let mut idx = 0;
+
+        // Produce trits from least- to most-significant by repeatedly dividing by
+        // three, treating a remainder of 2 as -1 with a carry into the next trit
+        while remaining != 0 {
+            if idx == N {
+                panic!("Value is out of range for the number of trits");
+            }
+
+            let (trit, carry) = match remaining.rem_euclid(3) {
+                0 => (Trit::Zero, 0),
+                1 => (Trit::Pos, 0),
+                _ => (Trit::Neg, 1)
+            };
+            output.0[N-1-idx] = trit;
+            remaining = remaining.div_euclid(3) + carry;
+            idx += 1;
+        }
+        output
+    }
+
+    /// Builds a balanced ternary number from its '-'/'0'/'+' encoding at compile
+    /// time. Unlike `From<&str>` this is strict: any invalid character or any
+    /// non-zero trit beyond the N that fit will cause a panic (or a compile error
+    /// when evaluated in a const context) rather than being truncated.
+    /// 
+    /// * `encoded` - An encoding of the value, where '-' represents -1, '+'
+    ///   represents +1 and '0' represents zero
+    /// 
+    /// **returns** The ternary number represented by the string
+    pub const fn from_trit_str(encoded: &str) -> Self {
+        Number::<N>::from_trit_bytes(encoded.as_bytes())
+    }
+
+    /// Builds a balanced ternary number from the bytes of its '-'/'0'/'+'
+    /// encoding. See `from_trit_str()`.
+    const fn from_trit_bytes(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            panic!("Cannot build a number from an empty trit string");
+        }
+
+        let mut output = Number::<N>::ZERO;
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let trit = match bytes[bytes.len()-1-idx] {
+                b'-' => Trit::Neg,
+                b'0' => Trit::Zero,
+                b'+' => Trit::Pos,
+                _ => panic!("Invalid trit in trit string, expected '-', '0' or '+'")
+            };
+
+            if idx < N {
+                output.0[N-1-idx] = trit;
+            } else if !matches!(trit, Trit::Zero) {
+                panic!("Trit string has too many trits for the number size");
+            }
+            idx += 1;
+        }
+        output
+    }
+
+    /// Builds a balanced ternary number from the source text of a literal, as
+    /// captured by the `bt!` macro. A quoted string is read as a trit string
+    /// and anything else is read as a decimal integer.
+    #[doc(hidden)]
+    pub const fn from_literal(literal: &str) -> Self {
+        let bytes = literal.as_bytes();
+        if bytes.len() >= 2 && bytes[0] == b'"' && bytes[bytes.len()-1] == b'"' {
+            let (_, trits) = bytes.split_at(1);
+            let (trits, _) = trits.split_at(trits.len() - 1);
+            return Number::<N>::from_trit_bytes(trits);
+        }
+
+        let (is_negative, mut idx) = match bytes.first() {
+            Some(b'-') => (true, 1),
+            _ => (false, 0)
+        };
+        if idx == bytes.len() {
+            panic!("Expected a trit string or decimal integer literal");
+        }
+
+        // Accumulate the magnitude unsigned, as i64::MIN has no positive i64
+        let mut magnitude: u64 = 0;
+        while idx < bytes.len() {
+            let digit = match bytes[idx] {
+                b'_' => {
+                    idx += 1;
+                    continue;
+                },
+                digit @ b'0'..=b'9' => (digit - b'0') as u64,
+                _ => panic!("Expected a trit string or decimal integer literal")
+            };
+            magnitude = match magnitude.checked_mul(10) {
+                Some(shifted) => match shifted.checked_add(digit) {
+                    Some(sum) => sum,
+                    None => panic!("Integer literal is too large")
+                },
+                None => panic!("Integer literal is too large")
+            };
+            idx += 1;
+        }
+
+        let value = match (is_negative, magnitude) {
+            (true, magnitude) if magnitude <= i64::MIN.unsigned_abs() => (magnitude as i64).wrapping_neg(),
+            (false, magnitude) if magnitude <= i64::MAX as u64 => magnitude as i64,
+            _ => panic!("Integer literal is too large")
+        };
+        Number::<N>::from_i64(value)
+    }
 
     /// Builds a balanced ternary number of length N from the supplied iterator of trits. The
     /// iterator should be in reverse order to allow the number to be populated from least-
@@ -47,6 +212,53 @@ impl<const N: usize> Number<N> {
         output       
     }
     
+    /// The sign of this number, which in balanced ternary is simply the sign of
+    /// its most significant non-zero trit.
+    /// 
+    /// **returns** `Trit::Pos` for a positive number, `Trit::Neg` for a negative
+    /// number and `Trit::Zero` for zero
+    pub fn signum(&self) -> Trit {
+        self.0.iter()
+            .copied()
+            .find(|trit| *trit != Trit::Zero)
+            .unwrap_or(Trit::Zero)
+    }
+
+    /// Whether this number is greater than zero.
+    pub fn is_positive(&self) -> bool {
+        self.signum() == Trit::Pos
+    }
+
+    /// Whether this number is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.signum() == Trit::Neg
+    }
+
+    /// Whether this number is zero.
+    pub fn is_zero(&self) -> bool {
+        *self == Number::<N>::ZERO
+    }
+
+    /// The absolute value of this number. Unlike two's complement integers this
+    /// can never overflow, as the range of balanced ternary is symmetric and so
+    /// `MIN` has a positive counterpart in `MAX`.
+    /// 
+    /// **returns** The absolute value of this number
+    pub fn abs(self) -> Self {
+        if self.is_negative() {-self} else {self}
+    }
+
+    /// The absolute difference between this number and another. This may
+    /// currently result in an overflow if the difference requires a length
+    /// that is greater than the templated size N, e.g. `MAX.abs_diff(MIN)`.
+    /// 
+    /// * `other` The number to find the difference from
+    /// 
+    /// **returns** The absolute value of the difference of the two numbers
+    pub fn abs_diff(self, other: Self) -> Self {
+        if self < other {other - self} else {self - other}
+    }
+
     /// Increments the number by adding 1. This may result in a positive
     /// wraparound if all trits are already positive.
     pub fn inc(&mut self) {
@@ -86,6 +298,17 @@ impl <const N: usize> Sum for Number<N> {
     }
 }
 
+impl <const N: usize> Hash for Number<N> {
+    /// Hash the trits of this number, most significant first. As every
+    /// number of a given size has the same length no length prefix is
+    /// written, so a trit-native hasher sees exactly N trits.
+    /// 
+    /// * `state` The hasher to write the trits into
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Trit::hash_slice(&self.0, state);
+    }
+}
+
 impl <const N: usize> Shl<usize> for Number<N> {
     type Output = Self;
 
@@ -139,9 +362,59 @@ impl <const N: usize> ShlAssign<usize> for Number<N> {
     }
 }
 
+impl <const N: usize> Shr<usize> for Number<N> {
+    type Output = Self;
+
+    /// Return the result of right-shifting this number by a specified amount
+    /// of trit positions, discarding the trits shifted out of the bottom. As
+    /// each trit is explicitly signed no sign extension is needed, and as the
+    /// discarded trits are always worth less than half of the lowest remaining
+    /// position this has the effect of dividing by 3 and rounding to the
+    /// nearest integer.
+    /// 
+    /// * `positions` The amount of trits to shift the number by
+    /// 
+    /// **returns** The result of right-shifting this number by the specified
+    /// number of trit positions.
+    fn shr(self, positions: usize) -> Self::Output {
+        let mut out = Number::<N>::ZERO;
+
+        // Early exit if we right-shift far enough that our number just becomes zero
+        if positions >= N {
+            return out;
+        }
+
+        // Right shift is just copying the correct trits from our value to the
+        // end of our zero-initialised output number 
+        out.0[positions..].copy_from_slice(&self.0[..(N-positions)]);
+        out
+    }
+}
+
+impl <const N: usize> ShrAssign<usize> for Number<N> {
+    /// In-place right-shift operation of this number by a specified amount
+    /// of trit positions, discarding the trits shifted out of the bottom. This
+    /// has the effect of dividing by 3 and rounding to the nearest integer.
+    ///  
+    /// * `positions` The amount of trits to shift this number by
+    fn shr_assign(&mut self, positions: usize) {
+        // Early exit if we right-shift far enough that our number just becomes zero
+        if positions >= N {
+            self.0.fill(Trit::Zero);
+            return;
+        }
+
+        // Mirror of the in-place left-shift, rotating the other way and zeroing
+        // out the most-significant trits.
+        self.0.rotate_right(positions);
+        self.0[..positions].fill(Trit::Zero);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bt;
 
     #[test]
     fn comparisons() {
@@ -174,6 +447,115 @@ mod tests {
         assert!(num_17 >= num_17_copy);
     }
 
+    #[test]
+    fn constants() {
+        assert_eq!(Number::<8>::ONE, Number::<8>::from("+"));
+        assert_eq!(Number::<8>::NEG_ONE, Number::<8>::from("-"));
+        assert_eq!(Number::<8>::MAX, Number::<8>::from("++++++++"));
+        assert_eq!(Number::<8>::MIN, Number::<8>::from("--------"));
+        assert_eq!(-Number::<8>::MIN, Number::<8>::MAX);
+        assert_eq!(Number::<8>::ONE + Number::<8>::NEG_ONE, Number::<8>::ZERO);
+    }
+
+    #[test]
+    fn const_constructors() {
+        const NUM_50: Number<8> = Number::<8>::from_i64(50);
+        const NUM_NEG_50: Number<8> = Number::<8>::from_trit_str("-+0++");
+        const FROM_TRITS: Number<3> = Number::<3>::from_trits([Trit::Pos, Trit::Zero, Trit::Neg]);
+
+        assert_eq!(NUM_50, Number::<8>::from("+-0--"));
+        assert_eq!(NUM_NEG_50, -NUM_50);
+        assert_eq!(FROM_TRITS, Number::<3>::from("+0-")); // 8
+        assert_eq!(Number::<8>::from_trit_str("0000+-0--"), NUM_50);
+        assert_eq!(Number::<8>::from_i64(3280), Number::<8>::MAX);
+        assert_eq!(Number::<41>::from_i64(i64::MIN), Number::<41>::try_from(i64::MIN).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Value is out of range for the number of trits")]
+    fn const_constructor_out_of_range() {
+        let _ = Number::<8>::from_i64(3281);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid trit in trit string")]
+    fn const_constructor_invalid_trit() {
+        let _ = Number::<8>::from_trit_str("+-T");
+    }
+
+    #[test]
+    fn literal_macro() {
+        const TABLE: [Number<8>; 4] = [bt!("+-0--"), bt!(50), bt!(-50), bt!(1_000)];
+
+        assert_eq!(TABLE[0], Number::<8>::from("+-0--"));
+        assert_eq!(TABLE[1], TABLE[0]);
+        assert_eq!(TABLE[2], -TABLE[0]);
+        assert_eq!(TABLE[3], Number::<8>::try_from(1000_i64).unwrap());
+
+        let inferred: Number<4> = bt!("+0-");
+        assert_eq!(inferred, Number::<4>::from("+0-"));
+
+        let extremes: [Number<41>; 2] = [bt!(-9223372036854775808), bt!(9223372036854775807)];
+        assert_eq!(extremes, [i64::MIN, i64::MAX].map(Number::from_i64));
+    }
+
+    #[test]
+    fn sign_queries() {
+        let num_17 = Number::<8>::from("+-0-");
+        let num_neg_17 = Number::<8>::from("-+0+");
+
+        assert_eq!(num_17.signum(), Trit::Pos);
+        assert_eq!(num_neg_17.signum(), Trit::Neg);
+        assert_eq!(Number::<8>::ZERO.signum(), Trit::Zero);
+
+        assert!(num_17.is_positive() && !num_17.is_negative() && !num_17.is_zero());
+        assert!(num_neg_17.is_negative() && !num_neg_17.is_positive() && !num_neg_17.is_zero());
+        assert!(Number::<8>::ZERO.is_zero() && !Number::<8>::ZERO.is_positive() && !Number::<8>::ZERO.is_negative());
+
+        assert_eq!(Number::<8>::TRITS, 8);
+    }
+
+    #[test]
+    fn absolute_values() {
+        let num_17 = Number::<8>::from("+-0-");
+        let num_neg_17 = Number::<8>::from("-+0+");
+
+        assert_eq!(num_17.abs(), num_17);
+        assert_eq!(num_neg_17.abs(), num_17);
+        assert_eq!(Number::<8>::ZERO.abs(), Number::<8>::ZERO);
+
+        assert_eq!(num_17.abs_diff(num_neg_17), Number::<8>::from("++-+")); // 34
+        assert_eq!(num_neg_17.abs_diff(num_17), Number::<8>::from("++-+"));
+        assert_eq!(num_17.abs_diff(num_17), Number::<8>::ZERO);
+    }
+
+    #[test]
+    fn abs_never_overflows() {
+        // Exhaustively check every value of a small number, including MIN, which
+        // overflows when negated in two's complement
+        assert_eq!(Number::<5>::MIN.abs(), Number::<5>::MAX);
+
+        for value in -121_i64..=121 {
+            let number = Number::<5>::try_from(value).unwrap();
+            let abs = number.abs();
+
+            assert!(!abs.is_negative());
+            assert_eq!(i64::try_from(abs), Ok(value.abs()));
+        }
+    }
+
+    #[test]
+    fn ordering_helpers() {
+        let num_17 = Number::<8>::from("+-0-");
+        let num_neg_17 = Number::<8>::from("-+0+");
+
+        assert_eq!(num_17.min(num_neg_17), num_neg_17);
+        assert_eq!(num_17.max(num_neg_17), num_17);
+        assert_eq!(Number::<8>::MAX.clamp(num_neg_17, num_17), num_17);
+        assert_eq!(Number::<8>::MIN.clamp(num_neg_17, num_17), num_neg_17);
+        assert_eq!(Number::<8>::ZERO.clamp(num_neg_17, num_17), Number::<8>::ZERO);
+    }
+
     #[test]
     fn increments() {
         let num_neg_one = Number::<8>::from("-");
@@ -254,6 +636,35 @@ mod tests {
         assert_eq!(num_neg_8 << 8, Number::<8>::from("00000000"));
     }
 
+    #[test]
+    fn right_shift() {
+        let num_neg_8 = Number::<8>::from("-0+00000");
+
+        assert_eq!(num_neg_8 >> 1, Number::<8>::from("0-0+0000"));
+        assert_eq!(num_neg_8 >> 5, Number::<8>::from("00000-0+"));
+        assert_eq!(num_neg_8 >> 6, Number::<8>::from("000000-0")); // -8 / 3 rounds to -3
+        assert_eq!(num_neg_8 >> 7, Number::<8>::from("0000000-")); // -8 / 9 rounds to -1
+        assert_eq!(num_neg_8 >> 8, Number::<8>::ZERO);
+
+        // Right shifting divides by 3, rounding to the nearest integer
+        assert_eq!(Number::<8>::from("+-") >> 1, Number::<8>::from("+")); // 2 / 3 rounds to 1
+        assert_eq!(Number::<8>::from("++") >> 1, Number::<8>::from("+")); // 4 / 3 rounds to 1
+    }
+
+    #[test]
+    fn in_place_right_shift() {
+        let mut shifting_num = Number::<8>::from("-0+00000");
+
+        shifting_num >>= 1;
+        assert_eq!(shifting_num, Number::<8>::from("0-0+0000"));
+        shifting_num >>= 4;
+        assert_eq!(shifting_num, Number::<8>::from("00000-0+"));
+        shifting_num >>= 2;
+        assert_eq!(shifting_num, Number::<8>::from("0000000-"));
+        shifting_num >>= 1;
+        assert_eq!(shifting_num, Number::<8>::ZERO);
+    }
+
     #[test]
     fn in_place_left_shift() {
         let mut shifting_num = Number::<8>::from("-0+"); // -8
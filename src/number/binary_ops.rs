@@ -1,5 +1,5 @@
 use std::iter::from_fn;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
 
 use crate::number::Number;
 use crate::sum_result::SumResult;
@@ -166,40 +166,14 @@ impl <const N: usize> Div for Number<N> {
     /// results towards zero rather than negative infinity, as the symmetry between
     /// positive and negative is a defining feature of balanced ternary.
     ///
-    /// If the divisor is zero then the program will exit with an error mesage. In
-    /// future integer division by zero will be handled gracefully with an error
-    /// result or exception.
+    /// If the divisor is zero then the program will exit with an error mesage. Use
+    /// `checked_div()` to handle division by zero gracefully.
     /// 
     /// * `divisor` the number to integer divide this number by
     /// 
     /// **returns** the result of integer dividing this number by the supplied divisor
     fn div(self, divisor: Self) -> Self::Output {
-        if divisor == Number::<N>::ZERO {
-            panic!("Attempt to divide by zero")
-        }
-
-        // Integer division implemented with a repeated subtraction approach. We
-        // convert numerator and divisor to positive to perform the division, and
-        // then decide whether to flip the result based on if they originally had
-        // different signs.
-
-        let numerator_is_negative = self < Number::<N>::ZERO;
-        let mut abs_remainder = if numerator_is_negative {-self} else {self};
-
-        let divisor_is_negative = divisor < Number::<N>::ZERO;
-        let abs_divisor = if divisor_is_negative {-divisor} else {divisor};
-
-        let mut quotient = Number::<N>::ZERO;
-        while abs_remainder >= abs_divisor {
-            abs_remainder -= abs_divisor;
-            quotient.inc();
-        }
-
-        if numerator_is_negative ^ divisor_is_negative {
-            -quotient
-        } else {
-            quotient
-        }
+        self.div_rem(divisor).0
     }
 }
 
@@ -209,9 +183,8 @@ impl <const N: usize> DivAssign for Number<N> {
     /// towards zero rather than negative infinity, as the symmetry between
     /// positive and negative is a defining feature of balanced ternary.
     ///
-    /// If the divisor is zero then the program will exit with an error mesage. In
-    /// future integer division by zero will be handled gracefully with an error
-    /// result or exception.
+    /// If the divisor is zero then the program will exit with an error mesage. Use
+    /// `checked_div()` to handle division by zero gracefully.
     /// 
     /// * `divisor` the number to integer divide this number by
     fn div_assign(&mut self, divisor: Self) {
@@ -219,6 +192,246 @@ impl <const N: usize> DivAssign for Number<N> {
     }
 }
 
+impl <const N: usize> Rem for Number<N> {
+    type Output = Self;
+
+    /// Calculate the remainder of integer dividing this ternary number by the
+    /// supplied divisor. As division rounds towards zero the remainder always
+    /// takes the sign of this number, matching the behaviour of Rust's primitive
+    /// integer types.
+    ///
+    /// If the divisor is zero then the program will exit with an error mesage.
+    /// 
+    /// * `divisor` the number to integer divide this number by
+    /// 
+    /// **returns** the remainder of integer dividing this number by the supplied
+    /// divisor
+    fn rem(self, divisor: Self) -> Self::Output {
+        self.div_rem(divisor).1
+    }
+}
+
+impl <const N: usize> RemAssign for Number<N> {
+    /// In-place replacement of this ternary number with the remainder of integer
+    /// dividing it by the supplied divisor. The remainder takes the sign of this
+    /// number.
+    ///
+    /// If the divisor is zero then the program will exit with an error mesage.
+    /// 
+    /// * `divisor` the number to integer divide this number by
+    fn rem_assign(&mut self, divisor: Self) {
+        *self = *self % divisor;
+    }
+}
+
+impl<const N: usize> Number<N> {
+    /// Add this ternary number to another, also returning the carry trit that
+    /// was produced from the most significant position. A non-zero carry means
+    /// that the true sum could not be represented in N trits and the returned
+    /// number has wrapped around.
+    /// 
+    /// * `rhs` The number to add this number to
+    /// 
+    /// **returns** The (possibly wrapped) sum and the carry out of the highest trit
+    pub fn overflowing_add(self, rhs: Self) -> (Self, Trit) {
+        let mut sum = self;
+        let mut carry = Trit::Zero;
+        sum.0.iter_mut().rev()
+            .zip(rhs.0.iter().rev())
+            .for_each(|(lhs, rhs)| {
+                let SumResult { result, carry: new_carry} = lhs.add_with_carry(rhs, &carry);
+                carry = new_carry;
+                *lhs = result;
+            });
+        (sum, carry)
+    }
+
+    /// Add this ternary number to another, returning `None` instead of wrapping
+    /// around if the sum cannot be represented in N trits.
+    /// 
+    /// * `rhs` The number to add this number to
+    /// 
+    /// **returns** The sum of the two numbers, or `None` on overflow
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, Trit::Zero) => Some(sum),
+            _ => None
+        }
+    }
+
+    /// Subtract another ternary number from this one, returning `None` instead
+    /// of wrapping around if the difference cannot be represented in N trits.
+    /// 
+    /// * `rhs` The number to subtract from this one
+    /// 
+    /// **returns** The difference of the two numbers, or `None` on overflow
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        // Negation can never overflow in balanced ternary, so this reduces
+        // cleanly to a checked addition
+        self.checked_add(-rhs)
+    }
+
+    /// Multiply this ternary number with another, returning `None` instead of
+    /// wrapping around if the product cannot be represented in N trits.
+    /// 
+    /// * `rhs` The number to multiply this number with
+    /// 
+    /// **returns** The product of the two numbers, or `None` on overflow
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.widening_mul(rhs) {
+            (low, high) if high.is_zero() => Some(low),
+            _ => None
+        }
+    }
+
+    /// Integer divide this ternary number by the supplied divisor, returning
+    /// `None` rather than panicking if the divisor is zero. Division itself can
+    /// never overflow in balanced ternary as there is no asymmetric minimum value.
+    /// 
+    /// * `divisor` the number to integer divide this number by
+    /// 
+    /// **returns** The quotient rounded towards zero, or `None` for a zero divisor
+    pub fn checked_div(self, divisor: Self) -> Option<Self> {
+        (!divisor.is_zero()).then(|| self.div_rem(divisor).0)
+    }
+
+    /// Calculate the remainder of integer dividing this ternary number by the
+    /// supplied divisor, returning `None` rather than panicking if the divisor
+    /// is zero.
+    /// 
+    /// * `divisor` the number to integer divide this number by
+    /// 
+    /// **returns** The remainder of the division, or `None` for a zero divisor
+    pub fn checked_rem(self, divisor: Self) -> Option<Self> {
+        (!divisor.is_zero()).then(|| self.div_rem(divisor).1)
+    }
+
+    /// Raise this ternary number to the power of the supplied exponent by
+    /// repeated squaring. This may currently result in an overflow or underflow
+    /// if the result requires a length that is greater than the templated size N.
+    /// 
+    /// * `exponent` The power to raise this number to
+    /// 
+    /// **returns** This number raised to the supplied power
+    pub fn pow(self, exponent: u32) -> Self {
+        let mut result = Number::<N>::ONE;
+        let mut base = self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Raise this ternary number to the power of the supplied exponent,
+    /// returning `None` instead of wrapping around if the result cannot be
+    /// represented in N trits.
+    /// 
+    /// * `exponent` The power to raise this number to
+    /// 
+    /// **returns** This number raised to the supplied power, or `None` on overflow
+    pub fn checked_pow(self, exponent: u32) -> Option<Self> {
+        let mut result = Number::<N>::ONE;
+        let mut base = self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            exponent >>= 1;
+            // The final square is never used, so it mustn't cause an overflow
+            if exponent > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Calculate the full product of this ternary number with another, which
+    /// may require up to 2N trits. This uses the same shift-and-add approach as
+    /// the `Mul` operator but keeps hold of the trits that would otherwise be
+    /// shifted or carried out of the top of the number.
+    /// 
+    /// * `rhs` The number to multiply this number with
+    /// 
+    /// **returns** The low N trits and the high N trits of the product
+    pub fn widening_mul(self, rhs: Self) -> (Self, Self) {
+        let mut low = Number::<N>::ZERO;
+        let mut high = Number::<N>::ZERO;
+
+        for (idx, current_trit) in self.0.iter().rev().enumerate() {
+            let addend = match current_trit {
+                Trit::Neg => -rhs,
+                Trit::Zero => continue,
+                Trit::Pos => rhs
+            };
+
+            // The shifted addend straddles both halves; the trits that a left
+            // shift would discard become the lowest trits of the high half.
+            let high_addend = Number::<N>::from_rev_iter(addend.0[..idx].iter().rev().copied());
+            let (new_low, carry) = low.overflowing_add(addend << idx);
+            low = new_low;
+            high += high_addend;
+            high += carry;
+        }
+
+        (low, high)
+    }
+
+    /// Calculate both the quotient and remainder of integer dividing this number
+    /// by the supplied divisor, with the quotient rounded towards zero.
+    /// 
+    /// If the divisor is zero then the program will exit with an error mesage.
+    /// 
+    /// * `divisor` the number to integer divide this number by
+    /// 
+    /// **returns** The quotient and the remainder of the division
+    pub fn div_rem(self, divisor: Self) -> (Self, Self) {
+        if divisor.is_zero() {
+            panic!("Attempt to divide by zero")
+        }
+
+        // Integer division implemented with a shift-and-subtract approach. We
+        // convert numerator and divisor to positive to perform the division, and
+        // then decide whether to flip the results based on their original signs.
+        // Negation can never overflow, so the absolute values are always valid.
+
+        let numerator_is_negative = self.is_negative();
+        let mut abs_remainder = self.abs();
+
+        let divisor_is_negative = divisor.is_negative();
+        let abs_divisor = divisor.abs();
+
+        // Work from the highest trit position downwards, subtracting the divisor
+        // shifted to that position for as long as it fits. As the remainder is
+        // always less than three times the shifted divisor from the previous
+        // position, this takes at most two subtractions per position.
+        let mut quotient = Number::<N>::ZERO;
+        for positions in (0..N).rev() {
+            // If shifting would push non-zero trits off the top then the shifted
+            // divisor is larger than any number we could be holding
+            if abs_divisor.0[..positions].iter().any(|trit| *trit != Trit::Zero) {
+                continue;
+            }
+
+            let shifted_divisor = abs_divisor << positions;
+            let place_value = Number::<N>::ONE << positions;
+            while abs_remainder >= shifted_divisor {
+                abs_remainder -= shifted_divisor;
+                quotient += place_value;
+            }
+        }
+
+        let quotient = if numerator_is_negative ^ divisor_is_negative {-quotient} else {quotient};
+        let remainder = if numerator_is_negative {-abs_remainder} else {abs_remainder};
+        (quotient, remainder)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,5 +509,91 @@ mod tests {
         let num_0: Number<8> = Number::<8>::ZERO;
 
         let _ = num_neg_61 / num_0;
-    }  
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn remainders() {
+        let num_59 = Number::<8>::from("+-+--");
+        let num_60 = Number::<8>::from("+-+-0");
+        let num_12 = Number::<8>::from("++0");
+
+        assert_eq!(num_59 % num_12, Number::<8>::from("++-")); // 59 % 12 = 11
+        assert_eq!(num_60 % num_12, Number::<8>::ZERO); // 60 % 12 = 0
+
+        // Remainder takes the sign of the numerator
+        assert_eq!(-num_59 %  num_12, Number::<8>::from("--+")); // -59 %  12 = -11
+        assert_eq!( num_59 % -num_12, Number::<8>::from("++-")); //  59 % -12 =  11
+
+        let mut temp = num_59;
+        temp %= num_12;
+        assert_eq!(temp, Number::<8>::from("++-"));
+    }
+
+    #[test]
+    fn division_of_extreme_values() {
+        let num_max = Number::<8>::from("++++++++"); // 3280
+        let num_min = -num_max;
+
+        assert_eq!(num_max / num_max, Number::<8>::from("+"));
+        assert_eq!(num_min / num_max, Number::<8>::from("-"));
+        assert_eq!(num_max / Number::<8>::from("+"), num_max);
+        assert_eq!(num_max / Number::<8>::from("+-"), Number::<8>::from("+-+-+-+-")); // 3280 / 2 = 1640
+        assert_eq!(num_max % Number::<8>::from("+-"), Number::<8>::ZERO);
+        assert_eq!(num_max / Number::<8>::from("+0+"), Number::<8>::from("++00++")); // 3280 / 10 = 328
+    }
+
+    #[test]
+    fn checked_operations() {
+        let num_max = Number::<8>::from("++++++++"); // 3280
+        let num_one = Number::<8>::from("+");
+        let num_two = Number::<8>::from("+-");
+        let num_0 = Number::<8>::ZERO;
+
+        assert_eq!(num_max.checked_add(num_one), None);
+        assert_eq!(num_max.checked_add(-num_one), Some(Number::<8>::from("+++++++0")));
+        assert_eq!((-num_max).checked_sub(num_one), None);
+        assert_eq!(num_max.checked_sub(num_max), Some(num_0));
+
+        assert_eq!(num_max.checked_mul(num_two), None);
+        assert_eq!(num_max.checked_mul(-num_one), Some(-num_max));
+        // 1640 * 2 fits even though 1640 * 3 does not
+        assert_eq!(Number::<8>::from("+-+-+-+-").checked_mul(num_two), Some(num_max));
+
+        assert_eq!(num_max.checked_div(num_0), None);
+        assert_eq!(num_max.checked_rem(num_0), None);
+        assert_eq!(num_max.checked_div(num_two), Some(Number::<8>::from("+-+-+-+-")));
+    }
+
+    #[test]
+    fn overflowing_results() {
+        let num_max = Number::<8>::from("++++++++");
+        let num_one = Number::<8>::from("+");
+
+        assert_eq!(num_max.overflowing_add(num_one), (Number::<8>::from("--------"), Trit::Pos));
+        assert_eq!(num_max.overflowing_add(-num_one), (Number::<8>::from("+++++++0"), Trit::Zero));
+
+        // 3280 * 3280 = 10758400 = 1640 * 6561 - 1640
+        let (low, high) = num_max.widening_mul(num_max);
+        assert_eq!(low, Number::<8>::from("-+-+-+-+"));
+        assert_eq!(high, Number::<8>::from("+-+-+-+-"));
+    }
+
+    #[test]
+    fn powers() {
+        let num_two = Number::<8>::from("+-");
+
+        assert_eq!(num_two.pow(0), Number::<8>::from("+"));
+        assert_eq!(num_two.pow(5), Number::<8>::from("++--")); // 2^5 = 32
+        assert_eq!((-num_two).pow(3), Number::<8>::from("-0+")); // -2^3 = -8
+        assert_eq!(num_two.checked_pow(11), Some(Number::<8>::from("+0-++0--"))); // 2^11 = 2048
+        assert_eq!(num_two.checked_pow(12), None);
+        assert_eq!(Number::<8>::from_i64(57).checked_pow(2), Some(Number::<8>::from_i64(3249)));
+        assert_eq!(Number::<8>::from_i64(57).checked_pow(3), None);
+
+        // Large exponents take logarithmic time
+        assert_eq!((-Number::<8>::ONE).checked_pow(u32::MAX), Some(-Number::<8>::ONE));
+        assert_eq!(Number::<8>::ONE.checked_pow(u32::MAX), Some(Number::<8>::ONE));
+        assert_eq!(Number::<8>::ZERO.checked_pow(u32::MAX), Some(Number::<8>::ZERO));
+        assert_eq!(num_two.checked_pow(u32::MAX), None);
+    }
+}
@@ -1,6 +1,12 @@
+use std::error::Error;
 use std::fmt;
+use std::io::{self, Read, Write};
+
+use std::iter::from_fn;
+use std::str::FromStr;
 
 use crate::{number::Number, trit::Trit};
+use crate::packing::{self, LengthHeader, PackingError};
 
 impl <const N: usize> From<&str> for Number<N> {
     /// Convert the specified encoded string to its equivalent ternary number
@@ -10,8 +16,8 @@ impl <const N: usize> From<&str> for Number<N> {
     /// characters are used.
     /// 
     /// * `encoded` An encoding of the value to initialise the ternary
-    /// number with, where '-' represents -1, '+' represents +1 and '0'
-    /// represents zero.
+    ///   number with, where '-' represents -1, '+' represents +1 and '0'
+    ///   represents zero.
     fn from(encoded: &str) -> Self {
         // View character slice as slice of trits, starting from right
         // hand size (lowest significant trit)
@@ -23,6 +29,59 @@ impl <const N: usize> From<&str> for Number<N> {
     }
 }
 
+/// Error returned when a string cannot be parsed as a balanced ternary number.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseNumberError {
+    /// The string was empty
+    Empty,
+    /// The string contained a character other than '-', '0' or '+'
+    InvalidTrit(char),
+    /// The string had non-zero trits beyond the N that the number can hold
+    OutOfRange
+}
+
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseNumberError::Empty => write!(f, "cannot parse number from empty string"),
+            ParseNumberError::InvalidTrit(c) => write!(f, "invalid trit '{}'", c),
+            ParseNumberError::OutOfRange => write!(f, "too many trits for number size")
+        }
+    }
+}
+
+impl Error for ParseNumberError {}
+
+impl<const N: usize> FromStr for Number<N> {
+    type Err = ParseNumberError;
+
+    /// Parse the specified encoded string as a ternary number, reporting an
+    /// error rather than panicking or truncating. Shorter strings are left-
+    /// padded with zero-trits, while longer strings are only accepted if the
+    /// excess leading trits are all zero.
+    /// 
+    /// * `encoded` An encoding of the value using '-', '0' and '+'
+    /// 
+    /// **returns** The parsed number, or an error describing why the string is
+    /// not a valid number of N trits
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        if encoded.is_empty() {
+            return Err(ParseNumberError::Empty);
+        }
+
+        let trits = encoded.chars()
+            .map(|c| Trit::from_char(c).ok_or(ParseNumberError::InvalidTrit(c)))
+            .collect::<Result<Vec<Trit>, _>>()?;
+
+        let excess = trits.len().saturating_sub(N);
+        if trits[..excess].iter().any(|trit| *trit != Trit::Zero) {
+            return Err(ParseNumberError::OutOfRange);
+        }
+
+        Ok(Number::<N>::from_rev_iter(trits.into_iter().rev()))
+    }
+}
+
 impl<const N: usize> From<Number<N>> for i32 {
     /// The value of this number in traditional signed 32-bit representation.
     /// 
@@ -41,6 +100,135 @@ impl<const N: usize> From<Number<N>> for i32 {
     }
 }
 
+/// Error returned when a value cannot be converted between a balanced ternary
+/// number and a binary integer type because it falls outside the range that
+/// the destination type can represent.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OutOfRangeError;
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "value out of range for the destination type")
+    }
+}
+
+impl Error for OutOfRangeError {}
+
+impl<const N: usize> TryFrom<i128> for Number<N> {
+    type Error = OutOfRangeError;
+
+    /// Convert a binary integer into its balanced ternary representation. Trits
+    /// are produced from least- to most-significant by repeatedly dividing by
+    /// three, where a remainder of 2 is instead treated as a remainder of -1
+    /// with a carry into the next position.
+    /// 
+    /// * `value` The integer to convert
+    /// 
+    /// **returns** The equivalent ternary number, or an error if it needs more
+    /// than N trits
+    fn try_from(value: i128) -> Result<Self, Self::Error> {
+        let mut remaining = value;
+        let trits = from_fn(|| {
+            let (trit, carry) = match remaining.rem_euclid(3) {
+                0 => (Trit::Zero, 0),
+                1 => (Trit::Pos, 0),
+                _ => (Trit::Neg, 1)
+            };
+            remaining = remaining.div_euclid(3) + carry;
+            Some(trit)
+        }).take(N);
+
+        let number = Number::<N>::from_rev_iter(trits);
+        if remaining == 0 {Ok(number)} else {Err(OutOfRangeError)}
+    }
+}
+
+impl<const N: usize> TryFrom<i64> for Number<N> {
+    type Error = OutOfRangeError;
+
+    /// Convert a binary integer into its balanced ternary representation.
+    /// 
+    /// * `value` The integer to convert
+    /// 
+    /// **returns** The equivalent ternary number, or an error if it needs more
+    /// than N trits
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Number::<N>::try_from(i128::from(value))
+    }
+}
+
+impl<const N: usize> TryFrom<Number<N>> for i128 {
+    type Error = OutOfRangeError;
+
+    /// The value of this number in signed 128-bit representation, accumulated
+    /// from the most significant trit downwards.
+    /// 
+    /// **returns** This number in signed 128-bit representation, or an error if
+    /// the number is too large to be represented
+    fn try_from(number: Number<N>) -> Result<i128, Self::Error> {
+        number.0.iter()
+            .try_fold(0_i128, |acc, trit| acc.checked_mul(3)?.checked_add(i8::from(*trit).into()))
+            .ok_or(OutOfRangeError)
+    }
+}
+
+impl<const N: usize> TryFrom<Number<N>> for i64 {
+    type Error = OutOfRangeError;
+
+    /// The value of this number in signed 64-bit representation.
+    /// 
+    /// **returns** This number in signed 64-bit representation, or an error if
+    /// the number is too large to be represented
+    fn try_from(number: Number<N>) -> Result<i64, Self::Error> {
+        i128::try_from(number)?.try_into().map_err(|_| OutOfRangeError)
+    }
+}
+
+impl<const N: usize> Number<N> {
+    /// Pack this number into bytes using the five-trits-per-byte wire format
+    /// described in the `packing` module. Trits are packed from least to most
+    /// significant, so the first byte holds the five lowest trits.
+    /// 
+    /// **returns** The `packed_len(N)` bytes holding this number
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let trits: Vec<Trit> = self.0.iter().rev().copied().collect();
+        packing::pack_trits(&trits, LengthHeader::Omitted)
+    }
+
+    /// Unpack a number from bytes produced by `to_bytes()`.
+    /// 
+    /// * `bytes` Exactly `packed_len(N)` packed bytes
+    /// 
+    /// **returns** The unpacked number, or an error if the bytes are not a
+    /// valid packing of N trits
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PackingError> {
+        let trits = packing::unpack_trits(bytes, LengthHeader::Omitted, N)?;
+        Ok(Number::<N>::from_rev_iter(trits.into_iter()))
+    }
+
+    /// Write this number to the supplied writer in packed form.
+    /// 
+    /// * `writer` The destination for the packed bytes
+    /// 
+    /// **returns** Success, or any error from the writer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Read a number in packed form from the supplied reader, consuming exactly
+    /// `packed_len(N)` bytes.
+    /// 
+    /// * `reader` The source of the packed bytes
+    /// 
+    /// **returns** The unpacked number, or an error from the reader. Invalid
+    /// packed bytes are reported as `io::ErrorKind::InvalidData`.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = vec![0; packing::packed_len(N)];
+        reader.read_exact(&mut bytes)?;
+        Ok(Number::<N>::from_bytes(&bytes)?)
+    }
+}
+
 impl<const N: usize> fmt::Display for Number<N> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for i in 0..N {
@@ -60,4 +248,59 @@ mod tests {
         
         assert_eq!(format!("{}", num_50), "000+-0-- (50)");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fallible_parsing() {
+        assert_eq!("+-0--".parse::<Number<8>>(), Ok(Number::<8>::from("+-0--")));
+        assert_eq!("000000+-0--".parse::<Number<8>>(), Ok(Number::<8>::from("+-0--")));
+        assert_eq!("+00000000".parse::<Number<8>>(), Err(ParseNumberError::OutOfRange));
+        assert_eq!("+-1".parse::<Number<8>>(), Err(ParseNumberError::InvalidTrit('1')));
+        assert_eq!("".parse::<Number<8>>(), Err(ParseNumberError::Empty));
+    }
+
+    #[test]
+    fn integer_conversions() {
+        let num_50 = Number::<8>::from("+-0--");
+        let num_neg_50 = Number::<8>::from("-+0++");
+
+        assert_eq!(Number::<8>::try_from(50_i64), Ok(num_50));
+        assert_eq!(Number::<8>::try_from(-50_i64), Ok(num_neg_50));
+        assert_eq!(i64::try_from(num_50), Ok(50));
+        assert_eq!(i64::try_from(num_neg_50), Ok(-50));
+
+        // 8 trits can hold values from -3280 to 3280
+        assert_eq!(Number::<8>::try_from(3280_i64), Ok(Number::<8>::from("++++++++")));
+        assert_eq!(Number::<8>::try_from(3281_i64), Err(OutOfRangeError));
+        assert_eq!(Number::<8>::try_from(-3281_i64), Err(OutOfRangeError));
+
+        // 41 trits are enough to hold every i64, but not every number of 41 trits
+        // can be held in an i64
+        let num_min = Number::<41>::try_from(i64::MIN).unwrap();
+        assert_eq!(i64::try_from(num_min), Ok(i64::MIN));
+        assert_eq!(i64::try_from(Number::<41>::from("+".repeat(41).as_str())), Err(OutOfRangeError));
+    }
+
+    #[test]
+    fn packed_bytes() {
+        let num_50 = Number::<8>::from("+-0--");
+        let num_neg_50 = Number::<8>::from("-+0++");
+
+        // Low five trits hold 50 + 121, high three trits hold 0 + 121
+        assert_eq!(num_50.to_bytes(), vec![171, 121]);
+        assert_eq!(num_neg_50.to_bytes(), vec![71, 121]);
+        assert_eq!(Number::<8>::from_bytes(&[171, 121]), Ok(num_50));
+
+        assert_eq!(Number::<8>::from_bytes(&[171]), Err(PackingError::WrongLength { expected: 2, actual: 1 }));
+        assert_eq!(Number::<8>::from_bytes(&[171, 255]), Err(PackingError::InvalidByte { position: 1, value: 255 }));
+        assert_eq!(Number::<8>::from_bytes(&[171, 242]), Err(PackingError::NonZeroPadding));
+
+        let mut buffer = Vec::new();
+        num_50.write_to(&mut buffer).unwrap();
+        num_neg_50.write_to(&mut buffer).unwrap();
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(Number::<8>::read_from(&mut reader).unwrap(), num_50);
+        assert_eq!(Number::<8>::read_from(&mut reader).unwrap(), num_neg_50);
+        assert_eq!(Number::<8>::read_from(&mut reader).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,315 @@
+//! Implementations of the `num-traits` numeric traits for ternary numbers, so
+//! that they can be used with generic numeric code. These are only available
+//! when the `num-traits` feature is enabled.
+//!
+//! `PrimInt` is deliberately not implemented. Most of its requirements (bit
+//! counting, byte swapping, big/little endian conversion and the bitwise
+//! operators) are defined in terms of a binary two's complement representation
+//! and have no meaningful equivalent for a number made of trits.
+
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+use num_traits::{
+    Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedNeg, CheckedRem, CheckedSub, FromPrimitive,
+    Num, NumCast, One, Pow, Signed, ToPrimitive, WrappingAdd, WrappingMul, WrappingNeg, WrappingSub,
+    Zero
+};
+
+use crate::number::{Number, OutOfRangeError};
+
+impl<const N: usize> Zero for Number<N> {
+    fn zero() -> Self {
+        Number::<N>::ZERO
+    }
+
+    fn is_zero(&self) -> bool {
+        Number::<N>::is_zero(self)
+    }
+}
+
+impl<const N: usize> One for Number<N> {
+    fn one() -> Self {
+        Number::<N>::ONE
+    }
+}
+
+/// Error returned when parsing a ternary number from a string in a given
+/// radix fails, either because the string is not a valid integer or because
+/// the value cannot be represented in N trits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FromStrRadixError {
+    /// The string isn't an integer in the radix
+    Invalid(ParseIntError),
+    /// The integer doesn't fit in N trits
+    OutOfRange(OutOfRangeError)
+}
+
+impl fmt::Display for FromStrRadixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FromStrRadixError::Invalid(err) => write!(f, "{}", err),
+            FromStrRadixError::OutOfRange(err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl Error for FromStrRadixError {}
+
+impl<const N: usize> Num for Number<N> {
+    type FromStrRadixErr = FromStrRadixError;
+
+    /// Parse a conventionally written signed integer in the given radix, such
+    /// as "-42" in radix 10 or "-1120" in radix 3. Balanced ternary strings
+    /// should instead be parsed with `From<&str>`.
+    ///
+    /// * `src` The string to parse
+    /// * `radix` The radix the string is written in, from 2 to 36
+    ///
+    /// **returns** The parsed number, or an error if the string is invalid or
+    /// the value needs more than N trits
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        let value = i128::from_str_radix(src, radix).map_err(FromStrRadixError::Invalid)?;
+        Number::<N>::try_from(value).map_err(FromStrRadixError::OutOfRange)
+    }
+}
+
+impl<const N: usize> Signed for Number<N> {
+    fn abs(&self) -> Self {
+        Number::<N>::abs(*self)
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self <= other {Number::<N>::ZERO} else {*self - *other}
+    }
+
+    fn signum(&self) -> Self {
+        Number::<N>::from_rev_iter(std::iter::once(Number::<N>::signum(self)))
+    }
+
+    fn is_positive(&self) -> bool {
+        Number::<N>::is_positive(self)
+    }
+
+    fn is_negative(&self) -> bool {
+        Number::<N>::is_negative(self)
+    }
+}
+
+impl<const N: usize> Bounded for Number<N> {
+    fn min_value() -> Self {
+        Number::<N>::MIN
+    }
+
+    fn max_value() -> Self {
+        Number::<N>::MAX
+    }
+}
+
+impl<const N: usize> CheckedAdd for Number<N> {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Number::<N>::checked_add(*self, *rhs)
+    }
+}
+
+impl<const N: usize> CheckedSub for Number<N> {
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Number::<N>::checked_sub(*self, *rhs)
+    }
+}
+
+impl<const N: usize> CheckedMul for Number<N> {
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Number::<N>::checked_mul(*self, *rhs)
+    }
+}
+
+impl<const N: usize> CheckedDiv for Number<N> {
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Number::<N>::checked_div(*self, *rhs)
+    }
+}
+
+impl<const N: usize> CheckedRem for Number<N> {
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        Number::<N>::checked_rem(*self, *rhs)
+    }
+}
+
+impl<const N: usize> CheckedNeg for Number<N> {
+    fn checked_neg(&self) -> Option<Self> {
+        Some(-*self)
+    }
+}
+
+// The arithmetic operators already wrap around on overflow, so the wrapping
+// traits simply delegate to them.
+
+impl<const N: usize> WrappingAdd for Number<N> {
+    fn wrapping_add(&self, rhs: &Self) -> Self {
+        *self + *rhs
+    }
+}
+
+impl<const N: usize> WrappingSub for Number<N> {
+    fn wrapping_sub(&self, rhs: &Self) -> Self {
+        *self - *rhs
+    }
+}
+
+impl<const N: usize> WrappingMul for Number<N> {
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        *self * *rhs
+    }
+}
+
+impl<const N: usize> WrappingNeg for Number<N> {
+    fn wrapping_neg(&self) -> Self {
+        -*self
+    }
+}
+
+impl<const N: usize> Pow<u32> for Number<N> {
+    type Output = Self;
+
+    fn pow(self, exponent: u32) -> Self::Output {
+        Number::<N>::pow(self, exponent)
+    }
+}
+
+impl<const N: usize> ToPrimitive for Number<N> {
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(*self).ok()
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        i128::try_from(*self).ok()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i128()?.try_into().ok()
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        self.to_i128()?.try_into().ok()
+    }
+}
+
+impl<const N: usize> FromPrimitive for Number<N> {
+    fn from_i64(n: i64) -> Option<Self> {
+        Number::<N>::try_from(n).ok()
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        Number::<N>::try_from(n).ok()
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Number::<N>::try_from(n as i128).ok()
+    }
+
+    fn from_u128(n: u128) -> Option<Self> {
+        Number::<N>::try_from(i128::try_from(n).ok()?).ok()
+    }
+}
+
+impl<const N: usize> NumCast for Number<N> {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_i128().and_then(Number::<N>::from_i128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // NumCast is left out as its `from` would be ambiguous with `From<&str>`
+    use num_traits::{
+        Bounded, CheckedAdd, FromPrimitive, Num, One, Pow, Signed, ToPrimitive, WrappingAdd,
+        WrappingMul, Zero
+    };
+
+    use super::{FromStrRadixError, Number, OutOfRangeError};
+
+    /// A generic algorithm written only against num-traits, to check that
+    /// ternary numbers can be plugged in unchanged.
+    fn gcd<T: Num + Signed + Copy>(a: T, b: T) -> T {
+        if b.is_zero() {a.abs()} else {gcd(b, a % b)}
+    }
+
+    /// Sums the values with overflow detection, as generic code would.
+    fn checked_sum<T: Zero + CheckedAdd>(values: &[T]) -> Option<T> {
+        values.iter().try_fold(T::zero(), |acc, value| acc.checked_add(value))
+    }
+
+    #[test]
+    fn generic_algorithms() {
+        let num_84 = Number::<8>::from("+00+0");
+        let num_neg_36 = Number::<8>::from("--00");
+
+        assert_eq!(gcd(num_84, num_neg_36), Number::<8>::from("++0")); // gcd is 12
+        assert_eq!(gcd(84_i32, -36_i32), 12);
+
+        let num_max = Number::<8>::max_value();
+        assert_eq!(checked_sum(&[num_84, num_neg_36]), Some(Number::<8>::from("+--+0"))); // 48
+        assert_eq!(checked_sum(&[num_max, num_84]), None);
+    }
+
+    #[test]
+    fn identities_and_bounds() {
+        assert!(Number::<8>::zero().is_zero());
+        assert_eq!(Number::<8>::one(), Number::<8>::from("+"));
+        assert_eq!(Number::<8>::min_value(), Number::<8>::from("--------"));
+        assert_eq!(Number::<8>::max_value(), Number::<8>::from("++++++++"));
+        assert_eq!(-Number::<8>::min_value(), Number::<8>::max_value());
+    }
+
+    #[test]
+    fn signed_behaviour() {
+        let num_neg_17 = Number::<8>::from("-+0+");
+        let num_17 = Number::<8>::from("+-0-");
+
+        assert_eq!(num_neg_17.abs(), num_17);
+        assert_eq!(Number::<8>::min_value().abs(), Number::<8>::max_value());
+        assert_eq!(Signed::signum(&num_neg_17), Number::<8>::from("-"));
+        assert_eq!(Signed::signum(&Number::<8>::zero()), Number::<8>::zero());
+        assert!(num_17.is_positive());
+        assert!(num_neg_17.is_negative());
+        assert_eq!(num_17.abs_sub(&num_neg_17), Number::<8>::from("++-+")); // 34
+        assert_eq!(num_neg_17.abs_sub(&num_17), Number::<8>::zero());
+    }
+
+    #[test]
+    fn parsing_with_radix() {
+        assert_eq!(Number::<8>::from_str_radix("-50", 10), Ok(Number::<8>::from("-+0++")));
+        assert_eq!(Number::<8>::from_str_radix("1212", 3), Ok(Number::<8>::from("+-0--"))); // 50
+        assert_eq!(Number::<8>::from_str_radix("ff", 16), Ok(Number::<8>::from("+00++0"))); // 255
+        assert_eq!(
+            Number::<8>::from_str_radix("3281", 10),
+            Err(FromStrRadixError::OutOfRange(OutOfRangeError))
+        );
+        assert!(matches!(Number::<8>::from_str_radix("+-0", 10), Err(FromStrRadixError::Invalid(_))));
+    }
+
+    #[test]
+    fn primitive_conversions() {
+        let num_neg_50 = Number::<8>::from("-+0++");
+
+        assert_eq!(num_neg_50.to_i64(), Some(-50));
+        assert_eq!(num_neg_50.to_u64(), None);
+        assert_eq!((-num_neg_50).to_u8(), Some(50));
+        assert_eq!(Number::<8>::from_i32(-50), Some(num_neg_50));
+        assert_eq!(Number::<8>::from_u64(u64::MAX), None);
+        assert_eq!(Number::<8>::from_f64(-50.7), Some(num_neg_50));
+        assert_eq!(<Number<8> as num_traits::NumCast>::from(-50_i16), Some(num_neg_50));
+    }
+
+    #[test]
+    fn wrapping_and_powers() {
+        let num_max = Number::<8>::max_value();
+        let num_two = Number::<8>::from("+-");
+
+        assert_eq!(num_max.wrapping_add(&Number::<8>::one()), Number::<8>::min_value());
+        assert_eq!(num_max.wrapping_mul(&Number::<8>::one()), num_max);
+        assert_eq!(Pow::pow(num_two, 5_u32), Number::<8>::from("++--")); // 32
+    }
+}
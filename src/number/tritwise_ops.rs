@@ -0,0 +1,131 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use crate::number::Number;
+
+impl <const N: usize> Not for Number<N> {
+    type Output = Self;
+
+    /// Tritwise logical NOT of the ternary number. As ternary NOT is negation
+    /// this is identical to unary negation of the number.
+    /// 
+    /// **return** The tritwise NOT of this ternary number
+    fn not(self) -> Self::Output {
+        Self(self.0.map(|trit| !trit))
+    }
+}
+
+impl <const N: usize> BitAnd for Number<N> {
+    type Output = Self;
+
+    /// Tritwise logical AND of this ternary number with another, taking the
+    /// minimum of each pair of matching-index trits.
+    /// 
+    /// * `rhs` The number to combine with this number
+    /// 
+    /// **returns** The tritwise AND of the two numbers
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let mut out = self;
+        out &= rhs;
+        out
+    }
+}
+
+impl <const N: usize> BitAndAssign for Number<N> {
+    /// In-place tritwise logical AND of another ternary number into this one.
+    /// 
+    /// * `rhs` The number to combine into this number
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0.iter_mut()
+            .zip(rhs.0)
+            .for_each(|(lhs, rhs)| *lhs = *lhs & rhs);
+    }
+}
+
+impl <const N: usize> BitOr for Number<N> {
+    type Output = Self;
+
+    /// Tritwise logical OR of this ternary number with another, taking the
+    /// maximum of each pair of matching-index trits.
+    /// 
+    /// * `rhs` The number to combine with this number
+    /// 
+    /// **returns** The tritwise OR of the two numbers
+    fn bitor(self, rhs: Self) -> Self::Output {
+        let mut out = self;
+        out |= rhs;
+        out
+    }
+}
+
+impl <const N: usize> BitOrAssign for Number<N> {
+    /// In-place tritwise logical OR of another ternary number into this one.
+    /// 
+    /// * `rhs` The number to combine into this number
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0.iter_mut()
+            .zip(rhs.0)
+            .for_each(|(lhs, rhs)| *lhs = *lhs | rhs);
+    }
+}
+
+impl <const N: usize> BitXor for Number<N> {
+    type Output = Self;
+
+    /// Tritwise logical XOR of this ternary number with another, which is the
+    /// negated product of each pair of matching-index trits.
+    /// 
+    /// * `rhs` The number to combine with this number
+    /// 
+    /// **returns** The tritwise XOR of the two numbers
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        let mut out = self;
+        out ^= rhs;
+        out
+    }
+}
+
+impl <const N: usize> BitXorAssign for Number<N> {
+    /// In-place tritwise logical XOR of another ternary number into this one.
+    /// 
+    /// * `rhs` The number to combine into this number
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0.iter_mut()
+            .zip(rhs.0)
+            .for_each(|(lhs, rhs)| *lhs = *lhs ^ rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tritwise_operations() {
+        let lhs = Number::<9>::from("---000+++");
+        let rhs = Number::<9>::from("-0+-0+-0+");
+
+        assert_eq!(!lhs, Number::<9>::from("+++000---"));
+        assert_eq!(!lhs, -lhs);
+        assert_eq!(lhs & rhs, Number::<9>::from("----00-0+"));
+        assert_eq!(lhs | rhs, Number::<9>::from("-0+00++++"));
+        assert_eq!(lhs ^ rhs, Number::<9>::from("-0+000+0-"));
+    }
+
+    #[test]
+    fn in_place_tritwise_operations() {
+        let lhs = Number::<9>::from("---000+++");
+        let rhs = Number::<9>::from("-0+-0+-0+");
+
+        let mut temp = lhs;
+        temp &= rhs;
+        assert_eq!(temp, Number::<9>::from("----00-0+"));
+
+        temp = lhs;
+        temp |= rhs;
+        assert_eq!(temp, Number::<9>::from("-0+00++++"));
+
+        temp = lhs;
+        temp ^= rhs;
+        assert_eq!(temp, Number::<9>::from("-0+000+0-"));
+    }
+}
@@ -0,0 +1,297 @@
+//! A compact binary wire format for trits, packing five trits into each byte.
+//!
+//! As 3^5 = 243 fits within the 256 values of a byte, five trits is the most
+//! that can be packed into each byte. Each group of five trits is stored as its
+//! balanced ternary value offset by 121 so that it falls in the range 0 to 242.
+//! This is the same as reading the group as an ordinary unbalanced ternary
+//! number with digits 0, 1 and 2 in place of '-', '0' and '+'. Byte values of
+//! 243 and above are never produced and are rejected when unpacking.
+//!
+//! Trit sequences are packed in order, with the first trit of each group of five
+//! becoming the least significant digit of its byte. A final partial group is
+//! padded with zero trits. Numbers are packed starting from their least
+//! significant trit, so their bytes are little-endian.
+//!
+//! An optional length header can be written ahead of a trit sequence, holding
+//! the number of trits as a little-endian `u32`. This allows sequences whose
+//! length isn't a multiple of five to be recovered exactly.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::trit::Trit;
+
+/// The number of trits that are packed into each byte.
+pub const TRITS_PER_BYTE: usize = 5;
+
+/// The number of bytes taken by a length header.
+pub const HEADER_BYTES: usize = 4;
+
+/// Whether a packed trit sequence is prefixed with a header holding its length
+/// in trits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LengthHeader {
+    /// No header is written; the reader must know the number of trits
+    Omitted,
+    /// The number of trits is written first as a little-endian `u32`
+    TritCount
+}
+
+/// Error returned when packed bytes cannot be unpacked into trits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PackingError {
+    /// A byte of 243 or greater, which cannot hold five trits, was found at
+    /// the given position
+    InvalidByte { position: usize, value: u8 },
+    /// The number of bytes did not match the number of trits expected
+    WrongLength { expected: usize, actual: usize },
+    /// The padding trits at the end of the final byte were not all zero
+    NonZeroPadding,
+    /// A length header was expected but there were too few bytes to hold it
+    MissingHeader
+}
+
+impl fmt::Display for PackingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackingError::InvalidByte { position, value } =>
+                write!(f, "invalid packed byte {} at position {}", value, position),
+            PackingError::WrongLength { expected, actual } =>
+                write!(f, "expected {} packed bytes but found {}", expected, actual),
+            PackingError::NonZeroPadding => write!(f, "non-zero padding trits in final packed byte"),
+            PackingError::MissingHeader => write!(f, "missing length header")
+        }
+    }
+}
+
+impl Error for PackingError {}
+
+impl From<PackingError> for io::Error {
+    fn from(err: PackingError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// The number of bytes needed to pack the given number of trits, excluding
+/// any length header.
+///
+/// * `trit_count` The number of trits to pack
+///
+/// **returns** The number of packed bytes
+pub const fn packed_len(trit_count: usize) -> usize {
+    trit_count.div_ceil(TRITS_PER_BYTE)
+}
+
+/// Pack a sequence of trits into bytes, five trits to a byte.
+///
+/// * `trits` The trits to pack, in order
+/// * `header` Whether to prefix the bytes with the number of trits
+///
+/// **returns** The packed bytes
+pub fn pack_trits(trits: &[Trit], header: LengthHeader) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_BYTES + packed_len(trits.len()));
+    if header == LengthHeader::TritCount {
+        bytes.extend_from_slice(&header_for(trits.len()));
+    }
+    bytes.extend(trits.chunks(TRITS_PER_BYTE).map(pack_group));
+    bytes
+}
+
+/// Unpack bytes produced by `pack_trits` back into their trits. Without a
+/// header the number of trits must be supplied, and the bytes must be exactly
+/// as many as are needed to hold them.
+///
+/// * `bytes` The packed bytes
+/// * `header` Either `LengthHeader::TritCount` if the bytes start with a length
+///   header, or `LengthHeader::Omitted` if `trit_count` should be used
+/// * `trit_count` The number of trits packed into the bytes when there is no
+///   header; ignored otherwise
+///
+/// **returns** The unpacked trits, or an error if the bytes are not a valid
+/// packing
+pub fn unpack_trits(bytes: &[u8], header: LengthHeader, trit_count: usize) -> Result<Vec<Trit>, PackingError> {
+    let (trit_count, bytes) = match header {
+        LengthHeader::Omitted => (trit_count, bytes),
+        LengthHeader::TritCount => {
+            if bytes.len() < HEADER_BYTES {
+                return Err(PackingError::MissingHeader);
+            }
+            let (header, bytes) = bytes.split_at(HEADER_BYTES);
+            (count_from_header(header.try_into().unwrap()), bytes)
+        }
+    };
+
+    if bytes.len() != packed_len(trit_count) {
+        return Err(PackingError::WrongLength { expected: packed_len(trit_count), actual: bytes.len() });
+    }
+
+    let mut trits = Vec::with_capacity(bytes.len() * TRITS_PER_BYTE);
+    for (position, byte) in bytes.iter().enumerate() {
+        let group = unpack_group(*byte).ok_or(PackingError::InvalidByte { position, value: *byte })?;
+        trits.extend_from_slice(&group);
+    }
+
+    // Padding trits in the final byte must be zero
+    if trits[trit_count..].iter().any(|trit| *trit != Trit::Zero) {
+        return Err(PackingError::NonZeroPadding);
+    }
+
+    trits.truncate(trit_count);
+    Ok(trits)
+}
+
+/// Pack a sequence of trits and write the bytes to the supplied writer.
+///
+/// * `writer` The destination for the packed bytes
+/// * `trits` The trits to pack, in order
+/// * `header` Whether to prefix the bytes with the number of trits
+///
+/// **returns** Success, or any error from the writer
+pub fn write_trits<W: Write>(writer: &mut W, trits: &[Trit], header: LengthHeader) -> io::Result<()> {
+    writer.write_all(&pack_trits(trits, header))
+}
+
+/// Read and unpack a sequence of trits from the supplied reader, consuming
+/// only the bytes that belong to the sequence.
+///
+/// * `reader` The source of the packed bytes
+/// * `header` Either `LengthHeader::TritCount` if a length header should be
+///   read first, or `LengthHeader::Omitted` if `trit_count` should be used
+/// * `trit_count` The number of trits to read when there is no header; ignored
+///   otherwise
+///
+/// **returns** The unpacked trits, or an error from the reader. Invalid packed
+/// bytes are reported as `io::ErrorKind::InvalidData`, and a reader that ends
+/// early as `io::ErrorKind::UnexpectedEof`.
+pub fn read_trits<R: Read>(reader: &mut R, header: LengthHeader, trit_count: usize) -> io::Result<Vec<Trit>> {
+    let trit_count = match header {
+        LengthHeader::Omitted => trit_count,
+        LengthHeader::TritCount => {
+            let mut header = [0; HEADER_BYTES];
+            reader.read_exact(&mut header)?;
+            count_from_header(&header)
+        }
+    };
+
+    // The header may be untrusted, so only allocate for the bytes actually read
+    let byte_count = packed_len(trit_count);
+    let mut bytes = Vec::new();
+    reader.take(byte_count as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != byte_count {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(unpack_trits(&bytes, LengthHeader::Omitted, trit_count)?)
+}
+
+/// Pack up to five trits into a single byte, padding with zero trits.
+fn pack_group(group: &[Trit]) -> u8 {
+    // Zero trits have an offset digit of 1. Digits are accumulated from the
+    // most significant position down, so padding comes first.
+    let padding = TRITS_PER_BYTE - group.len();
+    std::iter::repeat_n(1, padding)
+        .chain(group.iter().rev().map(|trit| (i8::from(*trit) + 1) as u8))
+        .fold(0, |acc, digit| acc * 3 + digit)
+}
+
+/// Unpack a single byte into its five trits, or `None` if the byte is 243 or
+/// greater.
+fn unpack_group(byte: u8) -> Option<[Trit; TRITS_PER_BYTE]> {
+    if byte >= 243 {
+        return None;
+    }
+
+    let mut remaining = byte;
+    Some([(); TRITS_PER_BYTE].map(|_| {
+        let trit = match remaining % 3 {
+            0 => Trit::Neg,
+            1 => Trit::Zero,
+            _ => Trit::Pos
+        };
+        remaining /= 3;
+        trit
+    }))
+}
+
+fn header_for(trit_count: usize) -> [u8; HEADER_BYTES] {
+    u32::try_from(trit_count)
+        .expect("Too many trits for length header")
+        .to_le_bytes()
+}
+
+fn count_from_header(header: &[u8; HEADER_BYTES]) -> usize {
+    u32::from_le_bytes(*header) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trits(encoded: &str) -> Vec<Trit> {
+        encoded.chars().map(Trit::from).collect()
+    }
+
+    #[test]
+    fn group_values() {
+        // Each byte holds its five trits' value plus 121, first trit lowest
+        assert_eq!(pack_trits(&trits("00000"), LengthHeader::Omitted), vec![121]);
+        assert_eq!(pack_trits(&trits("-----"), LengthHeader::Omitted), vec![0]);
+        assert_eq!(pack_trits(&trits("+++++"), LengthHeader::Omitted), vec![242]);
+        assert_eq!(pack_trits(&trits("+0000"), LengthHeader::Omitted), vec![122]);
+        assert_eq!(pack_trits(&trits("0000+"), LengthHeader::Omitted), vec![202]);
+    }
+
+    #[test]
+    fn round_trips() {
+        let sequence = trits("+-0-++0+--0-0+");
+
+        let packed = pack_trits(&sequence, LengthHeader::Omitted);
+        assert_eq!(packed.len(), 3);
+        assert_eq!(unpack_trits(&packed, LengthHeader::Omitted, sequence.len()), Ok(sequence.clone()));
+
+        let with_header = pack_trits(&sequence, LengthHeader::TritCount);
+        assert_eq!(with_header[..HEADER_BYTES], [14, 0, 0, 0]);
+        assert_eq!(unpack_trits(&with_header, LengthHeader::TritCount, 0), Ok(sequence));
+
+        assert_eq!(unpack_trits(&pack_trits(&[], LengthHeader::TritCount), LengthHeader::TritCount, 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn invalid_packings() {
+        assert_eq!(
+            unpack_trits(&[121, 243], LengthHeader::Omitted, 10),
+            Err(PackingError::InvalidByte { position: 1, value: 243 })
+        );
+        assert_eq!(
+            unpack_trits(&[121], LengthHeader::Omitted, 10),
+            Err(PackingError::WrongLength { expected: 2, actual: 1 })
+        );
+        // 202 has a '+' in its fifth trit, which is padding for a four trit sequence
+        assert_eq!(unpack_trits(&[202], LengthHeader::Omitted, 4), Err(PackingError::NonZeroPadding));
+        assert_eq!(unpack_trits(&[1, 0], LengthHeader::TritCount, 0), Err(PackingError::MissingHeader));
+    }
+
+    #[test]
+    fn streams() {
+        let first = trits("+-0-+");
+        let second = trits("0+--0-0");
+
+        let mut buffer = Vec::new();
+        write_trits(&mut buffer, &first, LengthHeader::TritCount).unwrap();
+        write_trits(&mut buffer, &second, LengthHeader::TritCount).unwrap();
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(read_trits(&mut reader, LengthHeader::TritCount, 0).unwrap(), first);
+        assert_eq!(read_trits(&mut reader, LengthHeader::TritCount, 0).unwrap(), second);
+        assert!(reader.is_empty());
+
+        let error = read_trits(&mut [250_u8].as_slice(), LengthHeader::Omitted, 5).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A header claiming billions of trits fails without allocating for them
+        let error = read_trits(&mut [255_u8, 255, 255, 255, 121].as_slice(), LengthHeader::TritCount, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        let error = read_trits(&mut [121_u8].as_slice(), LengthHeader::Omitted, 10).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,150 @@
+//! Deterministic pseudo-random trits, for reproducible test vectors.
+//!
+//! `Lfsr` is a linear feedback shift register, which is cheap and has a known
+//! period but is entirely predictable from a few outputs. `TritRng` is a
+//! general purpose generator for uniformly random trits and numbers. Neither
+//! is suitable for cryptography.
+
+mod lfsr;
+
+pub use lfsr::{Lfsr, LfsrError, LfsrForm};
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// 3^40, the largest power of three that fits in a u64.
+const TRITS_PER_WORD_LIMIT: u64 = 12_157_665_459_056_928_801;
+const TRITS_PER_WORD: u32 = 40;
+
+/// A seedable pseudo-random generator of trits.
+///
+/// Words are drawn from xoshiro256**, seeded through SplitMix64 so that
+/// nearby seeds give unrelated sequences. Each word below 3^40 is split into
+/// 40 independent uniform trits, and larger words are discarded so that no
+/// trit value is favoured.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TritRng {
+    state: [u64; 4],
+    // Trits not yet handed out, as an unsigned base 3 number
+    buffer: u64,
+    buffered: u32
+}
+
+impl TritRng {
+    /// Builds a generator whose output is determined entirely by the seed.
+    pub fn new(seed: u64) -> Self {
+        let mut seed = seed;
+        let mut split_mix = || {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        TritRng { state: [split_mix(), split_mix(), split_mix(), split_mix()], buffer: 0, buffered: 0 }
+    }
+
+    /// The next 64 uniformly random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let shifted = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= shifted;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// The next uniformly random trit.
+    pub fn next_trit(&mut self) -> Trit {
+        if self.buffered == 0 {
+            self.buffer = loop {
+                let word = self.next_u64();
+                if word < TRITS_PER_WORD_LIMIT {
+                    break word;
+                }
+            };
+            self.buffered = TRITS_PER_WORD;
+        }
+
+        let digit = self.buffer % 3;
+        self.buffer /= 3;
+        self.buffered -= 1;
+        match digit {
+            0 => Trit::Zero,
+            1 => Trit::Pos,
+            _ => Trit::Neg
+        }
+    }
+
+    /// Fill a slice with uniformly random trits.
+    pub fn fill(&mut self, trits: &mut [Trit]) {
+        trits.iter_mut().for_each(|trit| *trit = self.next_trit());
+    }
+
+    /// A number chosen uniformly from every value of N trits, `MIN` to `MAX`
+    /// inclusive.
+    pub fn next_number<const N: usize>(&mut self) -> Number<N> {
+        let mut trits = [Trit::Zero; N];
+        self.fill(&mut trits);
+        Number::from_trits(trits)
+    }
+}
+
+impl Iterator for TritRng {
+    type Item = Trit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_trit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_limit() {
+        assert_eq!(3_u64.pow(TRITS_PER_WORD), TRITS_PER_WORD_LIMIT);
+        assert_eq!(TRITS_PER_WORD_LIMIT.checked_mul(3), None);
+    }
+
+    #[test]
+    fn reproducible() {
+        let first: Vec<Trit> = TritRng::new(42).take(100).collect();
+        assert_eq!(first, TritRng::new(42).take(100).collect::<Vec<_>>());
+        assert_ne!(first, TritRng::new(43).take(100).collect::<Vec<_>>());
+
+        let mut rng = TritRng::new(7);
+        let copy = rng.clone();
+        rng.next_trit();
+        assert_ne!(rng, copy);
+    }
+
+    #[test]
+    fn uniform_trits() {
+        let mut counts = [0; 3];
+        for trit in TritRng::new(1).take(30_000) {
+            counts[(i8::from(trit) + 1) as usize] += 1;
+        }
+        // Each count is within about five standard deviations of 10,000
+        assert!(counts.iter().all(|count| (9_600..=10_400).contains(count)), "{:?}", counts);
+    }
+
+    #[test]
+    fn uniform_numbers() {
+        let mut rng = TritRng::new(2);
+        let mut counts = [0; 9];
+        for _ in 0..9_000 {
+            let value = i32::from(rng.next_number::<2>());
+            counts[(value + 4) as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| (850..=1_150).contains(count)), "{:?}", counts);
+
+        let mut trits = [Trit::Zero; 50];
+        rng.fill(&mut trits);
+        assert!(trits.contains(&Trit::Neg) && trits.contains(&Trit::Zero) && trits.contains(&Trit::Pos));
+    }
+}
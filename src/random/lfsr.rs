@@ -0,0 +1,223 @@
+//! Linear feedback shift registers over GF(3).
+//!
+//! A register of n trits steps through a sequence determined by a monic
+//! characteristic polynomial of degree n. When that polynomial is primitive,
+//! every nonzero state is visited, so the output repeats only after 3^n - 1
+//! trits, the longest possible period.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::gf3::{mul, sub, Poly3};
+use crate::trit::Trit;
+
+/// How the feedback is applied to the register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LfsrForm {
+    /// The tapped trits are combined into a single new trit shifted in at the
+    /// end, computing the linear recurrence directly
+    Fibonacci,
+    /// The trit shifted out is fed back into every tapped position, which
+    /// multiplies the state by x modulo the characteristic polynomial
+    Galois
+}
+
+/// Error returned when a shift register cannot be built.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LfsrError {
+    /// The characteristic polynomial wasn't monic with a degree of at least one
+    InvalidPolynomial,
+    /// The seed didn't have one trit per register stage, which is this many
+    WrongSeedLength(usize),
+    /// The seed was all zero, which the register never leaves
+    ZeroSeed
+}
+
+impl fmt::Display for LfsrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LfsrError::InvalidPolynomial => write!(f, "characteristic polynomial must be monic and not constant"),
+            LfsrError::WrongSeedLength(length) => write!(f, "seed must have exactly {} trits", length),
+            LfsrError::ZeroSeed => write!(f, "seed must not be all zero")
+        }
+    }
+}
+
+impl Error for LfsrError {}
+
+/// A ternary linear feedback shift register, yielding one trit per step.
+///
+/// The register and the taps are both ordered with the next trit to be output
+/// first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lfsr {
+    state: Vec<Trit>,
+    // The characteristic polynomial below its leading term, highest degree
+    // first
+    taps: Vec<Trit>,
+    form: LfsrForm
+}
+
+impl Lfsr {
+    /// Builds a shift register.
+    ///
+    /// * `polynomial` The monic characteristic polynomial, whose degree is the
+    ///   length of the register
+    /// * `seed` The initial register contents, next output first
+    /// * `form` Whether to use the Fibonacci or Galois feedback
+    ///
+    /// **returns** The register, or an error if the polynomial or seed are
+    /// unusable
+    pub fn new(polynomial: &Poly3, seed: &[Trit], form: LfsrForm) -> Result<Self, LfsrError> {
+        let degree = polynomial.degree()
+            .filter(|degree| *degree > 0 && polynomial.is_monic())
+            .ok_or(LfsrError::InvalidPolynomial)?;
+        if seed.len() != degree {
+            return Err(LfsrError::WrongSeedLength(degree));
+        }
+        if seed.iter().all(|trit| *trit == Trit::Zero) {
+            return Err(LfsrError::ZeroSeed);
+        }
+        Ok(Lfsr { state: seed.to_vec(), taps: polynomial.coefficients()[1..].to_vec(), form })
+    }
+
+    /// Builds a register with the maximal period of 3^degree - 1, using the
+    /// first primitive polynomial of that degree and a seed of a single
+    /// nonzero trit.
+    ///
+    /// **returns** The register, or `None` if the degree is zero or above 40
+    pub fn maximal(degree: usize, form: LfsrForm) -> Option<Self> {
+        if degree == 0 || degree > 40 {
+            return None;
+        }
+        let polynomial = Poly3::primitive_polynomials(degree).next()?;
+        let mut seed = vec![Trit::Zero; degree];
+        seed[degree - 1] = Trit::Pos;
+        Lfsr::new(&polynomial, &seed, form).ok()
+    }
+
+    /// The register contents, next output first.
+    pub fn state(&self) -> &[Trit] {
+        &self.state
+    }
+
+    /// The characteristic polynomial.
+    pub fn polynomial(&self) -> Poly3 {
+        let coefficients: Vec<Trit> = [Trit::Pos].into_iter().chain(self.taps.iter().copied()).collect();
+        Poly3::new(&coefficients)
+    }
+
+    /// How the feedback is applied.
+    pub fn form(&self) -> LfsrForm {
+        self.form
+    }
+
+    /// Step the register once.
+    ///
+    /// **returns** The trit shifted out
+    pub fn step(&mut self) -> Trit {
+        let output = self.state[0];
+        match self.form {
+            LfsrForm::Fibonacci => {
+                // The recurrence s[k + n] = -Σ c[i]s[k + i], where the oldest
+                // trit is paired with the constant coefficient
+                let feedback = self.taps.iter().zip(self.state.iter().rev())
+                    .fold(Trit::Zero, |feedback, (tap, trit)| sub(feedback, mul(*tap, *trit)));
+                self.state.rotate_left(1);
+                *self.state.last_mut().expect("Registers are not empty") = feedback;
+            }
+            LfsrForm::Galois => {
+                // Multiplying by x moves every coefficient up a degree, then
+                // the overflowing x^n is replaced by the rest of the polynomial
+                self.state.rotate_left(1);
+                *self.state.last_mut().expect("Registers are not empty") = Trit::Zero;
+                for (trit, tap) in self.state.iter_mut().zip(&self.taps) {
+                    *trit = sub(*trit, mul(output, *tap));
+                }
+            }
+        }
+        output
+    }
+
+    /// Count the steps until the register returns to its current state.
+    ///
+    /// **returns** The period, or `None` if the state is never revisited,
+    /// which happens when the polynomial is divisible by x
+    pub fn period(&self) -> Option<u64> {
+        let limit = 3_u64.checked_pow(self.state.len() as u32)?;
+        let mut register = self.clone();
+        (1..limit).find(|_| {
+            register.step();
+            register.state == self.state
+        })
+    }
+}
+
+impl Iterator for Lfsr {
+    type Item = Trit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.step())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maximal_length() {
+        for form in [LfsrForm::Fibonacci, LfsrForm::Galois] {
+            for degree in 1..=6 {
+                let register = Lfsr::maximal(degree, form).unwrap();
+                let period = 3_u64.pow(degree as u32) - 1;
+                assert_eq!(register.period(), Some(period), "{:?} of degree {}", form, degree);
+
+                // Over a full period every nonzero window of n trits appears
+                // once, so zero appears once less than each nonzero trit
+                let trits: Vec<Trit> = register.take(period as usize).collect();
+                let count = |value| trits.iter().filter(|trit| **trit == value).count() as u64;
+                let third = 3_u64.pow(degree as u32 - 1);
+                assert_eq!((count(Trit::Neg), count(Trit::Zero), count(Trit::Pos)), (third, third - 1, third));
+            }
+        }
+        assert_eq!(Lfsr::maximal(0, LfsrForm::Galois), None);
+    }
+
+    #[test]
+    fn fibonacci_recurrence() {
+        // x^2 - x - 1 gives s[k + 2] = s[k + 1] + s[k], Fibonacci modulo 3
+        let polynomial: Poly3 = "+--".parse().unwrap();
+        let register = Lfsr::new(&polynomial, &[Trit::Zero, Trit::Pos], LfsrForm::Fibonacci).unwrap();
+        let fibonacci: Vec<i8> = register.take(10).map(i8::from).collect();
+        assert_eq!(fibonacci, [0, 1, 1, -1, 0, -1, -1, 1, 0, 1]);
+        assert!(polynomial.is_primitive());
+    }
+
+    #[test]
+    fn galois_multiplies_by_x() {
+        // x^2 + 1 is irreducible but not primitive, and x has order 4 modulo it
+        let polynomial: Poly3 = "+0+".parse().unwrap();
+        let mut register = Lfsr::new(&polynomial, &[Trit::Zero, Trit::Pos], LfsrForm::Galois).unwrap();
+        assert_eq!(register.polynomial(), polynomial);
+        assert_eq!(register.period(), Some(4));
+        register.step();
+        assert_eq!(register.state(), [Trit::Pos, Trit::Zero]);
+        register.step();
+        assert_eq!(register.state(), [Trit::Zero, Trit::Neg]);
+
+        // x^2 + x is divisible by x, so the starting state is never revisited
+        let reducible: Poly3 = "++0".parse().unwrap();
+        let register = Lfsr::new(&reducible, &[Trit::Pos, Trit::Pos], LfsrForm::Galois).unwrap();
+        assert_eq!(register.period(), None);
+    }
+
+    #[test]
+    fn invalid_registers() {
+        let polynomial: Poly3 = "+0+".parse().unwrap();
+        assert_eq!(Lfsr::new(&polynomial, &[Trit::Pos], LfsrForm::Fibonacci), Err(LfsrError::WrongSeedLength(2)));
+        assert_eq!(Lfsr::new(&polynomial, &[Trit::Zero; 2], LfsrForm::Fibonacci), Err(LfsrError::ZeroSeed));
+        assert_eq!(Lfsr::new(&-&polynomial, &[Trit::Pos; 2], LfsrForm::Fibonacci), Err(LfsrError::InvalidPolynomial));
+        assert_eq!(Lfsr::new(&Poly3::one(), &[], LfsrForm::Galois), Err(LfsrError::InvalidPolynomial));
+    }
+}
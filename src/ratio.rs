@@ -0,0 +1,653 @@
+//! Exact rational numbers over balanced ternary integers.
+//!
+//! A `Ratio<N>` holds a numerator and denominator of N trits each, always
+//! reduced to lowest terms with a positive denominator so that every value has
+//! exactly one representation. All arithmetic is done with `Number`'s own
+//! checked operations, so any width is supported, and the operators panic if a
+//! result doesn't fit rather than wrapping, as a wrapped ratio has no meaning.
+
+mod expansion;
+
+pub use expansion::{expand, ExpandError, Expansion, ParseExpansionError};
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::fixed::Fixed;
+use crate::number::{Number, OutOfRangeError, ParseNumberError};
+use crate::trit::Trit;
+
+/// A rational number with an N trit numerator and denominator.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ratio<const N: usize> {
+    numerator: Number<N>,
+    denominator: Number<N>
+}
+
+/// Error returned when a string cannot be parsed as a ratio.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseRatioError {
+    /// The numerator or denominator was not a valid trit string
+    InvalidNumber(ParseNumberError),
+    /// The denominator was zero
+    ZeroDenominator,
+    /// The string was not a decimal number such as `-12.375`
+    InvalidDecimal,
+    /// The numerator or denominator had too many trits for the number size
+    OutOfRange
+}
+
+impl fmt::Display for ParseRatioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRatioError::InvalidNumber(err) => write!(f, "{}", err),
+            ParseRatioError::ZeroDenominator => write!(f, "denominator is zero"),
+            ParseRatioError::InvalidDecimal => write!(f, "invalid decimal number"),
+            ParseRatioError::OutOfRange => write!(f, "too many trits for number size")
+        }
+    }
+}
+
+impl Error for ParseRatioError {}
+
+impl<const N: usize> Ratio<N> {
+    /// Zero, as 0/1.
+    pub const ZERO: Self = Ratio { numerator: Number::<N>::ZERO, denominator: Number::<N>::ONE };
+
+    /// One, as 1/1.
+    pub const ONE: Self = Ratio { numerator: Number::<N>::ONE, denominator: Number::<N>::ONE };
+
+    /// Builds a ratio reduced to lowest terms with a positive denominator.
+    ///
+    /// * `numerator` The number to divide
+    /// * `denominator` The number to divide by
+    ///
+    /// **returns** The reduced ratio, or `None` if the denominator is zero
+    pub fn new(numerator: Number<N>, denominator: Number<N>) -> Option<Self> {
+        if denominator.is_zero() {
+            return None;
+        }
+
+        // Negation can't overflow in balanced ternary, so neither can
+        // normalising the sign
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+        Some(match denominator.signum() {
+            Trit::Neg => Ratio { numerator: -numerator, denominator: -denominator },
+            _ => Ratio { numerator, denominator }
+        })
+    }
+
+    /// Builds the ratio equal to an integer, with a denominator of one.
+    pub const fn from_integer(integer: Number<N>) -> Self {
+        Ratio { numerator: integer, denominator: Number::<N>::ONE }
+    }
+
+    /// The numerator, which carries the sign of the ratio.
+    pub fn numerator(&self) -> Number<N> {
+        self.numerator
+    }
+
+    /// The denominator, which is always positive.
+    pub fn denominator(&self) -> Number<N> {
+        self.denominator
+    }
+
+    /// Whether the ratio is a whole number, i.e. its denominator is one.
+    pub fn is_integer(&self) -> bool {
+        self.denominator == Number::<N>::ONE
+    }
+
+    /// The sign of the ratio, which is the sign of its numerator.
+    pub fn signum(&self) -> Trit {
+        self.numerator.signum()
+    }
+
+    /// The absolute value, which can't overflow in balanced ternary.
+    pub fn abs(self) -> Self {
+        Ratio { numerator: self.numerator.abs(), denominator: self.denominator }
+    }
+
+    /// The reciprocal, or `None` for zero.
+    pub fn recip(self) -> Option<Self> {
+        Ratio::new(self.denominator, self.numerator)
+    }
+
+    /// Add, cancelling common factors of the denominators first so that
+    /// overflow is only reported when a term of the result really is too
+    /// large.
+    ///
+    /// **returns** The sum, or `None` if it can't be represented in N trits
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let divisor = gcd(self.denominator, rhs.denominator);
+        let (lhs_factor, rhs_factor) = (rhs.denominator / divisor, self.denominator / divisor);
+        let numerator = self.numerator.checked_mul(lhs_factor)?.checked_add(rhs.numerator.checked_mul(rhs_factor)?)?;
+        Ratio::new(numerator, self.denominator.checked_mul(lhs_factor)?)
+    }
+
+    /// Subtract, as the sum with the negated right hand side.
+    ///
+    /// **returns** The difference, or `None` if it can't be represented in N
+    /// trits
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(-rhs)
+    }
+
+    /// Multiply, cancelling common factors across the two ratios first.
+    ///
+    /// **returns** The product, or `None` if it can't be represented in N trits
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let lhs_divisor = gcd(self.numerator, rhs.denominator);
+        let rhs_divisor = gcd(rhs.numerator, self.denominator);
+        let numerator = (self.numerator / lhs_divisor).checked_mul(rhs.numerator / rhs_divisor)?;
+        let denominator = (self.denominator / rhs_divisor).checked_mul(rhs.denominator / lhs_divisor)?;
+        Ratio::new(numerator, denominator)
+    }
+
+    /// Divide by multiplying with the reciprocal.
+    ///
+    /// **returns** The quotient, or `None` if the divisor is zero or the
+    /// quotient can't be represented in N trits
+    pub fn checked_div(self, divisor: Self) -> Option<Self> {
+        self.checked_mul(divisor.recip()?)
+    }
+
+    /// Parse a decimal number such as `-12.375` into the exact ratio it
+    /// represents.
+    ///
+    /// * `decimal` An optional '-' followed by digits, with an optional
+    ///   fractional part after a '.'
+    ///
+    /// **returns** The ratio, or an error if the string isn't a decimal number
+    /// or its value needs more than N trits
+    pub fn from_decimal_str(decimal: &str) -> Result<Self, ParseRatioError> {
+        let (negative, unsigned) = match decimal.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, decimal)
+        };
+        let is_digits = |digits: &str| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) if is_digits(fraction) => (integer, fraction),
+            Some(_) => return Err(ParseRatioError::InvalidDecimal),
+            None => (unsigned, "")
+        };
+        if !is_digits(integer) {
+            return Err(ParseRatioError::InvalidDecimal);
+        }
+
+        let ten = Number::<N>::try_from(10_i64).map_err(|_| ParseRatioError::OutOfRange)?;
+        let mut numerator = Number::<N>::ZERO;
+        let mut denominator = Number::<N>::ONE;
+        for (idx, digit) in integer.chars().chain(fraction.chars()).enumerate() {
+            let digit = Number::<N>::from_i64(digit.to_digit(10).expect("Checked to be a digit").into());
+            numerator = numerator.checked_mul(ten).and_then(|shifted| shifted.checked_add(digit))
+                .ok_or(ParseRatioError::OutOfRange)?;
+            if idx >= integer.len() {
+                denominator = denominator.checked_mul(ten).ok_or(ParseRatioError::OutOfRange)?;
+            }
+        }
+
+        let numerator = if negative { -numerator } else { numerator };
+        Ok(Ratio::new(numerator, denominator).expect("Denominator is a power of ten"))
+    }
+
+    /// Write the value in decimal, rounded to the nearest value with the given
+    /// number of decimal places, with ties rounded away from zero.
+    ///
+    /// * `places` The number of digits after the decimal point
+    ///
+    /// **returns** The decimal representation, e.g. "0.333" for 1/3 to three
+    /// places
+    pub fn to_decimal_string(&self, places: usize) -> String {
+        let (integer, mut remainder) = self.numerator.abs().div_rem(self.denominator);
+        let mut digits = Vec::with_capacity(places);
+        for _ in 0..places {
+            let (digit, next) = times_ten(remainder, self.denominator);
+            digits.push(digit);
+            remainder = next;
+        }
+
+        // Round up if the remainder is at least half the denominator, carrying
+        // through any trailing nines into the integer part
+        let mut integer = integer;
+        if remainder >= self.denominator - remainder {
+            match digits.iter().rposition(|digit| *digit != 9) {
+                Some(idx) => {
+                    digits[idx] += 1;
+                    digits[idx + 1..].fill(0);
+                },
+                None => {
+                    digits.fill(0);
+                    integer.inc();
+                }
+            }
+        }
+
+        let mut output = String::new();
+        if self.is_negative() && !(integer.is_zero() && digits.iter().all(|digit| *digit == 0)) {
+            output.push('-');
+        }
+        output.push_str(&decimal_integer(integer));
+        if places > 0 {
+            output.push('.');
+            output.extend(digits.iter().map(|digit| char::from(b'0' + digit)));
+        }
+        output
+    }
+
+    /// Convert to the nearest fixed-point number, with ties rounded away from
+    /// zero as in `Fixed` division.
+    ///
+    /// **returns** The fixed-point number, or an error if its integer part
+    /// needs more than I trits
+    pub fn to_fixed<const I: usize, const F: usize>(&self) -> Result<Fixed<I, F>, OutOfRangeError> {
+        // Rounding the integer part to the nearest leaves at most a half, which
+        // is the range of F fractional trits
+        let (integer, mut remainder) = self.split_nearest();
+        let mut fraction = [Trit::Zero; F];
+        for trit in fraction.iter_mut() {
+            (*trit, remainder) = next_trit(remainder, self.denominator);
+        }
+
+        let fixed = Fixed::new(resize(integer).ok_or(OutOfRangeError)?, Number::<F>::from_trits(fraction));
+        // The remaining fraction is exactly a half only when the value is
+        // halfway between two fixed-point values
+        let is_tie = remainder.abs() == self.denominator - remainder.abs();
+        if is_tie && remainder.signum() == self.signum() {
+            let step = if self.is_negative() { -Fixed::<I, F>::EPSILON } else { Fixed::<I, F>::EPSILON };
+            return fixed.checked_add(step).ok_or(OutOfRangeError);
+        }
+        Ok(fixed)
+    }
+
+    fn is_negative(&self) -> bool {
+        self.numerator.is_negative()
+    }
+
+    /// Split into the nearest integer and the remainder over the denominator,
+    /// which is no more than half the denominator in magnitude. When the value
+    /// is exactly halfway between two integers the one towards zero is chosen.
+    fn split_nearest(&self) -> (Number<N>, Number<N>) {
+        let (mut integer, mut remainder) = self.numerator.div_rem(self.denominator);
+        if remainder.abs() > self.denominator - remainder.abs() {
+            if remainder.is_negative() {
+                integer.dec();
+                remainder += self.denominator;
+            } else {
+                integer.inc();
+                remainder -= self.denominator;
+            }
+        }
+        (integer, remainder)
+    }
+}
+
+/// The greatest common divisor of two numbers, which is always positive unless
+/// both numbers are zero, when it is one so that it can always be divided by.
+fn gcd<const N: usize>(lhs: Number<N>, rhs: Number<N>) -> Number<N> {
+    let (mut lhs, mut rhs) = (lhs.abs(), rhs.abs());
+    while !rhs.is_zero() {
+        (lhs, rhs) = (rhs, lhs % rhs);
+    }
+    if lhs.is_zero() { Number::<N>::ONE } else { lhs }
+}
+
+/// The next decimal digit of `remainder / denominator`, along with the new
+/// remainder. The remainder is multiplied by ten by repeated addition, reducing
+/// it each time it would reach the denominator so that nothing can overflow.
+///
+/// * `remainder` A remainder from zero up to but not including the denominator
+/// * `denominator` The positive denominator
+fn times_ten<const N: usize>(remainder: Number<N>, denominator: Number<N>) -> (u8, Number<N>) {
+    let room = denominator - remainder;
+    let mut digit = 0;
+    let mut accumulated = Number::<N>::ZERO;
+    for _ in 0..10 {
+        if accumulated >= room {
+            accumulated -= room;
+            digit += 1;
+        } else {
+            accumulated += remainder;
+        }
+    }
+    (digit, accumulated)
+}
+
+/// The next trit of the balanced ternary expansion of `remainder / denominator`,
+/// along with the new remainder.
+///
+/// * `remainder` A remainder no more than half the denominator in magnitude
+/// * `denominator` The positive denominator
+///
+/// **returns** The nearest trit to three times the fraction, and the remainder
+/// after subtracting it, which is again no more than half the denominator
+fn next_trit<const N: usize>(remainder: Number<N>, denominator: Number<N>) -> (Trit, Number<N>) {
+    // Three times the fraction is more than a half exactly when the remainder
+    // is more than a sixth of the denominator. Subtracting the denominator
+    // first keeps every intermediate value within it, so nothing can overflow.
+    let over_sixth = |remainder: Number<N>| match Number::<N>::try_from(6_i64) {
+        Ok(six) => remainder > denominator / six,
+        // Numbers of fewer than three trits are small enough to compare directly
+        Err(_) => 6 * i32::from(remainder) > i32::from(denominator)
+    };
+    let (trit, base) = if over_sixth(remainder) {
+        (Trit::Pos, remainder - denominator)
+    } else if over_sixth(-remainder) {
+        (Trit::Neg, remainder + denominator)
+    } else {
+        (Trit::Zero, remainder)
+    };
+    (trit, base + remainder + remainder)
+}
+
+/// Convert a number to a different width, or `None` if it has nonzero trits
+/// that don't fit.
+fn resize<const FROM: usize, const TO: usize>(number: Number<FROM>) -> Option<Number<TO>> {
+    let excess = FROM.saturating_sub(TO);
+    if number.trits()[..excess].iter().any(|trit| *trit != Trit::Zero) {
+        return None;
+    }
+    Some(Number::<TO>::from_rev_iter(number.trits().iter().rev().copied()))
+}
+
+/// The decimal digits of a non-negative number.
+fn decimal_integer<const N: usize>(number: Number<N>) -> String {
+    let Ok(ten) = Number::<N>::try_from(10_i64) else {
+        // Numbers of fewer than three trits are single digits
+        return i32::from(number).to_string();
+    };
+
+    let mut digits = Vec::new();
+    let mut remaining = number;
+    loop {
+        let (quotient, digit) = remaining.div_rem(ten);
+        digits.push(char::from(b'0' + i32::from(digit) as u8));
+        remaining = quotient;
+        if remaining.is_zero() {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+impl<const N: usize> From<Number<N>> for Ratio<N> {
+    fn from(integer: Number<N>) -> Self {
+        Ratio::from_integer(integer)
+    }
+}
+
+impl<const N: usize, const I: usize, const F: usize> TryFrom<Fixed<I, F>> for Ratio<N> {
+    type Error = OutOfRangeError;
+
+    /// Convert a fixed-point number to the exact ratio it represents, whose
+    /// denominator is a power of three.
+    fn try_from(value: Fixed<I, F>) -> Result<Self, Self::Error> {
+        // The integer and fractional trits together are the value scaled by
+        // 3^F, and any trailing zero trits cancel with the denominator
+        let trits: Vec<Trit> = value.integer().trits().iter().chain(value.fraction().trits()).copied().collect();
+        let cancelled = trits.iter().rev().take(F).take_while(|trit| **trit == Trit::Zero).count();
+        let numerator: Vec<Trit> = trits[..trits.len() - cancelled].iter().copied().skip_while(|trit| *trit == Trit::Zero).collect();
+        let denominator_trits = F - cancelled + 1;
+        if numerator.len() > N || denominator_trits > N {
+            return Err(OutOfRangeError);
+        }
+
+        Ok(Ratio {
+            numerator: Number::<N>::from_rev_iter(numerator.into_iter().rev()),
+            denominator: Number::<N>::ONE << (denominator_trits - 1)
+        })
+    }
+}
+
+impl<const N: usize> Ord for Ratio<N> {
+    /// Compare by cross-multiplying with the full double-width products, so
+    /// any two ratios can be compared without overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (lhs_low, lhs_high) = self.numerator.widening_mul(other.denominator);
+        let (rhs_low, rhs_high) = other.numerator.widening_mul(self.denominator);
+        // Like a single number the high half outweighs any low half
+        (lhs_high, lhs_low).cmp(&(rhs_high, rhs_low))
+    }
+}
+
+impl<const N: usize> PartialOrd for Ratio<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Add for Ratio<N> {
+    type Output = Self;
+
+    /// If the sum can't be represented in N trits then the program will exit
+    /// with an error message. Use `checked_add()` to handle this gracefully.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).expect("Attempt to add ratios with overflow")
+    }
+}
+
+impl<const N: usize> AddAssign for Ratio<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const N: usize> Sub for Ratio<N> {
+    type Output = Self;
+
+    /// If the difference can't be represented in N trits then the program will
+    /// exit with an error message. Use `checked_sub()` to handle this
+    /// gracefully.
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).expect("Attempt to subtract ratios with overflow")
+    }
+}
+
+impl<const N: usize> SubAssign for Ratio<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const N: usize> Mul for Ratio<N> {
+    type Output = Self;
+
+    /// If the product can't be represented in N trits then the program will
+    /// exit with an error message. Use `checked_mul()` to handle this
+    /// gracefully.
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).expect("Attempt to multiply ratios with overflow")
+    }
+}
+
+impl<const N: usize> MulAssign for Ratio<N> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const N: usize> Div for Ratio<N> {
+    type Output = Self;
+
+    /// If the divisor is zero or the quotient can't be represented in N trits
+    /// then the program will exit with an error message. Use `checked_div()`
+    /// to handle this gracefully.
+    fn div(self, divisor: Self) -> Self::Output {
+        if divisor.numerator.is_zero() {
+            panic!("Attempt to divide by zero")
+        }
+        self.checked_div(divisor).expect("Attempt to divide ratios with overflow")
+    }
+}
+
+impl<const N: usize> DivAssign for Ratio<N> {
+    fn div_assign(&mut self, divisor: Self) {
+        *self = *self / divisor;
+    }
+}
+
+impl<const N: usize> Neg for Ratio<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Ratio { numerator: -self.numerator, denominator: self.denominator }
+    }
+}
+
+impl<const N: usize> FromStr for Ratio<N> {
+    type Err = ParseRatioError;
+
+    /// Parse a ratio in trit notation such as "+-/++", or a single trit string
+    /// for an integer. The ratio is reduced to lowest terms.
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        let parse = |trits: &str| trits.parse::<Number<N>>().map_err(|err| match err {
+            ParseNumberError::OutOfRange => ParseRatioError::OutOfRange,
+            err => ParseRatioError::InvalidNumber(err)
+        });
+        match encoded.split_once('/') {
+            Some((numerator, denominator)) =>
+                Ratio::new(parse(numerator)?, parse(denominator)?).ok_or(ParseRatioError::ZeroDenominator),
+            None => Ok(Ratio::from_integer(parse(encoded)?))
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for Ratio<N> {
+    /// Displays the numerator and denominator in trits without leading zeros,
+    /// e.g. `-0/+-` for -3/2.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_trimmed(f, self.numerator)?;
+        write!(f, "/")?;
+        write_trimmed(f, self.denominator)
+    }
+}
+
+fn write_trimmed<const N: usize>(f: &mut fmt::Formatter, number: Number<N>) -> fmt::Result {
+    let trits: Vec<&Trit> = number.trits().iter().skip_while(|trit| **trit == Trit::Zero).collect();
+    if trits.is_empty() {
+        return write!(f, "0");
+    }
+    for trit in trits {
+        write!(f, "{}", trit)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type R = Ratio<8>;
+
+    fn ratio(numerator: i64, denominator: i64) -> R {
+        R::new(Number::from_i64(numerator), Number::from_i64(denominator)).unwrap()
+    }
+
+    #[test]
+    fn normalisation() {
+        let value = ratio(6, -4);
+        assert_eq!(value.numerator(), Number::from_i64(-3));
+        assert_eq!(value.denominator(), Number::from_i64(2));
+        assert_eq!(value.to_string(), "-0/+-");
+        assert_eq!(ratio(0, -5), R::ZERO);
+        assert_eq!(R::ZERO.to_string(), "0/+");
+        assert!(ratio(-9, 3).is_integer());
+        assert_eq!(R::new(Number::ONE, Number::ZERO), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(ratio(1, 3) + ratio(1, 6), ratio(1, 2));
+        assert_eq!(ratio(1, 3) - ratio(1, 2), ratio(-1, 6));
+        assert_eq!(ratio(2, 3) * ratio(3, 4), ratio(1, 2));
+        assert_eq!(ratio(1, 2) / ratio(-1, 4), ratio(-2, 1));
+        assert_eq!(-ratio(5, 7), ratio(-5, 7));
+        assert_eq!(ratio(-5, 7).abs(), ratio(5, 7));
+        assert_eq!(ratio(-5, 7).recip(), Some(ratio(-7, 5)));
+        assert_eq!(R::ZERO.recip(), None);
+
+        // Common factors are cancelled before multiplying, so large terms that
+        // cancel out don't overflow
+        let large = ratio(3280, 3279);
+        assert_eq!(large * ratio(3279, 3280), R::ONE);
+        assert_eq!(large.checked_add(ratio(1, 3278)), None);
+        assert_eq!(R::ONE.checked_div(R::ZERO), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempt to divide by zero")]
+    fn division_by_zero() {
+        let _ = R::ONE / R::ZERO;
+    }
+
+    #[test]
+    fn ordering() {
+        let values = [ratio(-1, 2), ratio(-1, 3), R::ZERO, ratio(1, 3), ratio(1, 2), ratio(3, 2)];
+        for pair in values.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        // Cross products of these overflow 8 trits, but compare correctly
+        assert!(ratio(3280, 3279) < ratio(3279, 3278));
+        assert!(ratio(-3280, 3279) < ratio(3279, 3280));
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!("+-/++".parse::<R>(), Ok(ratio(1, 2)));
+        assert_eq!("-0".parse::<R>(), Ok(ratio(-3, 1)));
+        assert_eq!("+/0".parse::<R>(), Err(ParseRatioError::ZeroDenominator));
+        assert_eq!("+/".parse::<R>(), Err(ParseRatioError::InvalidNumber(ParseNumberError::Empty)));
+        assert_eq!("+x/+".parse::<R>(), Err(ParseRatioError::InvalidNumber(ParseNumberError::InvalidTrit('x'))));
+        assert_eq!("+000000000/+".parse::<R>(), Err(ParseRatioError::OutOfRange));
+    }
+
+    #[test]
+    fn decimal_strings() {
+        assert_eq!(R::from_decimal_str("-0.125"), Ok(ratio(-1, 8)));
+        assert_eq!(R::from_decimal_str("3"), Ok(ratio(3, 1)));
+        assert_eq!(R::from_decimal_str("2.50"), Ok(ratio(5, 2)));
+        assert_eq!(R::from_decimal_str("1."), Err(ParseRatioError::InvalidDecimal));
+        assert_eq!(R::from_decimal_str(".5"), Err(ParseRatioError::InvalidDecimal));
+        assert_eq!(R::from_decimal_str("1e3"), Err(ParseRatioError::InvalidDecimal));
+        assert_eq!(R::from_decimal_str("0.000001"), Err(ParseRatioError::OutOfRange));
+
+        assert_eq!(ratio(1, 3).to_decimal_string(4), "0.3333");
+        assert_eq!(ratio(2, 3).to_decimal_string(4), "0.6667");
+        assert_eq!(ratio(-1, 8).to_decimal_string(2), "-0.13");
+        assert_eq!(ratio(5, 2).to_decimal_string(0), "3");
+        assert_eq!(ratio(-1, 3).to_decimal_string(0), "0");
+        assert_eq!(ratio(999, 1000).to_decimal_string(2), "1.00");
+        assert_eq!(ratio(-1234, 1).to_decimal_string(1), "-1234.0");
+        // The remainder times ten would overflow 8 trits
+        assert_eq!(ratio(3279, 3280).to_decimal_string(6), "0.999695");
+    }
+
+    #[test]
+    fn fixed_point_conversions() {
+        type Q = Fixed<4, 3>;
+        let q = |encoded: &str| encoded.parse::<Q>().unwrap();
+
+        assert_eq!(R::try_from(q("+-.0+")), Ok(ratio(19, 9)));
+        assert_eq!(R::try_from(q("-.+")), Ok(ratio(-2, 3)));
+        assert_eq!(R::try_from(q("++++.+++")), Ok(ratio(40 * 27 + 13, 27)));
+        assert_eq!(Ratio::<4>::try_from(q("++++.+++")), Err(OutOfRangeError));
+        assert_eq!(Ratio::<4>::try_from(q(".+")), Ok(Ratio::<4>::new(Number::ONE, Number::from_i64(3)).unwrap()));
+
+        assert_eq!(ratio(19, 9).to_fixed(), Ok(q("+-.0+")));
+        // 1/4 is 6.75/27, which rounds to 7/27
+        assert_eq!(ratio(1, 4).to_fixed(), Ok(q(".+-+")));
+        assert_eq!(ratio(-1, 4).to_fixed(), Ok(q(".-+-")));
+        // Ties round away from zero, as in fixed-point division
+        assert_eq!(ratio(1, 2).to_fixed(), Ok(q("+.---")));
+        assert_eq!(ratio(-1, 2).to_fixed(), Ok(q("-.+++")));
+        assert_eq!(ratio(41, 1).to_fixed::<4, 3>(), Err(OutOfRangeError));
+        assert_eq!(ratio(-7, 2).to_fixed(), Ok(Q::try_from(-3.5).unwrap()));
+
+        // Narrow ratios can't hold six, but still expand correctly
+        let half = Ratio::<2>::new(Number::ONE, Number::from_i64(2)).unwrap();
+        assert_eq!(half.to_fixed::<1, 2>(), Ok("+.--".parse().unwrap()));
+    }
+}
@@ -0,0 +1,315 @@
+//! Balanced ternary expansions of fractions, with their repeating trits.
+//!
+//! Every fraction has a balanced ternary expansion that either terminates or
+//! eventually repeats, written with the repeating trits in parentheses, e.g.
+//! `0.+(-0)` for 5/24 is 0.+-0-0-0… The integer part is the nearest integer,
+//! so the fractional trits are never more than a half in magnitude.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::number::{Number, ParseNumberError};
+use crate::ratio::{next_trit, Ratio};
+use crate::trit::Trit;
+
+/// The balanced ternary expansion of a fraction, split into its integer part,
+/// the fractional trits before any repetition, and the trits that repeat.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Expansion<const N: usize> {
+    integer: Number<N>,
+    prefix: Vec<Trit>,
+    period: Vec<Trit>
+}
+
+/// Error returned when a fraction cannot be expanded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExpandError {
+    /// The denominator was zero
+    ZeroDenominator,
+    /// The expansion didn't terminate or repeat within this many trits
+    TooLong(usize)
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpandError::ZeroDenominator => write!(f, "denominator is zero"),
+            ExpandError::TooLong(limit) => write!(f, "expansion does not repeat within {} trits", limit)
+        }
+    }
+}
+
+impl Error for ExpandError {}
+
+/// Error returned when a string cannot be parsed as a repeating expansion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseExpansionError {
+    /// The integer part or a fractional trit was invalid
+    InvalidNumber(ParseNumberError),
+    /// The parentheses around the repeating trits were missing, empty or not
+    /// at the end
+    InvalidSyntax
+}
+
+impl fmt::Display for ParseExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseExpansionError::InvalidNumber(err) => write!(f, "{}", err),
+            ParseExpansionError::InvalidSyntax => write!(f, "repeating trits must be a non-empty group in parentheses at the end")
+        }
+    }
+}
+
+impl Error for ParseExpansionError {}
+
+/// Expand the fraction p/q into balanced ternary, finding where its trits
+/// start to repeat. The expansion repeats once a remainder recurs, which takes
+/// at most q trits, so `max_trits` bounds the work for large denominators.
+///
+/// * `numerator` The numerator p
+/// * `denominator` The denominator q
+/// * `max_trits` The most fractional trits to generate before giving up
+///
+/// **returns** The expansion, or an error if the denominator is zero or the
+/// expansion doesn't repeat within `max_trits`
+pub fn expand<const N: usize>(numerator: Number<N>, denominator: Number<N>, max_trits: usize) -> Result<Expansion<N>, ExpandError> {
+    let ratio = Ratio::new(numerator, denominator).ok_or(ExpandError::ZeroDenominator)?;
+    let (integer, mut remainder) = ratio.split_nearest();
+
+    // The next trit depends only on the remainder, so the first remainder to
+    // recur marks the start of the period
+    let mut seen = BTreeMap::new();
+    let mut trits = Vec::new();
+    while !remainder.is_zero() {
+        if let Some(&start) = seen.get(&remainder) {
+            let period = trits.split_off(start);
+            return Ok(Expansion { integer, prefix: trits, period });
+        }
+        if trits.len() == max_trits {
+            return Err(ExpandError::TooLong(max_trits));
+        }
+
+        seen.insert(remainder, trits.len());
+        let (trit, next) = next_trit(remainder, ratio.denominator);
+        trits.push(trit);
+        remainder = next;
+    }
+
+    Ok(Expansion { integer, prefix: trits, period: Vec::new() })
+}
+
+impl<const N: usize> Expansion<N> {
+    /// The integer part, which is the nearest integer to the value.
+    pub fn integer(&self) -> Number<N> {
+        self.integer
+    }
+
+    /// The fractional trits before the repeating ones.
+    pub fn prefix(&self) -> &[Trit] {
+        &self.prefix
+    }
+
+    /// The trits that repeat forever after the prefix, which is empty for a
+    /// terminating expansion.
+    pub fn period(&self) -> &[Trit] {
+        &self.period
+    }
+
+    /// Whether the expansion ends, i.e. has no repeating period.
+    pub fn is_terminating(&self) -> bool {
+        self.period.is_empty()
+    }
+
+    /// All of the fractional trits, which continue forever with the period or
+    /// with zeros once a terminating expansion ends.
+    pub fn fractional_trits(&self) -> impl Iterator<Item = Trit> + '_ {
+        let repeating: &[Trit] = if self.period.is_empty() { &[Trit::Zero] } else { &self.period };
+        self.prefix.iter().chain(repeating.iter().cycle()).copied()
+    }
+
+    /// The exact value of the expansion. With a prefix of a trits with value P
+    /// and a period of b trits with value Q, the fractional part is
+    /// P/3^a + Q/(3^a × (3^b - 1)).
+    ///
+    /// **returns** The ratio, or `None` if it or a term in calculating it
+    /// doesn't fit in N trits
+    pub fn to_ratio(&self) -> Option<Ratio<N>> {
+        let prefix_scale = power_of_three::<N>(self.prefix.len())?;
+        let mut ratio = Ratio::from_integer(self.integer)
+            .checked_add(Ratio::new(trits_value(&self.prefix)?, prefix_scale)?)?;
+        if !self.period.is_empty() {
+            let period_scale = power_of_three::<N>(self.period.len())? - Number::<N>::ONE;
+            ratio = ratio.checked_add(Ratio::new(trits_value(&self.period)?, prefix_scale.checked_mul(period_scale)?)?)?;
+        }
+        Some(ratio)
+    }
+}
+
+/// 3^exponent, or `None` if it needs more than N trits.
+fn power_of_three<const N: usize>(exponent: usize) -> Option<Number<N>> {
+    (exponent < N).then(|| Number::<N>::ONE << exponent)
+}
+
+/// The value of a sequence of trits, most significant first, or `None` if it
+/// needs more than N trits.
+fn trits_value<const N: usize>(trits: &[Trit]) -> Option<Number<N>> {
+    let significant = trits.iter().position(|trit| *trit != Trit::Zero).unwrap_or(trits.len());
+    (trits.len() - significant <= N).then(|| Number::<N>::from_rev_iter(trits.iter().rev().copied()))
+}
+
+impl<const N: usize> FromStr for Expansion<N> {
+    type Err = ParseExpansionError;
+
+    /// Parse an expansion such as `0.+(-0)`, with the integer part in trits
+    /// and any repeating fractional trits in parentheses at the end. The
+    /// integer part can be left out if there is a radix point.
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        let (integer, fraction) = match encoded.split_once('.') {
+            Some(("", "")) => return Err(ParseExpansionError::InvalidNumber(ParseNumberError::Empty)),
+            Some(("", fraction)) => (Number::<N>::ZERO, fraction),
+            Some((integer, fraction)) => (integer.parse().map_err(ParseExpansionError::InvalidNumber)?, fraction),
+            None => (encoded.parse().map_err(ParseExpansionError::InvalidNumber)?, "")
+        };
+        let (prefix, period) = match fraction.split_once('(') {
+            Some((prefix, rest)) => match rest.strip_suffix(')') {
+                Some(period) if !period.is_empty() => (prefix, period),
+                _ => return Err(ParseExpansionError::InvalidSyntax)
+            },
+            None => (fraction, "")
+        };
+
+        let parse_trits = |trits: &str| trits.chars()
+            .map(|c| match c {
+                '(' | ')' => Err(ParseExpansionError::InvalidSyntax),
+                c => Trit::from_char(c).ok_or(ParseExpansionError::InvalidNumber(ParseNumberError::InvalidTrit(c)))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        Ok(Expansion { integer, prefix: parse_trits(prefix)?, period: parse_trits(period)? })
+    }
+}
+
+impl<const N: usize> fmt::Display for Expansion<N> {
+    /// Displays the integer part without leading zeros, followed by any
+    /// fractional trits with the repeating ones in parentheses.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let integer: String = self.integer.trits().iter()
+            .skip_while(|trit| **trit == Trit::Zero)
+            .map(|trit| trit.to_string())
+            .collect();
+        write!(f, "{}", if integer.is_empty() { "0" } else { &integer })?;
+        if self.prefix.is_empty() && self.period.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, ".")?;
+        for trit in &self.prefix {
+            write!(f, "{}", trit)?;
+        }
+        if !self.period.is_empty() {
+            write!(f, "(")?;
+            for trit in &self.period {
+                write!(f, "{}", trit)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::Fixed;
+
+    fn expand_i64(numerator: i64, denominator: i64) -> Result<Expansion<8>, ExpandError> {
+        expand(Number::<8>::from_i64(numerator), Number::<8>::from_i64(denominator), 100)
+    }
+
+    #[test]
+    fn expansions() {
+        let expansion = expand_i64(5, 24).unwrap();
+        assert_eq!(expansion.to_string(), "0.+(-0)");
+        assert_eq!(expansion.prefix(), &[Trit::Pos]);
+        assert_eq!(expansion.period(), &[Trit::Neg, Trit::Zero]);
+
+        assert_eq!(expand_i64(1, 3).unwrap().to_string(), "0.+");
+        assert!(expand_i64(1, 3).unwrap().is_terminating());
+        assert_eq!(expand_i64(1, 4).unwrap().to_string(), "0.(+-)");
+        assert_eq!(expand_i64(-4, 3).unwrap().to_string(), "-.-");
+        assert_eq!(expand_i64(6, 3).unwrap().to_string(), "+-");
+        assert_eq!(expand_i64(0, 5).unwrap().to_string(), "0");
+        // The order of 3 modulo 7 is 6
+        assert_eq!(expand_i64(1, 7).unwrap().period().len(), 6);
+    }
+
+    #[test]
+    fn halves_repeat_towards_zero() {
+        // Halves have two expansions, like 0.999… and 1 in decimal, and the
+        // integer part is chosen towards zero
+        assert_eq!(expand_i64(1, 2).unwrap().to_string(), "0.(+)");
+        assert_eq!(expand_i64(-1, 2).unwrap().to_string(), "0.(-)");
+        assert_eq!(expand_i64(7, 2).unwrap().to_string(), "+0.(+)");
+    }
+
+    #[test]
+    fn expansion_errors() {
+        assert_eq!(expand_i64(1, 0), Err(ExpandError::ZeroDenominator));
+        assert_eq!(expand(Number::<8>::ONE, Number::<8>::from_i64(7), 5), Err(ExpandError::TooLong(5)));
+        assert!(expand(Number::<8>::ONE, Number::<8>::from_i64(7), 6).is_ok());
+    }
+
+    #[test]
+    fn parsing_back_to_ratios() {
+        let ratio = |numerator, denominator| Ratio::<8>::new(Number::from_i64(numerator), Number::from_i64(denominator)).unwrap();
+        let parse = |encoded: &str| encoded.parse::<Expansion<8>>().unwrap().to_ratio();
+
+        assert_eq!(parse("0.+(-0)"), Some(ratio(5, 24)));
+        assert_eq!(parse("0.(+)"), Some(ratio(1, 2)));
+        assert_eq!(parse(".(+)"), Some(ratio(1, 2)));
+        // Non-canonical forms still have the right value
+        assert_eq!(parse("+.-(0)"), Some(ratio(2, 3)));
+        assert_eq!(parse("+.(-)"), Some(ratio(1, 2)));
+        assert_eq!(parse("-+"), Some(ratio(-2, 1)));
+        assert_eq!(parse("0.00000000+"), None);
+
+        // Periods can be much longer than the denominator has trits, so use a
+        // width that can hold 3^period. The denominators cover terminating,
+        // mixed and purely repeating expansions, with periods up to 18.
+        for denominator in [1, 2, 3, 4, 5, 7, 9, 13, 17, 19, 24, 36, 40] {
+            for numerator in [-20, -7, -1, 0, 1, 2, 11, 20] {
+                let (numerator, denominator) = (Number::<40>::from_i64(numerator), Number::<40>::from_i64(denominator));
+                let expansion = expand(numerator, denominator, 100).unwrap();
+                let reparsed: Expansion<40> = expansion.to_string().parse().unwrap();
+                assert_eq!(reparsed, expansion);
+                assert_eq!(reparsed.to_ratio(), Ratio::new(numerator, denominator), "{} is {}", numerator, expansion);
+            }
+        }
+        assert_eq!(expand_i64(-50, 17).unwrap().to_ratio(), None);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |encoded: &str| encoded.parse::<Expansion<8>>().unwrap_err();
+        assert_eq!(error("0.+(-0"), ParseExpansionError::InvalidSyntax);
+        assert_eq!(error("0.()"), ParseExpansionError::InvalidSyntax);
+        assert_eq!(error("0.(+)+"), ParseExpansionError::InvalidSyntax);
+        assert_eq!(error("0.+)"), ParseExpansionError::InvalidSyntax);
+        assert_eq!(error("0.+x"), ParseExpansionError::InvalidNumber(ParseNumberError::InvalidTrit('x')));
+        assert_eq!(error("."), ParseExpansionError::InvalidNumber(ParseNumberError::Empty));
+    }
+
+    #[test]
+    fn agrees_with_fixed_point_conversion() {
+        // Away from ties, rounding to fixed point keeps the leading trits
+        for (numerator, denominator) in [(5, 24), (1, 7), (-13, 11), (40, 9), (-2, 5)] {
+            let expansion = expand_i64(numerator, denominator).unwrap();
+            let fixed: Fixed<4, 6> = Ratio::new(Number::<8>::from_i64(numerator), Number::from_i64(denominator)).unwrap()
+                .to_fixed().unwrap();
+            let trits: Vec<Trit> = expansion.fractional_trits().take(6).collect();
+            assert_eq!(fixed.fraction().trits().as_slice(), trits.as_slice(), "{}/{}", numerator, denominator);
+            assert_eq!(i32::from(fixed.integer()), i32::from(expansion.integer()));
+        }
+    }
+}
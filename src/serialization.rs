@@ -0,0 +1,308 @@
+//! Serde support for trits and ternary numbers, available when the `serde`
+//! feature is enabled.
+//!
+//! By default a `Number<N>` is written as its trit string (e.g. "000+-0--") in
+//! human-readable formats such as JSON, and as packed bytes holding five trits
+//! each in compact binary formats. A `Trit` is written as its character in
+//! human-readable formats and as the integer -1, 0 or 1 otherwise.
+//!
+//! The submodules can be used with `#[serde(with = "...")]` to pick a specific
+//! encoding for a field regardless of the format:
+//!
+//! * `decimal` writes the number as a plain integer
+//! * `trits` always writes the trit string
+//! * `packed` always writes the packed bytes
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Unexpected, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+impl Serialize for Trit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_char(match self {
+                Trit::Neg => '-',
+                Trit::Zero => '0',
+                Trit::Pos => '+'
+            })
+        } else {
+            serializer.serialize_i8(i8::from(*self))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Trit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TritVisitor;
+
+        impl Visitor<'_> for TritVisitor {
+            type Value = Trit;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "one of the characters '-', '0' or '+', or an integer from -1 to 1")
+            }
+
+            fn visit_char<E: de::Error>(self, value: char) -> Result<Trit, E> {
+                Trit::from_char(value).ok_or_else(|| E::invalid_value(Unexpected::Char(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Trit, E> {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => self.visit_char(c),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self))
+                }
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Trit, E> {
+                match value {
+                    -1 => Ok(Trit::Neg),
+                    0 => Ok(Trit::Zero),
+                    1 => Ok(Trit::Pos),
+                    _ => Err(E::invalid_value(Unexpected::Signed(value), &self))
+                }
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Trit, E> {
+                match value {
+                    0 => Ok(Trit::Zero),
+                    1 => Ok(Trit::Pos),
+                    _ => Err(E::invalid_value(Unexpected::Unsigned(value), &self))
+                }
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_char(TritVisitor)
+        } else {
+            deserializer.deserialize_i8(TritVisitor)
+        }
+    }
+}
+
+impl<const N: usize> Serialize for Number<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            trits::serialize(self, serializer)
+        } else {
+            packed::serialize(self, serializer)
+        }
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Number<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            trits::deserialize(deserializer)
+        } else {
+            packed::deserialize(deserializer)
+        }
+    }
+}
+
+/// Encode a ternary number as a plain integer, e.g. `50`. Numbers too large
+/// for an `i128` cannot be written in this form.
+pub mod decimal {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(number: &Number<N>, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = i128::try_from(*number).map_err(serde::ser::Error::custom)?;
+        match i64::try_from(value) {
+            Ok(small_value) => serializer.serialize_i64(small_value),
+            Err(_) => serializer.serialize_i128(value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<Number<N>, D::Error> {
+        struct DecimalVisitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for DecimalVisitor<N> {
+            type Value = Number<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an integer that fits in {} trits", N)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Number<N>, E> {
+                self.visit_i128(value.into())
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Number<N>, E> {
+                self.visit_i128(value.into())
+            }
+
+            fn visit_i128<E: de::Error>(self, value: i128) -> Result<Number<N>, E> {
+                Number::<N>::try_from(value).map_err(|_| E::invalid_value(Unexpected::Other("out of range integer"), &self))
+            }
+
+            fn visit_u128<E: de::Error>(self, value: u128) -> Result<Number<N>, E> {
+                let value = i128::try_from(value).map_err(|_| E::invalid_value(Unexpected::Other("out of range integer"), &self))?;
+                self.visit_i128(value)
+            }
+        }
+
+        deserializer.deserialize_i128(DecimalVisitor::<N>)
+    }
+}
+
+/// Encode a ternary number as a string of all N of its trits, e.g. "000+-0--".
+/// Shorter strings are accepted when reading and are padded with zero trits.
+pub mod trits {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(number: &Number<N>, serializer: S) -> Result<S::Ok, S::Error> {
+        let encoded: String = number.0.iter().map(|trit| trit.to_string()).collect();
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<Number<N>, D::Error> {
+        struct TritsVisitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for TritsVisitor<N> {
+            type Value = Number<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a string of at most {} significant trits using '-', '0' and '+'", N)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Number<N>, E> {
+                value.parse().map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_str(TritsVisitor::<N>)
+    }
+}
+
+/// Encode a ternary number as bytes packing five trits each, least significant
+/// trits first, as produced by `Number::to_bytes()`.
+pub mod packed {
+    use super::*;
+    use crate::packing::{packed_len, PackingError};
+
+    pub fn serialize<S: Serializer, const N: usize>(number: &Number<N>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&number.to_bytes())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<Number<N>, D::Error> {
+        struct PackedVisitor<const N: usize>;
+
+        impl<const N: usize> PackedVisitor<N> {
+            fn unpack<E: de::Error>(&self, bytes: &[u8]) -> Result<Number<N>, E> {
+                Number::<N>::from_bytes(bytes).map_err(|err| match err {
+                    PackingError::WrongLength { actual, .. } => E::invalid_length(actual, self),
+                    PackingError::InvalidByte { value, .. } => E::invalid_value(Unexpected::Unsigned(value.into()), self),
+                    _ => E::invalid_value(Unexpected::Bytes(bytes), self)
+                })
+            }
+        }
+
+        impl<'de, const N: usize> Visitor<'de> for PackedVisitor<N> {
+            type Value = Number<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{} bytes each packing five trits", packed_len(N))
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Number<N>, E> {
+                self.unpack(value)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Number<N>, A::Error> {
+                let mut bytes = Vec::with_capacity(packed_len(N));
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if bytes.len() == packed_len(N) {
+                        return Err(de::Error::invalid_length(bytes.len() + 1, &self));
+                    }
+                    bytes.push(byte);
+                }
+                self.unpack(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(PackedVisitor::<N>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_test::{assert_de_tokens_error, assert_tokens, Compact, Configure, Readable, Token};
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Reading {
+        #[serde(with = "decimal")]
+        decimal: Number<8>,
+        #[serde(with = "trits")]
+        trits: Number<8>,
+        #[serde(with = "packed")]
+        packed: Number<8>,
+        default: Number<8>
+    }
+
+    #[test]
+    fn trit_encodings() {
+        assert_tokens(&Trit::Neg.readable(), &[Token::Char('-')]);
+        assert_tokens(&Trit::Pos.readable(), &[Token::Char('+')]);
+        assert_tokens(&Trit::Neg.compact(), &[Token::I8(-1)]);
+        assert_tokens(&Trit::Zero.compact(), &[Token::I8(0)]);
+
+        assert_de_tokens_error::<Readable<Trit>>(
+            &[Token::Char('x')],
+            "invalid value: character `x`, expected one of the characters '-', '0' or '+', or an integer from -1 to 1"
+        );
+    }
+
+    #[test]
+    fn number_encodings() {
+        let num_50 = Number::<8>::from("+-0--");
+
+        assert_tokens(&num_50.readable(), &[Token::Str("000+-0--")]);
+
+        // Low five trits "+-0--" are 50 + 121 = 171, high three "000" are 0 + 121
+        assert_tokens(&num_50.compact(), &[Token::Bytes(&[171, 121])]);
+    }
+
+    #[test]
+    fn invalid_packed_bytes() {
+        assert_de_tokens_error::<Compact<Number<8>>>(
+            &[Token::Bytes(&[171, 243])],
+            "invalid value: integer `243`, expected 2 bytes each packing five trits"
+        );
+        assert_de_tokens_error::<Compact<Number<8>>>(
+            &[Token::Bytes(&[171])],
+            "invalid length 1, expected 2 bytes each packing five trits"
+        );
+        // 242 sets every trit of the second byte, but only three of them fit
+        assert_de_tokens_error::<Compact<Number<8>>>(
+            &[Token::Bytes(&[171, 242])],
+            "invalid value: byte array, expected 2 bytes each packing five trits"
+        );
+        // A sequence is rejected as soon as it has too many elements
+        assert_de_tokens_error::<Compact<Number<8>>>(
+            &[Token::Seq { len: None }, Token::U8(171), Token::U8(121), Token::U8(0)],
+            "invalid length 3, expected 2 bytes each packing five trits"
+        );
+    }
+
+    #[test]
+    fn json_with_field_encodings() {
+        let num_neg_50 = Number::<8>::from("-+0++");
+        let reading = Reading {decimal: num_neg_50, trits: num_neg_50, packed: num_neg_50, default: num_neg_50};
+
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(json, r#"{"decimal":-50,"trits":"000-+0++","packed":[71,121],"default":"000-+0++"}"#);
+        assert_eq!(serde_json::from_str::<Reading>(&json).unwrap(), reading);
+
+        let short_form = r#"{"decimal":-50,"trits":"-+0++","packed":[71,121],"default":"-+0++"}"#;
+        assert_eq!(serde_json::from_str::<Reading>(short_form).unwrap(), reading);
+
+        assert!(serde_json::from_str::<Reading>(r#"{"decimal":9999,"trits":"","packed":[],"default":""}"#).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
 use crate::sum_result::SumResult;
 
@@ -26,6 +28,14 @@ impl fmt::Debug for Trit {
     }
 }
 
+impl Hash for Trit {
+    /// Hash the trit as its integer value, so that a hasher can recognise
+    /// the -1, 0 and 1 written by `write_i8` as a single trit.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_i8(i8::from(*self));
+    }
+}
+
     impl From<char> for Trit {
     /// Convert the character representing of a trit into a Trit enum
     /// value. This representation accepts '+' as the +1 trit, '-' as the
@@ -37,16 +47,44 @@ impl fmt::Debug for Trit {
     /// **return** The trit represented by the submitted character, or the zero
     /// trit if an invalid character is provided.
     fn from(encoded: char) -> Self {
-        match encoded {
-            '-' => Trit::Neg,
-            '0' => Trit::Zero,
-            '+' => Trit::Pos,
-            _ => panic!("Fail to parse invalid trit {}", encoded)
+        Trit::from_char(encoded)
+            .unwrap_or_else(|| panic!("Fail to parse invalid trit {}", encoded))
+    }
+}
+
+impl From<Trit> for i8 {
+    /// Convert a trit into the integer value that it represents.
+    /// 
+    /// * `trit` The trit to convert
+    /// 
+    /// **return** -1, 0 or 1 for the negative, zero and positive trits
+    fn from(trit: Trit) -> Self {
+        match trit {
+            Trit::Neg => -1,
+            Trit::Zero => 0,
+            Trit::Pos => 1
         }
     }
 }
 
 impl Trit {
+    /// Convert the character representation of a trit into a Trit enum value,
+    /// without panicking on invalid characters. This representation accepts '+'
+    /// as the +1 trit, '-' as the -1 trit and '0' as the zero trit.
+    /// 
+    /// * `encoded` A character representing a trit
+    /// 
+    /// **return** The trit represented by the submitted character, or `None` if
+    /// an invalid character is provided.
+    pub fn from_char(encoded: char) -> Option<Self> {
+        match encoded {
+            '-' => Some(Trit::Neg),
+            '0' => Some(Trit::Zero),
+            '+' => Some(Trit::Pos),
+            _ => None
+        }
+    }
+
     /// Return the opposite of the submitted trit, i.e. '+' is returned for
     /// '-' and vice versa. The negation of '0' is '0'.
     /// 
@@ -104,6 +142,51 @@ impl Trit {
     }
 }
 
+impl Not for Trit {
+    type Output = Self;
+
+    /// Ternary logical NOT, which is simply negation. '+' (true) and '-'
+    /// (false) are swapped and '0' (unknown) is left unchanged.
+    fn not(self) -> Self::Output {
+        self.negate()
+    }
+}
+
+impl BitAnd for Trit {
+    type Output = Self;
+
+    /// Ternary logical AND, treating '+' as true, '-' as false and '0' as
+    /// unknown. This is the minimum of the two trits.
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.min(rhs)
+    }
+}
+
+impl BitOr for Trit {
+    type Output = Self;
+
+    /// Ternary logical OR, treating '+' as true, '-' as false and '0' as
+    /// unknown. This is the maximum of the two trits.
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.max(rhs)
+    }
+}
+
+impl BitXor for Trit {
+    type Output = Self;
+
+    /// Ternary logical XOR, treating '+' as true, '-' as false and '0' as
+    /// unknown. This is `(a OR b) AND NOT (a AND b)`, which in balanced ternary
+    /// works out to be the negated product of the two trits.
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Trit::Zero, _) | (_, Trit::Zero) => Trit::Zero,
+            (l, r) if l == r => Trit::Neg,
+            _ => Trit::Pos
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +228,22 @@ mod tests {
         assert_eq!(Trit::Pos.negate().negate(), Trit::Pos);
         assert_eq!(Trit::Neg.negate().negate(), Trit::Neg);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn logical_operations() {
+        let trits = [Trit::Neg, Trit::Zero, Trit::Pos];
+
+        for l in trits {
+            assert_eq!(!l, l.negate());
+            for r in trits {
+                assert_eq!(l & r, l.min(r));
+                assert_eq!(l | r, l.max(r));
+                assert_eq!(l ^ r, (l | r) & !(l & r));
+            }
+        }
+
+        assert_eq!(Trit::Pos ^ Trit::Neg, Trit::Pos);
+        assert_eq!(Trit::Pos ^ Trit::Pos, Trit::Neg);
+        assert_eq!(Trit::Zero ^ Trit::Pos, Trit::Zero);
+    }
+}
@@ -0,0 +1,512 @@
+//! A small virtual machine that runs programs on balanced ternary words.
+//!
+//! The machine has nine general purpose registers each holding a word of N
+//! trits, a memory of trytes, a program counter and a single sign flag. In
+//! place of the separate zero, negative and carry flags of a binary machine,
+//! the sign flag is a trit that records whether the last result was negative,
+//! zero or positive, and branches test it directly.
+//!
+//! Memory is addressed in trytes of six trits, and each word occupies N / 6
+//! consecutive trytes with the most significant tryte at the lowest address.
+//! Instructions are one word long; see `Instruction` for the encoding.
+//! Arithmetic wraps around on overflow, exactly as the `Number` operators do.
+//!
+//! Programs can be written in a simple assembly language with `assemble()`,
+//! and memory rendered back into that language with `disassemble()`.
+//!
+//! The `setun` module emulates the instruction set of the historical Setun
+//! computer, separately from this machine.
+
+mod assembler;
+mod disassembler;
+mod instruction;
+pub mod setun;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+pub use assembler::{assemble, AssembleError, AssembleErrorKind, MAX_IMAGE_TRYTES};
+pub use disassembler::disassemble;
+pub use instruction::{Instruction, Register, HEADER_TRITS, REGISTERS};
+
+/// The number of trits in a tryte, the unit of memory addressing.
+pub const TRYTE_TRITS: usize = 6;
+
+/// A single addressable unit of memory.
+pub type Tryte = Number<TRYTE_TRITS>;
+
+/// Errors that stop the machine or prevent a program from being encoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VmError {
+    /// A load, store or instruction fetch referred to a word outside memory
+    AddressOutOfRange(i64),
+    /// The word at the given address is not a valid instruction
+    InvalidInstruction(usize),
+    /// An instruction operand does not fit in the operand field of a word
+    OperandOutOfRange(i64),
+    /// The machine did not halt within the allowed number of steps
+    StepLimitExceeded
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VmError::AddressOutOfRange(address) => write!(f, "address {} is out of range", address),
+            VmError::InvalidInstruction(address) => write!(f, "invalid instruction at address {}", address),
+            VmError::OperandOutOfRange(operand) => write!(f, "operand {} does not fit in an instruction", operand),
+            VmError::StepLimitExceeded => write!(f, "step limit exceeded before halting")
+        }
+    }
+}
+
+impl Error for VmError {}
+
+/// A balanced ternary machine with words of N trits. N must be a multiple of
+/// the tryte size and large enough to hold an instruction.
+#[derive(Clone, Debug)]
+pub struct Machine<const N: usize> {
+    registers: [Number<N>; REGISTERS],
+    memory: Vec<Tryte>,
+    pc: usize,
+    sign: Trit,
+    halted: bool
+}
+
+impl<const N: usize> Machine<N> {
+    /// The number of trytes taken up by each word.
+    pub const WORD_TRYTES: usize = N / TRYTE_TRITS;
+
+    /// Builds a machine with zeroed registers and memory, ready to execute from
+    /// address zero.
+    ///
+    /// * `memory_trytes` The size of memory in trytes
+    ///
+    /// **returns** The new machine
+    pub fn new(memory_trytes: usize) -> Self {
+        const {
+            assert!(N.is_multiple_of(TRYTE_TRITS), "Word size must be a whole number of trytes");
+            assert!(N > HEADER_TRITS + 1, "Word size is too small to hold an instruction");
+        };
+
+        Machine {
+            registers: [Number::<N>::ZERO; REGISTERS],
+            memory: vec![Tryte::ZERO; memory_trytes],
+            pc: 0,
+            sign: Trit::Zero,
+            halted: false
+        }
+    }
+
+    /// The current value of a register.
+    pub fn register(&self, register: Register) -> Number<N> {
+        self.registers[register.index()]
+    }
+
+    /// Overwrite the value of a register.
+    pub fn set_register(&mut self, register: Register, value: Number<N>) {
+        self.registers[register.index()] = value;
+    }
+
+    /// The address of the next instruction to be executed.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Set the address of the next instruction to be executed, and clear the
+    /// halted state so that execution can resume.
+    pub fn set_pc(&mut self, address: usize) {
+        self.pc = address;
+        self.halted = false;
+    }
+
+    /// The sign of the last result, or of the last comparison.
+    pub fn sign(&self) -> Trit {
+        self.sign
+    }
+
+    /// Whether the machine has executed a `Halt` instruction.
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// The machine's memory, one tryte per address.
+    pub fn memory(&self) -> &[Tryte] {
+        &self.memory
+    }
+
+    /// Read the word starting at the given tryte address.
+    ///
+    /// * `address` The address of the word's most significant tryte
+    ///
+    /// **returns** The word, or an error if it extends outside memory
+    pub fn read_word(&self, address: usize) -> Result<Number<N>, VmError> {
+        let trytes = self.word_range(address)?;
+        let trits = self.memory[trytes].iter().flat_map(|tryte| tryte.0);
+        Ok(Number::<N>::from_rev_iter(trits.rev()))
+    }
+
+    /// Write a word starting at the given tryte address.
+    ///
+    /// * `address` The address of the word's most significant tryte
+    /// * `word` The word to write
+    ///
+    /// **returns** Success, or an error if the word extends outside memory
+    pub fn write_word(&mut self, address: usize, word: Number<N>) -> Result<(), VmError> {
+        let trytes = self.word_range(address)?;
+        for (tryte, trits) in self.memory[trytes].iter_mut().zip(word.0.chunks(TRYTE_TRITS)) {
+            tryte.0.copy_from_slice(trits);
+        }
+        Ok(())
+    }
+
+    /// Encode a sequence of instructions and write them to consecutive words
+    /// of memory.
+    ///
+    /// * `address` The address to write the first instruction to
+    /// * `program` The instructions to write
+    ///
+    /// **returns** Success, or an error if an instruction cannot be encoded or
+    /// the program does not fit in memory
+    pub fn load_program(&mut self, address: usize, program: &[Instruction]) -> Result<(), VmError> {
+        for (idx, instruction) in program.iter().enumerate() {
+            self.write_word(address + idx * Self::WORD_TRYTES, instruction.encode()?)?;
+        }
+        Ok(())
+    }
+
+    /// Write already encoded words, such as the output of `assemble()`, to
+    /// consecutive words of memory.
+    ///
+    /// * `address` The address to write the first word to
+    /// * `words` The words to write
+    ///
+    /// **returns** Success, or an error if the words do not fit in memory
+    pub fn load_words(&mut self, address: usize, words: &[Number<N>]) -> Result<(), VmError> {
+        for (idx, word) in words.iter().enumerate() {
+            self.write_word(address + idx * Self::WORD_TRYTES, *word)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch, decode and execute a single instruction. Does nothing if the
+    /// machine has halted.
+    ///
+    /// **returns** Success, or an error if the instruction could not be
+    /// executed. The machine is left unchanged on error.
+    pub fn step(&mut self) -> Result<(), VmError> {
+        if self.halted {
+            return Ok(());
+        }
+
+        let word = self.read_word(self.pc)?;
+        let instruction = Instruction::decode(word).ok_or(VmError::InvalidInstruction(self.pc))?;
+        let next_pc = self.pc + Self::WORD_TRYTES;
+
+        match instruction {
+            Instruction::Halt => {
+                self.halted = true;
+                return Ok(());
+            },
+            Instruction::Load { dest, base, offset } => {
+                let address = self.effective_address(base, offset)?;
+                let value = self.read_word(address)?;
+                self.set_result(dest, value);
+            },
+            Instruction::Store { src, base, offset } => {
+                let address = self.effective_address(base, offset)?;
+                self.write_word(address, self.register(src))?;
+            },
+            Instruction::LoadImmediate { dest, value } => {
+                self.set_result(dest, Self::immediate(value));
+            },
+            Instruction::Add { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) + self.register(rhs));
+            },
+            Instruction::Sub { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) - self.register(rhs));
+            },
+            Instruction::Mul { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) * self.register(rhs));
+            },
+            Instruction::AddImmediate { dest, src, value } => {
+                self.set_result(dest, self.register(src) + Self::immediate(value));
+            },
+            Instruction::Shift { dest, src, amount } => {
+                let positions = amount.unsigned_abs().try_into().unwrap_or(usize::MAX);
+                let value = if amount >= 0 {
+                    self.register(src) << positions
+                } else {
+                    self.register(src) >> positions
+                };
+                self.set_result(dest, value);
+            },
+            Instruction::And { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) & self.register(rhs));
+            },
+            Instruction::Or { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) | self.register(rhs));
+            },
+            Instruction::Xor { dest, lhs, rhs } => {
+                self.set_result(dest, self.register(lhs) ^ self.register(rhs));
+            },
+            Instruction::Neg { dest, src } => {
+                self.set_result(dest, -self.register(src));
+            },
+            Instruction::Compare { lhs, rhs } => {
+                self.sign = match self.register(lhs).cmp(&self.register(rhs)) {
+                    std::cmp::Ordering::Less => Trit::Neg,
+                    std::cmp::Ordering::Equal => Trit::Zero,
+                    std::cmp::Ordering::Greater => Trit::Pos
+                };
+            },
+            Instruction::Jump { target } => {
+                self.pc = Self::address(target)?;
+                return Ok(());
+            },
+            Instruction::Branch { sign, target } => {
+                if self.sign == sign {
+                    self.pc = Self::address(target)?;
+                    return Ok(());
+                }
+            },
+            Instruction::Branch3 => {
+                // Skip over zero, one or two of the following instructions
+                let skipped = (i8::from(self.sign) + 1) as usize;
+                self.pc = next_pc + skipped * Self::WORD_TRYTES;
+                return Ok(());
+            }
+        }
+
+        self.pc = next_pc;
+        Ok(())
+    }
+
+    /// Execute instructions until the machine halts.
+    ///
+    /// * `max_steps` The most instructions to execute before giving up
+    ///
+    /// **returns** The number of instructions executed, including the final
+    /// `Halt`, or an error if execution failed or did not halt in time
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, VmError> {
+        for steps in 1..=max_steps {
+            self.step()?;
+            if self.halted {
+                return Ok(steps);
+            }
+        }
+        Err(VmError::StepLimitExceeded)
+    }
+
+    /// Store a result in a register and update the sign flag to match.
+    fn set_result(&mut self, dest: Register, value: Number<N>) {
+        self.registers[dest.index()] = value;
+        self.sign = value.signum();
+    }
+
+    /// Immediates are always small enough to fit in a word, as they were
+    /// decoded from part of one.
+    fn immediate(value: i64) -> Number<N> {
+        Number::<N>::try_from(value).expect("Immediate operand larger than word")
+    }
+
+    /// The address referred to by a base register and offset.
+    fn effective_address(&self, base: Register, offset: i64) -> Result<usize, VmError> {
+        let base = i64::try_from(self.register(base)).map_err(|_| VmError::AddressOutOfRange(i64::MAX))?;
+        Self::address(base.saturating_add(offset))
+    }
+
+    fn address(address: i64) -> Result<usize, VmError> {
+        usize::try_from(address).map_err(|_| VmError::AddressOutOfRange(address))
+    }
+
+    /// The range of trytes occupied by the word at the given address.
+    fn word_range(&self, address: usize) -> Result<std::ops::Range<usize>, VmError> {
+        let end = address.checked_add(Self::WORD_TRYTES)
+            .filter(|end| *end <= self.memory.len())
+            .ok_or(VmError::AddressOutOfRange(address as i64))?;
+        Ok(address..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(index: usize) -> Register {
+        Register::new(index).unwrap()
+    }
+
+    #[test]
+    fn words_span_trytes() {
+        let mut machine = Machine::<12>::new(8);
+        let word = Number::<12>::from("+-0+-0000--+");
+
+        machine.write_word(2, word).unwrap();
+        assert_eq!(machine.read_word(2), Ok(word));
+
+        // Most significant tryte is stored first
+        assert_eq!(machine.memory()[2], Tryte::from("+-0+-0"));
+        assert_eq!(machine.memory()[3], Tryte::from("000--+"));
+
+        assert_eq!(machine.read_word(7), Err(VmError::AddressOutOfRange(7)));
+    }
+
+    #[test]
+    fn arithmetic_program() {
+        // Compute (7 + 5) * 3 - 2 and store it after the program
+        let program = [
+            Instruction::LoadImmediate { dest: r(1), value: 7 },
+            Instruction::LoadImmediate { dest: r(2), value: 5 },
+            Instruction::Add { dest: r(3), lhs: r(1), rhs: r(2) },
+            Instruction::LoadImmediate { dest: r(4), value: 3 },
+            Instruction::Mul { dest: r(3), lhs: r(3), rhs: r(4) },
+            Instruction::AddImmediate { dest: r(3), src: r(3), value: -2 },
+            Instruction::Store { src: r(3), base: r(0), offset: 20 },
+            Instruction::Halt
+        ];
+
+        let mut machine = Machine::<12>::new(24);
+        machine.load_program(0, &program).unwrap();
+
+        assert_eq!(machine.run(100), Ok(8));
+        assert!(machine.is_halted());
+        assert_eq!(machine.register(r(3)), Number::<12>::from_i64(34));
+        assert_eq!(machine.read_word(20), Ok(Number::<12>::from_i64(34)));
+        assert_eq!(machine.sign(), Trit::Pos);
+    }
+
+    #[test]
+    fn shifts_and_logic() {
+        let program = [
+            Instruction::LoadImmediate { dest: r(1), value: 10 },
+            Instruction::Shift { dest: r(2), src: r(1), amount: 2 },
+            Instruction::Shift { dest: r(3), src: r(1), amount: -1 },
+            Instruction::Neg { dest: r(4), src: r(1) },
+            Instruction::And { dest: r(5), lhs: r(1), rhs: r(4) },
+            Instruction::Or { dest: r(6), lhs: r(1), rhs: r(4) },
+            Instruction::Xor { dest: r(7), lhs: r(1), rhs: r(1) },
+            Instruction::Halt
+        ];
+
+        let mut machine = Machine::<12>::new(16);
+        machine.load_program(0, &program).unwrap();
+        machine.run(100).unwrap();
+
+        assert_eq!(machine.register(r(2)), Number::<12>::from_i64(90));
+        assert_eq!(machine.register(r(3)), Number::<12>::from_i64(3)); // 10 / 3 rounds to 3
+        assert_eq!(machine.register(r(4)), Number::<12>::from_i64(-10));
+        // AND and OR of "+0+" with "-0-" take the minimum and maximum trits
+        assert_eq!(machine.register(r(5)), Number::<12>::from_i64(-10));
+        assert_eq!(machine.register(r(6)), Number::<12>::from_i64(10));
+        // XOR of a number with itself gives '-' wherever it had a non-zero trit
+        assert_eq!(machine.register(r(7)), Number::<12>::from("-0-"));
+        assert_eq!(machine.sign(), Trit::Neg);
+    }
+
+    #[test]
+    fn countdown_loop() {
+        // Sum 5 + 4 + 3 + 2 + 1 by counting down to zero
+        let word = Machine::<12>::WORD_TRYTES as i64;
+        let program = [
+            Instruction::LoadImmediate { dest: r(1), value: 5 },
+            Instruction::LoadImmediate { dest: r(2), value: 0 },
+            Instruction::Add { dest: r(2), lhs: r(2), rhs: r(1) },
+            Instruction::AddImmediate { dest: r(1), src: r(1), value: -1 },
+            Instruction::Branch { sign: Trit::Pos, target: 2 * word },
+            Instruction::Halt
+        ];
+
+        let mut machine = Machine::<12>::new(12);
+        machine.load_program(0, &program).unwrap();
+
+        assert_eq!(machine.run(100), Ok(18));
+        assert_eq!(machine.register(r(2)), Number::<12>::from_i64(15));
+        assert_eq!(machine.register(r(1)), Number::<12>::ZERO);
+    }
+
+    #[test]
+    fn three_way_branch() {
+        // Set r3 to -1, 0 or 1 by branching three ways on the comparison of r1 and r2
+        let word = Machine::<12>::WORD_TRYTES as i64;
+        let program = [
+            Instruction::Compare { lhs: r(1), rhs: r(2) },
+            Instruction::Branch3,
+            Instruction::Jump { target: 5 * word },
+            Instruction::Jump { target: 7 * word },
+            Instruction::Jump { target: 9 * word },
+            Instruction::LoadImmediate { dest: r(3), value: -1 },
+            Instruction::Halt,
+            Instruction::LoadImmediate { dest: r(3), value: 0 },
+            Instruction::Halt,
+            Instruction::LoadImmediate { dest: r(3), value: 1 },
+            Instruction::Halt
+        ];
+
+        for (lhs, rhs, expected) in [(3, 8, -1), (8, 8, 0), (8, 3, 1)] {
+            let mut machine = Machine::<12>::new(22);
+            machine.load_program(0, &program).unwrap();
+            machine.set_register(r(1), Number::<12>::from_i64(lhs));
+            machine.set_register(r(2), Number::<12>::from_i64(rhs));
+
+            assert_eq!(machine.run(10), Ok(5));
+            assert_eq!(machine.register(r(3)), Number::<12>::from_i64(expected));
+        }
+    }
+
+    #[test]
+    fn faults() {
+        let mut machine = Machine::<12>::new(8);
+        machine.load_program(0, &[
+            Instruction::Load { dest: r(1), base: r(0), offset: 100 }
+        ]).unwrap();
+        assert_eq!(machine.step(), Err(VmError::AddressOutOfRange(100)));
+        assert_eq!(machine.pc(), 0);
+
+        machine.load_program(0, &[Instruction::Jump { target: 0 }]).unwrap();
+        assert_eq!(machine.run(50), Err(VmError::StepLimitExceeded));
+
+        // Opcode -13 is not assigned
+        machine.write_word(0, Number::<12>::from("---000000000")).unwrap();
+        assert_eq!(machine.step(), Err(VmError::InvalidInstruction(0)));
+
+        // Operands must fit in the five trits below the header of a 12 trit word
+        assert_eq!(
+            machine.load_program(0, &[Instruction::LoadImmediate { dest: r(1), value: 122 }]),
+            Err(VmError::OperandOutOfRange(122))
+        );
+    }
+
+    #[test]
+    fn instruction_encoding_round_trips() {
+        let instructions = [
+            Instruction::Halt,
+            Instruction::Load { dest: r(8), base: r(0), offset: -40 },
+            Instruction::Store { src: r(1), base: r(2), offset: 121 },
+            Instruction::Add { dest: r(3), lhs: r(4), rhs: r(5) },
+            Instruction::Shift { dest: r(6), src: r(7), amount: -3 },
+            Instruction::Compare { lhs: r(0), rhs: r(8) },
+            Instruction::Branch { sign: Trit::Neg, target: 12 },
+            Instruction::Branch3
+        ];
+
+        for instruction in instructions {
+            let word: Number<12> = instruction.encode().unwrap();
+            assert_eq!(Instruction::decode(word), Some(instruction));
+        }
+
+        assert_eq!(Instruction::Halt.encode::<12>(), Ok(Number::<12>::ZERO));
+        // Halt with stray operand trits is not a valid instruction
+        assert_eq!(Instruction::decode(Number::<12>::from("+")), None);
+
+        // Wide words hold any i64 operand, but reject operands beyond that
+        for value in [i64::MIN, i64::MAX] {
+            let instruction = Instruction::LoadImmediate { dest: r(2), value };
+            let word: Number<60> = instruction.encode().unwrap();
+            assert_eq!(Instruction::decode(word), Some(instruction));
+        }
+        assert_eq!(Instruction::decode(Number::<60>::MAX), None);
+    }
+}
@@ -0,0 +1,446 @@
+//! A two pass assembler for the virtual machine's instruction set.
+//!
+//! Each line holds an optional label, an optional instruction or directive and
+//! an optional comment starting with ';'. Operands are separated by commas:
+//!
+//! ```text
+//! ; Sum the numbers from 1 to 5
+//!         li r1, 5
+//!         li r2, 0t0          ; ternary immediate
+//! loop:   add r2, r2, r1
+//!         addi r1, r1, -1
+//!         brp loop
+//!         store r2, [r0 + result]
+//!         halt
+//! result: .word 0
+//! ```
+//!
+//! Immediates are either decimal integers, balanced ternary strings prefixed
+//! with `0t` (e.g. `0t+-0`), or labels, which stand for the tryte address they
+//! were defined at. Memory operands are written as a base register with an
+//! optional offset, e.g. `[r2]`, `[r2 + 6]` or `[r2 - table]`.
+//!
+//! The supported directives are:
+//!
+//! * `.word value` places a word holding the value
+//! * `.zero count` places `count` zero words
+//! * `.org address` continues assembling at the given tryte address, which must
+//!   be word aligned and not before the current address
+//!
+//! Neither directive may move the address beyond `MAX_IMAGE_TRYTES`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::number::Number;
+use crate::trit::Trit;
+use crate::vm::{Instruction, Machine, Register, VmError};
+
+/// The largest address, in trytes, that `.org` and `.zero` may move to. This
+/// stops a single directive from requesting an arbitrarily large image.
+pub const MAX_IMAGE_TRYTES: usize = 1 << 24;
+
+/// The reasons that a line of assembly can fail to assemble.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AssembleErrorKind {
+    /// The mnemonic or directive is not recognised
+    UnknownMnemonic(String),
+    /// The instruction was given the wrong number of operands
+    WrongOperandCount { expected: usize, found: usize },
+    /// An operand that should be a register, from r0 to r8, is not
+    InvalidRegister(String),
+    /// An operand that should be an immediate value or label is not
+    InvalidImmediate(String),
+    /// An operand that should be a memory reference like `[r1 + 3]` is not
+    InvalidMemoryOperand(String),
+    /// A label was used but never defined
+    UnknownLabel(String),
+    /// A label was defined more than once
+    DuplicateLabel(String),
+    /// A label name is not a valid identifier
+    InvalidLabel(String),
+    /// A value does not fit in the space available for it
+    OperandOutOfRange(i64),
+    /// An `.org` address is unaligned, before the current address or beyond
+    /// `MAX_IMAGE_TRYTES`
+    InvalidOrigin(i64)
+}
+
+/// Error returned when assembly fails, identifying the offending line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssembleError {
+    /// The line number, counting from 1
+    pub line: usize,
+    pub kind: AssembleErrorKind
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssembleErrorKind::UnknownMnemonic(mnemonic) => write!(f, "unknown mnemonic '{}'", mnemonic),
+            AssembleErrorKind::WrongOperandCount { expected, found } =>
+                write!(f, "expected {} operands but found {}", expected, found),
+            AssembleErrorKind::InvalidRegister(operand) => write!(f, "invalid register '{}'", operand),
+            AssembleErrorKind::InvalidImmediate(operand) => write!(f, "invalid immediate '{}'", operand),
+            AssembleErrorKind::InvalidMemoryOperand(operand) => write!(f, "invalid memory operand '{}'", operand),
+            AssembleErrorKind::UnknownLabel(label) => write!(f, "unknown label '{}'", label),
+            AssembleErrorKind::DuplicateLabel(label) => write!(f, "label '{}' is already defined", label),
+            AssembleErrorKind::InvalidLabel(label) => write!(f, "invalid label '{}'", label),
+            AssembleErrorKind::OperandOutOfRange(value) => write!(f, "value {} is out of range", value),
+            AssembleErrorKind::InvalidOrigin(address) => write!(f, "invalid origin address {}", address)
+        }
+    }
+}
+
+impl Error for AssembleError {}
+
+/// A line holding an instruction or `.word` directive, which each produce one
+/// word of output.
+struct Statement<'a> {
+    line: usize,
+    address: usize,
+    mnemonic: String,
+    operands: Vec<&'a str>
+}
+
+/// Assemble source text into machine words for a machine with N trit words.
+///
+/// * `source` The assembly source
+///
+/// **returns** The words of the program starting from address zero, with any
+/// gaps left by `.org` or `.zero` filled with zero words, or the first error
+pub fn assemble<const N: usize>(source: &str) -> Result<Vec<Number<N>>, AssembleError> {
+    let word_trytes = Machine::<N>::WORD_TRYTES;
+
+    // First pass finds the address of every statement and label
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (idx, line) in source.lines().enumerate() {
+        let line_number = idx + 1;
+        let error = |kind| AssembleError { line: line_number, kind };
+
+        let mut text = line.split(';').next().unwrap_or_default().trim();
+        while let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_identifier(label) {
+                return Err(error(AssembleErrorKind::InvalidLabel(label.to_string())));
+            }
+            if labels.insert(label, address).is_some() {
+                return Err(error(AssembleErrorKind::DuplicateLabel(label.to_string())));
+            }
+            text = rest.trim();
+        }
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let mnemonic = mnemonic.to_ascii_lowercase();
+        let operands: Vec<&str> = match operands.trim() {
+            "" => Vec::new(),
+            operands => operands.split(',').map(str::trim).collect()
+        };
+
+        match mnemonic.as_str() {
+            ".org" => {
+                expect_operands(&operands, 1).map_err(error)?;
+                let origin = decimal(operands[0]).map_err(error)?;
+                address = usize::try_from(origin).ok()
+                    .filter(|origin| (address..=MAX_IMAGE_TRYTES).contains(origin) && origin.is_multiple_of(word_trytes))
+                    .ok_or(error(AssembleErrorKind::InvalidOrigin(origin)))?;
+            },
+            ".zero" => {
+                expect_operands(&operands, 1).map_err(error)?;
+                let count = decimal(operands[0]).map_err(error)?;
+                address = usize::try_from(count).ok()
+                    .and_then(|count| count.checked_mul(word_trytes))
+                    .and_then(|trytes| trytes.checked_add(address))
+                    .filter(|end| *end <= MAX_IMAGE_TRYTES)
+                    .ok_or(error(AssembleErrorKind::OperandOutOfRange(count)))?;
+            },
+            _ => {
+                statements.push(Statement { line: line_number, address, mnemonic, operands });
+                address += word_trytes;
+            }
+        }
+    }
+
+    // Second pass encodes each statement now that every label is known
+    let mut words = Vec::with_capacity(address / word_trytes);
+    for statement in statements {
+        let parser = OperandParser { labels: &labels, operands: &statement.operands };
+        let error = |kind| AssembleError { line: statement.line, kind };
+
+        words.resize(statement.address / word_trytes, Number::<N>::ZERO);
+        let word = if statement.mnemonic == ".word" {
+            expect_operands(&statement.operands, 1).map_err(error)?;
+            parser.word(0).map_err(error)?
+        } else {
+            parser.instruction(&statement.mnemonic)
+                .map_err(error)?
+                .encode()
+                .map_err(|err| match err {
+                    VmError::OperandOutOfRange(value) => error(AssembleErrorKind::OperandOutOfRange(value)),
+                    _ => unreachable!("Encoding only fails for out of range operands")
+                })?
+        };
+        words.push(word);
+    }
+    words.resize(address / word_trytes, Number::<N>::ZERO);
+
+    Ok(words)
+}
+
+/// Parses the operands of a single statement.
+struct OperandParser<'a> {
+    labels: &'a HashMap<&'a str, usize>,
+    operands: &'a [&'a str]
+}
+
+impl OperandParser<'_> {
+    /// Build the instruction named by the mnemonic from the operands.
+    fn instruction(&self, mnemonic: &str) -> Result<Instruction, AssembleErrorKind> {
+        let operand_count = match mnemonic {
+            "halt" | "br3" => 0,
+            "jmp" | "brn" | "brz" | "brp" => 1,
+            "load" | "store" | "li" | "neg" | "cmp" => 2,
+            "add" | "sub" | "mul" | "and" | "or" | "xor" | "addi" | "shift" => 3,
+            _ => return Err(AssembleErrorKind::UnknownMnemonic(mnemonic.to_string()))
+        };
+        expect_operands(self.operands, operand_count)?;
+
+        Ok(match mnemonic {
+            "halt" => Instruction::Halt,
+            "load" => {
+                let (base, offset) = self.memory(1)?;
+                Instruction::Load { dest: self.register(0)?, base, offset }
+            },
+            "store" => {
+                let (base, offset) = self.memory(1)?;
+                Instruction::Store { src: self.register(0)?, base, offset }
+            },
+            "li" => Instruction::LoadImmediate { dest: self.register(0)?, value: self.immediate(1)? },
+            "add" => Instruction::Add { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "sub" => Instruction::Sub { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "mul" => Instruction::Mul { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "addi" => Instruction::AddImmediate {
+                dest: self.register(0)?, src: self.register(1)?, value: self.immediate(2)?
+            },
+            "shift" => Instruction::Shift {
+                dest: self.register(0)?, src: self.register(1)?, amount: self.immediate(2)?
+            },
+            "and" => Instruction::And { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "or" => Instruction::Or { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "xor" => Instruction::Xor { dest: self.register(0)?, lhs: self.register(1)?, rhs: self.register(2)? },
+            "neg" => Instruction::Neg { dest: self.register(0)?, src: self.register(1)? },
+            "cmp" => Instruction::Compare { lhs: self.register(0)?, rhs: self.register(1)? },
+            "jmp" => Instruction::Jump { target: self.immediate(0)? },
+            "brn" => Instruction::Branch { sign: Trit::Neg, target: self.immediate(0)? },
+            "brz" => Instruction::Branch { sign: Trit::Zero, target: self.immediate(0)? },
+            "brp" => Instruction::Branch { sign: Trit::Pos, target: self.immediate(0)? },
+            "br3" => Instruction::Branch3,
+            _ => unreachable!()
+        })
+    }
+
+    fn register(&self, idx: usize) -> Result<Register, AssembleErrorKind> {
+        register(self.operands[idx])
+    }
+
+    fn immediate(&self, idx: usize) -> Result<i64, AssembleErrorKind> {
+        immediate(self.operands[idx], self.labels)
+    }
+
+    /// Parse the value of a `.word` directive. Ternary values are read straight
+    /// into the word, so that they can use every trit even when N is too long
+    /// for an i64.
+    fn word<const N: usize>(&self, idx: usize) -> Result<Number<N>, AssembleErrorKind> {
+        let operand = self.operands[idx];
+        match operand.strip_prefix("0t") {
+            Some(trits) => trits.parse().map_err(|_| AssembleErrorKind::InvalidImmediate(operand.to_string())),
+            None => {
+                let value = self.immediate(idx)?;
+                Number::<N>::try_from(value).map_err(|_| AssembleErrorKind::OperandOutOfRange(value))
+            }
+        }
+    }
+
+    /// Parse a memory operand of the form `[rB]`, `[rB + imm]` or `[rB - imm]`.
+    fn memory(&self, idx: usize) -> Result<(Register, i64), AssembleErrorKind> {
+        let operand = self.operands[idx];
+        let invalid = || AssembleErrorKind::InvalidMemoryOperand(operand.to_string());
+
+        let inner = operand.strip_prefix('[')
+            .and_then(|inner| inner.strip_suffix(']'))
+            .ok_or_else(invalid)?
+            .trim();
+        let register_end = inner.find(|c: char| c.is_whitespace() || c == '+' || c == '-').unwrap_or(inner.len());
+        let (base, offset) = inner.split_at(register_end);
+        let base = register(base)?;
+
+        let offset = offset.trim();
+        let offset = if offset.is_empty() {
+            0
+        } else if let Some(offset) = offset.strip_prefix('+') {
+            immediate(offset.trim(), self.labels)?
+        } else if let Some(offset) = offset.strip_prefix('-') {
+            let offset = immediate(offset.trim(), self.labels)?;
+            offset.checked_neg().ok_or(AssembleErrorKind::OperandOutOfRange(offset))?
+        } else {
+            return Err(invalid());
+        };
+
+        Ok((base, offset))
+    }
+}
+
+fn expect_operands(operands: &[&str], expected: usize) -> Result<(), AssembleErrorKind> {
+    if operands.len() == expected {
+        Ok(())
+    } else {
+        Err(AssembleErrorKind::WrongOperandCount { expected, found: operands.len() })
+    }
+}
+
+fn register(operand: &str) -> Result<Register, AssembleErrorKind> {
+    operand.strip_prefix(['r', 'R'])
+        .and_then(|index| index.parse().ok())
+        .and_then(Register::new)
+        .ok_or_else(|| AssembleErrorKind::InvalidRegister(operand.to_string()))
+}
+
+/// Parse a decimal or `0t` prefixed balanced ternary value, or look up a label.
+fn immediate(operand: &str, labels: &HashMap<&str, usize>) -> Result<i64, AssembleErrorKind> {
+    if is_identifier(operand) {
+        return labels.get(operand)
+            .map(|address| *address as i64)
+            .ok_or_else(|| AssembleErrorKind::UnknownLabel(operand.to_string()));
+    }
+    decimal(operand)
+}
+
+/// Parse a decimal or `0t` prefixed balanced ternary value.
+fn decimal(operand: &str) -> Result<i64, AssembleErrorKind> {
+    let invalid = || AssembleErrorKind::InvalidImmediate(operand.to_string());
+
+    match operand.strip_prefix("0t") {
+        // 41 trits is enough to hold any i64
+        Some(trits) => trits.parse::<Number<41>>().ok()
+            .and_then(|value| i64::try_from(value).ok())
+            .ok_or_else(invalid),
+        None => operand.parse().map_err(|_| invalid())
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(index: usize) -> Register {
+        Register::new(index).unwrap()
+    }
+
+    #[test]
+    fn assembles_instructions() {
+        let source = "
+            li r1, 7            ; decimal immediate
+            li r2, 0t+--        ; ternary immediate for 5
+            add r3, r1, r2
+            LOAD r4, [r3 - 2]
+            store r4, [r0]
+            halt
+        ";
+
+        let words = assemble::<12>(source).unwrap();
+        let instructions: Vec<_> = words.iter().map(|word| Instruction::decode(*word).unwrap()).collect();
+        assert_eq!(instructions, vec![
+            Instruction::LoadImmediate { dest: r(1), value: 7 },
+            Instruction::LoadImmediate { dest: r(2), value: 5 },
+            Instruction::Add { dest: r(3), lhs: r(1), rhs: r(2) },
+            Instruction::Load { dest: r(4), base: r(3), offset: -2 },
+            Instruction::Store { src: r(4), base: r(0), offset: 0 },
+            Instruction::Halt
+        ]);
+    }
+
+    #[test]
+    fn labels_and_directives() {
+        let source = "
+            start:  jmp main
+            value:  .word 0t++--
+                    .zero 2
+            main:   load r1, [r0 + value]
+                    brz start
+                    .org 16
+            end:    .word end
+        ";
+
+        let words = assemble::<12>(source).unwrap();
+        assert_eq!(words.len(), 9);
+        assert_eq!(Instruction::decode(words[0]), Some(Instruction::Jump { target: 8 }));
+        assert_eq!(words[1], Number::<12>::from("++--"));
+        assert_eq!(words[2], Number::<12>::ZERO);
+        assert_eq!(words[3], Number::<12>::ZERO);
+        assert_eq!(Instruction::decode(words[4]), Some(Instruction::Load { dest: r(1), base: r(0), offset: 2 }));
+        assert_eq!(Instruction::decode(words[5]), Some(Instruction::Branch { sign: Trit::Zero, target: 0 }));
+        assert_eq!(words[6], Number::<12>::ZERO);
+        assert_eq!(words[8], Number::<12>::from_i64(16));
+    }
+
+    #[test]
+    fn runs_assembled_program() {
+        let source = "
+                    li r1, 5
+                    li r2, 0
+            loop:   add r2, r2, r1
+                    addi r1, r1, -1
+                    brp loop
+                    store r2, [r0 + result]
+                    halt
+            result: .word 0
+        ";
+
+        let mut machine = Machine::<12>::new(32);
+        machine.load_words(0, &assemble(source).unwrap()).unwrap();
+        machine.run(100).unwrap();
+
+        assert_eq!(machine.read_word(14), Ok(Number::<12>::from_i64(15)));
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let error = |source| assemble::<12>(source).unwrap_err();
+
+        assert_eq!(error("halt\nfrob r1"), AssembleError {
+            line: 2, kind: AssembleErrorKind::UnknownMnemonic("frob".to_string())
+        });
+        assert_eq!(error("add r1, r2").kind, AssembleErrorKind::WrongOperandCount { expected: 3, found: 2 });
+        assert_eq!(error("neg r9, r1").kind, AssembleErrorKind::InvalidRegister("r9".to_string()));
+        assert_eq!(error("li r1, 0t+1").kind, AssembleErrorKind::InvalidImmediate("0t+1".to_string()));
+        assert_eq!(error("load r1, r2").kind, AssembleErrorKind::InvalidMemoryOperand("r2".to_string()));
+        assert_eq!(error("jmp nowhere").kind, AssembleErrorKind::UnknownLabel("nowhere".to_string()));
+        assert_eq!(error("a: halt\na: halt").kind, AssembleErrorKind::DuplicateLabel("a".to_string()));
+        assert_eq!(error("li r1, 122").kind, AssembleErrorKind::OperandOutOfRange(122));
+        assert_eq!(error("halt\n.org 1").kind, AssembleErrorKind::InvalidOrigin(1));
+        assert_eq!(error("load r1, [r2 - -9223372036854775808]").kind, AssembleErrorKind::OperandOutOfRange(i64::MIN));
+
+        // Directives can't request an arbitrarily large image
+        assert_eq!(error(".zero 9223372036854775807").kind, AssembleErrorKind::OperandOutOfRange(i64::MAX));
+        assert_eq!(error(".zero -1").kind, AssembleErrorKind::OperandOutOfRange(-1));
+        assert_eq!(error(".org 9223372036854775806").kind, AssembleErrorKind::InvalidOrigin(9223372036854775806));
+        assert_eq!(error(".org 16777216\n.zero 1").kind, AssembleErrorKind::OperandOutOfRange(1));
+
+        assert_eq!(error("\n\njmp x").to_string(), "line 3: unknown label 'x'");
+    }
+}
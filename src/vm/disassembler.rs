@@ -0,0 +1,111 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::number::Number;
+use crate::vm::instruction::branch_mnemonic;
+use crate::vm::{Instruction, Machine};
+
+/// Render machine words as assembly source that `assemble()` turns back into
+/// the same words.
+///
+/// Words are assumed to start at address zero. Jump and branch targets that
+/// land on a word within the image are given generated labels such as `L12`,
+/// named after their tryte address. Words that don't decode to an instruction
+/// are written as `.word` directives holding their trits.
+///
+/// * `words` The words to disassemble
+///
+/// **returns** The assembly source, one word per line
+pub fn disassemble<const N: usize>(words: &[Number<N>]) -> String {
+    let word_trytes = Machine::<N>::WORD_TRYTES;
+    let instructions: Vec<_> = words.iter().map(|word| Instruction::decode(*word)).collect();
+
+    let image_end = (words.len() * word_trytes) as i64;
+    let labels: BTreeSet<i64> = instructions.iter()
+        .filter_map(|instruction| match instruction {
+            Some(Instruction::Jump { target }) | Some(Instruction::Branch { target, .. }) => Some(*target),
+            _ => None
+        })
+        .filter(|target| (0..image_end).contains(target) && target % word_trytes as i64 == 0)
+        .collect();
+    let label = |target: i64| labels.contains(&target).then(|| format!("L{}", target));
+
+    let mut source = String::new();
+    for (idx, (word, instruction)) in words.iter().zip(&instructions).enumerate() {
+        let address = (idx * word_trytes) as i64;
+        if let Some(name) = label(address) {
+            writeln!(source, "{}:", name).unwrap();
+        }
+
+        match instruction {
+            Some(Instruction::Jump { target }) if labels.contains(target) =>
+                writeln!(source, "    jmp L{}", target),
+            Some(Instruction::Branch { sign, target }) if labels.contains(target) =>
+                writeln!(source, "    {} L{}", branch_mnemonic(*sign), target),
+            Some(instruction) => writeln!(source, "    {}", instruction),
+            None => {
+                let trits: String = word.0.iter().map(|trit| trit.to_string()).collect();
+                writeln!(source, "    .word 0t{}", trits)
+            }
+        }.unwrap();
+    }
+
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::assemble;
+
+    #[test]
+    fn renders_labels_and_data() {
+        let source = "
+            loop:   addi r1, r1, -1
+                    brp loop
+                    jmp 90
+                    .word 0t-+0+-0++0--+
+        ";
+        let words = assemble::<12>(source).unwrap();
+
+        assert_eq!(disassemble(&words), "\
+L0:
+    addi r1, r1, -1
+    brp L0
+    jmp 90
+    .word 0t-+0+-0++0--+
+");
+    }
+
+    #[test]
+    fn round_trips_through_assembler() {
+        let source = "
+                    li r1, 0t+0-
+                    li r2, -3
+            loop:   mul r3, r1, r2
+                    store r3, [r0 + table]
+                    load r4, [r3 - 1]
+                    shift r4, r4, -2
+                    xor r5, r4, r3
+                    cmp r1, r2
+                    br3
+                    brn loop
+                    brz done
+                    neg r1, r1
+            done:   halt
+            table:  .word 0t+++-
+                    .zero 2
+        ";
+
+        let words = assemble::<12>(source).unwrap();
+        let text = disassemble(&words);
+        assert_eq!(assemble::<12>(&text).unwrap(), words);
+
+        let wide_words = assemble::<18>(source).unwrap();
+        assert_eq!(assemble::<18>(&disassemble(&wide_words)).unwrap(), wide_words);
+
+        // Data words longer than an i64 still reassemble
+        let data = [Number::<60>::MAX, Number::<60>::MIN];
+        assert_eq!(assemble::<60>(&disassemble(&data)).unwrap(), data);
+    }
+}
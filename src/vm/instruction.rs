@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::number::Number;
+use crate::trit::Trit;
+use crate::vm::VmError;
+
+/// The number of registers in the register file. Register fields are two
+/// trits wide, giving nine possible values.
+pub const REGISTERS: usize = 9;
+
+/// The number of trits at the top of an instruction word used for the opcode
+/// and the two register fields. The remaining trits hold the operand.
+pub const HEADER_TRITS: usize = 7;
+
+const OPCODE_TRITS: usize = 3;
+const REGISTER_TRITS: usize = 2;
+
+/// One of the machine's general purpose registers, numbered from 0 to 8. In
+/// an instruction word the register number is stored offset by -4, so that the
+/// two trit field is centred on zero.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Register(u8);
+
+impl Register {
+    /// Build a register reference from its number.
+    ///
+    /// * `index` The register number, from 0 to 8
+    ///
+    /// **returns** The register, or `None` if there is no such register
+    pub fn new(index: usize) -> Option<Self> {
+        (index < REGISTERS).then_some(Register(index as u8))
+    }
+
+    /// The number of this register, from 0 to 8.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "r{}", self.0)
+    }
+}
+
+/// A single machine instruction. Each instruction is encoded into one word,
+/// with the opcode in the top three trits, two register fields `a` and `b` in
+/// the next four trits and a signed operand in the remaining trits. Where an
+/// instruction needs a third register it is stored in the operand field.
+///
+/// Unless noted, instructions that produce a value set the sign flag to the
+/// sign of that value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Instruction {
+    /// Stop the machine. Encoded as an all-zero word, so that running into
+    /// uninitialised memory halts.
+    Halt,
+    /// Load the word at address `base + offset` into `dest`.
+    Load { dest: Register, base: Register, offset: i64 },
+    /// Store `src` to the word at address `base + offset`. Leaves the sign
+    /// flag unchanged.
+    Store { src: Register, base: Register, offset: i64 },
+    /// Set `dest` to the immediate `value`.
+    LoadImmediate { dest: Register, value: i64 },
+    /// Set `dest` to `lhs + rhs`.
+    Add { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to `lhs - rhs`.
+    Sub { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to `lhs * rhs`.
+    Mul { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to `src + value`.
+    AddImmediate { dest: Register, src: Register, value: i64 },
+    /// Set `dest` to `src` shifted left by `amount` trits, or right if
+    /// `amount` is negative.
+    Shift { dest: Register, src: Register, amount: i64 },
+    /// Set `dest` to the tritwise AND (minimum) of `lhs` and `rhs`.
+    And { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to the tritwise OR (maximum) of `lhs` and `rhs`.
+    Or { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to the tritwise XOR of `lhs` and `rhs`.
+    Xor { dest: Register, lhs: Register, rhs: Register },
+    /// Set `dest` to the negation (tritwise NOT) of `src`.
+    Neg { dest: Register, src: Register },
+    /// Set the sign flag to the sign of `lhs - rhs`, without overflow.
+    Compare { lhs: Register, rhs: Register },
+    /// Continue execution at address `target`.
+    Jump { target: i64 },
+    /// Continue execution at address `target` if the sign flag matches `sign`.
+    Branch { sign: Trit, target: i64 },
+    /// Three-way branch on the sign flag, continuing at the first, second or
+    /// third following instruction if the flag is negative, zero or positive.
+    /// These are usually `Jump` instructions forming a jump table.
+    Branch3
+}
+
+/// The opcodes of each instruction, as the value of the three trit field.
+mod opcode {
+    pub const HALT: i64 = 0;
+    pub const LOAD: i64 = 1;
+    pub const STORE: i64 = 2;
+    pub const LOAD_IMMEDIATE: i64 = 3;
+    pub const ADD: i64 = 4;
+    pub const SUB: i64 = 5;
+    pub const MUL: i64 = 6;
+    pub const ADD_IMMEDIATE: i64 = 7;
+    pub const SHIFT: i64 = 8;
+    pub const AND: i64 = 9;
+    pub const OR: i64 = 10;
+    pub const XOR: i64 = 11;
+    pub const NEG: i64 = 12;
+    pub const COMPARE: i64 = 13;
+    pub const JUMP: i64 = -1;
+    pub const BRANCH: i64 = -2;
+    pub const BRANCH3: i64 = -3;
+}
+
+impl Instruction {
+    /// Encode this instruction into a machine word of N trits.
+    ///
+    /// **returns** The encoded word, or an error if an operand does not fit in
+    /// the N - 7 trits available for it
+    pub fn encode<const N: usize>(&self) -> Result<Number<N>, VmError> {
+        const { assert!(N > HEADER_TRITS + 1, "Instruction words need at least 9 trits") };
+
+        // Each field as its balanced value; registers are offset to centre them on zero
+        let r = register_field;
+        let (op, a, b, operand) = match *self {
+            Instruction::Halt => (opcode::HALT, 0, 0, 0),
+            Instruction::Load { dest, base, offset } => (opcode::LOAD, r(dest), r(base), offset),
+            Instruction::Store { src, base, offset } => (opcode::STORE, r(src), r(base), offset),
+            Instruction::LoadImmediate { dest, value } => (opcode::LOAD_IMMEDIATE, r(dest), 0, value),
+            Instruction::Add { dest, lhs, rhs } => (opcode::ADD, r(dest), r(lhs), r(rhs)),
+            Instruction::Sub { dest, lhs, rhs } => (opcode::SUB, r(dest), r(lhs), r(rhs)),
+            Instruction::Mul { dest, lhs, rhs } => (opcode::MUL, r(dest), r(lhs), r(rhs)),
+            Instruction::AddImmediate { dest, src, value } => (opcode::ADD_IMMEDIATE, r(dest), r(src), value),
+            Instruction::Shift { dest, src, amount } => (opcode::SHIFT, r(dest), r(src), amount),
+            Instruction::And { dest, lhs, rhs } => (opcode::AND, r(dest), r(lhs), r(rhs)),
+            Instruction::Or { dest, lhs, rhs } => (opcode::OR, r(dest), r(lhs), r(rhs)),
+            Instruction::Xor { dest, lhs, rhs } => (opcode::XOR, r(dest), r(lhs), r(rhs)),
+            Instruction::Neg { dest, src } => (opcode::NEG, r(dest), r(src), 0),
+            Instruction::Compare { lhs, rhs } => (opcode::COMPARE, 0, r(lhs), r(rhs)),
+            Instruction::Jump { target } => (opcode::JUMP, 0, 0, target),
+            // Branches keep their sign condition in the `a` field in place of a register
+            Instruction::Branch { sign, target } => (opcode::BRANCH, i8::from(sign).into(), 0, target),
+            Instruction::Branch3 => (opcode::BRANCH3, 0, 0, 0)
+        };
+
+        // The operand must fit below the header, i.e. its top trits must be zero
+        let mut word = Number::<N>::try_from(operand)
+            .ok()
+            .filter(|word| word.0[..HEADER_TRITS].iter().all(|trit| *trit == Trit::Zero))
+            .ok_or(VmError::OperandOutOfRange(operand))?;
+
+        let (op_field, register_fields) = word.0[..HEADER_TRITS].split_at_mut(OPCODE_TRITS);
+        let (a_field, b_field) = register_fields.split_at_mut(REGISTER_TRITS);
+        op_field.copy_from_slice(&Number::<OPCODE_TRITS>::from_i64(op).0);
+        a_field.copy_from_slice(&Number::<REGISTER_TRITS>::from_i64(a).0);
+        b_field.copy_from_slice(&Number::<REGISTER_TRITS>::from_i64(b).0);
+
+        Ok(word)
+    }
+
+    /// Decode a machine word of N trits into an instruction.
+    ///
+    /// * `word` The word to decode
+    ///
+    /// **returns** The decoded instruction, or `None` if the opcode or a field
+    /// holding a register or sign is not valid, or the operand doesn't fit in
+    /// an i64
+    pub fn decode<const N: usize>(word: Number<N>) -> Option<Self> {
+        const { assert!(N > HEADER_TRITS + 1, "Instruction words need at least 9 trits") };
+
+        // Fields are accumulated wider than i64, so that the last step to i64::MIN
+        // doesn't overflow, but may still be too long for either
+        let field = |range: std::ops::Range<usize>| -> Option<i64> {
+            let value = word.0[range].iter()
+                .try_fold(0_i128, |acc, trit| acc.checked_mul(3)?.checked_add(i8::from(*trit).into()))?;
+            i64::try_from(value).ok()
+        };
+        let register_at = |value: i64| Register::new(usize::try_from(value.checked_add(4)?).ok()?);
+
+        let op = field(0..OPCODE_TRITS)?;
+        let a = field(OPCODE_TRITS..OPCODE_TRITS + REGISTER_TRITS)?;
+        let b = field(OPCODE_TRITS + REGISTER_TRITS..HEADER_TRITS)?;
+        let operand = field(HEADER_TRITS..N)?;
+
+        let dest = register_at(a);
+        let src = register_at(b);
+        let rhs = register_at(operand);
+
+        let instruction = match op {
+            opcode::HALT => Instruction::Halt,
+            opcode::LOAD => Instruction::Load { dest: dest?, base: src?, offset: operand },
+            opcode::STORE => Instruction::Store { src: dest?, base: src?, offset: operand },
+            opcode::LOAD_IMMEDIATE => Instruction::LoadImmediate { dest: dest?, value: operand },
+            opcode::ADD => Instruction::Add { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::SUB => Instruction::Sub { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::MUL => Instruction::Mul { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::ADD_IMMEDIATE => Instruction::AddImmediate { dest: dest?, src: src?, value: operand },
+            opcode::SHIFT => Instruction::Shift { dest: dest?, src: src?, amount: operand },
+            opcode::AND => Instruction::And { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::OR => Instruction::Or { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::XOR => Instruction::Xor { dest: dest?, lhs: src?, rhs: rhs? },
+            opcode::NEG => Instruction::Neg { dest: dest?, src: src? },
+            opcode::COMPARE => Instruction::Compare { lhs: src?, rhs: rhs? },
+            opcode::JUMP => Instruction::Jump { target: operand },
+            opcode::BRANCH => Instruction::Branch {
+                sign: match a {
+                    -1 => Trit::Neg,
+                    0 => Trit::Zero,
+                    1 => Trit::Pos,
+                    _ => return None
+                },
+                target: operand
+            },
+            opcode::BRANCH3 => Instruction::Branch3,
+            _ => return None
+        };
+
+        // Reject words with stray trits in fields the instruction doesn't use,
+        // so that every valid word has exactly one meaning
+        (instruction.encode::<N>().ok() == Some(word)).then_some(instruction)
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// Render the instruction in the assembly syntax accepted by `assemble()`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instruction::Halt => write!(f, "halt"),
+            Instruction::Load { dest, base, offset } => write!(f, "load {}, {}", dest, MemoryOperand(base, offset)),
+            Instruction::Store { src, base, offset } => write!(f, "store {}, {}", src, MemoryOperand(base, offset)),
+            Instruction::LoadImmediate { dest, value } => write!(f, "li {}, {}", dest, value),
+            Instruction::Add { dest, lhs, rhs } => write!(f, "add {}, {}, {}", dest, lhs, rhs),
+            Instruction::Sub { dest, lhs, rhs } => write!(f, "sub {}, {}, {}", dest, lhs, rhs),
+            Instruction::Mul { dest, lhs, rhs } => write!(f, "mul {}, {}, {}", dest, lhs, rhs),
+            Instruction::AddImmediate { dest, src, value } => write!(f, "addi {}, {}, {}", dest, src, value),
+            Instruction::Shift { dest, src, amount } => write!(f, "shift {}, {}, {}", dest, src, amount),
+            Instruction::And { dest, lhs, rhs } => write!(f, "and {}, {}, {}", dest, lhs, rhs),
+            Instruction::Or { dest, lhs, rhs } => write!(f, "or {}, {}, {}", dest, lhs, rhs),
+            Instruction::Xor { dest, lhs, rhs } => write!(f, "xor {}, {}, {}", dest, lhs, rhs),
+            Instruction::Neg { dest, src } => write!(f, "neg {}, {}", dest, src),
+            Instruction::Compare { lhs, rhs } => write!(f, "cmp {}, {}", lhs, rhs),
+            Instruction::Jump { target } => write!(f, "jmp {}", target),
+            Instruction::Branch { sign, target } => write!(f, "{} {}", branch_mnemonic(sign), target),
+            Instruction::Branch3 => write!(f, "br3")
+        }
+    }
+}
+
+/// The mnemonic of the branch taken when the sign flag matches `sign`.
+pub(crate) fn branch_mnemonic(sign: Trit) -> &'static str {
+    match sign {
+        Trit::Neg => "brn",
+        Trit::Zero => "brz",
+        Trit::Pos => "brp"
+    }
+}
+
+/// A base register and offset, rendered as e.g. `[r2 + 20]`.
+struct MemoryOperand(Register, i64);
+
+impl fmt::Display for MemoryOperand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            0 => write!(f, "[{}]", self.0),
+            offset if offset < 0 => write!(f, "[{} - {}]", self.0, offset.unsigned_abs()),
+            offset => write!(f, "[{} + {}]", self.0, offset)
+        }
+    }
+}
+
+/// Registers are stored offset by -4 so that the nine registers fill the range
+/// of a two trit field. Registers used as the third operand are stored in the
+/// operand field with the same offset.
+fn register_field(register: Register) -> i64 {
+    register.index() as i64 - 4
+}
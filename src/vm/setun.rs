@@ -0,0 +1,778 @@
+//! An emulator for the instruction set of the Setun, the balanced ternary
+//! computer built at Moscow State University in 1958.
+//!
+//! The Setun's memory holds 9 trit short words, and pairs of consecutive short
+//! words make up 18 trit long words with the most significant half at the lower
+//! address. Addresses are five trits, giving the 243 short words from -121 to
+//! 121. The machine has the registers:
+//!
+//! * `S` the 18 trit accumulator
+//! * `R` an 18 trit multiplier register
+//! * `F` a 5 trit index register that can modify instruction addresses
+//! * `C` the 5 trit address of the next instruction
+//! * `ω` a trit holding the sign of the last result in `S` or `F`
+//!
+//! Each instruction is a short word holding a five trit address `A`, a three
+//! trit opcode and a modifier trit, most significant first. A non-zero modifier
+//! adds `F` to or subtracts it from `A` before it is used. `S` and `R` operate
+//! on long words, while `F` and `C` operate on the five trit address field at
+//! the top of a short word, so that `F` and `C` can patch the address of an
+//! instruction in memory.
+//!
+//! Values in `S` are fixed point fractions with the point after the second
+//! trit, so they range up to ±4.5. Multiplication scales its result to match,
+//! rounding to the nearest representable value, and arithmetic that leaves
+//! this range stops the machine with an overflow, as the original did.
+//!
+//! Programs in the original trit notation, one short word of '+', '0' and '-'
+//! per line, are loaded with `Setun::load_listing()`.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::number::Number;
+use crate::trit::Trit;
+
+/// The number of trits in a short word.
+pub const SHORT_TRITS: usize = 9;
+
+/// The number of trits in a long word.
+pub const LONG_TRITS: usize = 18;
+
+/// The number of trits in an address.
+pub const ADDRESS_TRITS: usize = 5;
+
+/// The number of trits after the fixed point in a long word.
+pub const FRACTION_TRITS: usize = 16;
+
+/// A short word, the unit of memory addressing and the size of an instruction.
+pub type ShortWord = Number<SHORT_TRITS>;
+
+/// A long word, the size of the `S` and `R` registers.
+pub type LongWord = Number<LONG_TRITS>;
+
+/// A memory address, and the size of the `F` and `C` registers.
+pub type Address = Number<ADDRESS_TRITS>;
+
+/// The number of short words of memory, one for every address.
+const MEMORY_WORDS: usize = 243;
+
+/// The 24 operations of the Setun. `(A)` is the word at the instruction's
+/// address after modification, and `A` is the address itself.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Opcode {
+    /// `+00` Load S: (A) ⇒ S
+    LoadS,
+    /// `+0+` Add to S: S + (A) ⇒ S
+    AddS,
+    /// `+0-` Subtract from S: S - (A) ⇒ S
+    SubtractS,
+    /// `++0` Multiply: S ⇒ R, then (A) × R ⇒ S
+    Multiply,
+    /// `+++` Multiply and accumulate: S + (A) × R ⇒ S
+    MultiplyAdd,
+    /// `++-` Multiply and add operand: (A) + S × R ⇒ S
+    MultiplyAddOperand,
+    /// `+-0` Tritwise multiply: each trit of S times the matching trit of (A)
+    TritwiseMultiply,
+    /// `+-+` Load R: (A) ⇒ R
+    LoadR,
+    /// `+--` Stop the machine
+    Stop,
+    /// `0+0` Jump to A if ω is zero
+    JumpIfZero,
+    /// `0++` Jump to A if ω is positive
+    JumpIfPositive,
+    /// `0+-` Jump to A if ω is negative
+    JumpIfNegative,
+    /// `00+` Jump to A
+    Jump,
+    /// `000` Do nothing
+    NoOperation,
+    /// `00-` Store the address of the next instruction in the address field of (A)
+    StoreC,
+    /// `0-0` Load F from the address field of (A)
+    LoadF,
+    /// `0-+` Add the address field of (A) to F
+    AddF,
+    /// `0--` Store F in the address field of (A)
+    StoreF,
+    /// `-00` Store S: S ⇒ (A)
+    StoreS,
+    /// `-0+` Shift S left by the address field of (A), or right if negative
+    Shift,
+    /// `-0-` Normalise S and store it in (A), then load S with the shift count
+    Normalise,
+    /// `-+0` Read the next short word of input into (A)
+    Input,
+    /// `-+-` Write the short word (A) to the output
+    Output,
+    /// `--0` Store R: R ⇒ (A)
+    StoreR
+}
+
+impl Opcode {
+    /// Every opcode, in order of their codes from `+00` down.
+    pub const ALL: [Opcode; 24] = [
+        Opcode::LoadS, Opcode::AddS, Opcode::SubtractS, Opcode::Multiply, Opcode::MultiplyAdd,
+        Opcode::MultiplyAddOperand, Opcode::TritwiseMultiply, Opcode::LoadR, Opcode::Stop, Opcode::JumpIfZero,
+        Opcode::JumpIfPositive, Opcode::JumpIfNegative, Opcode::Jump, Opcode::NoOperation, Opcode::StoreC,
+        Opcode::LoadF, Opcode::AddF, Opcode::StoreF, Opcode::StoreS, Opcode::Shift, Opcode::Normalise,
+        Opcode::Input, Opcode::Output, Opcode::StoreR
+    ];
+
+    /// The value of the opcode's three trits.
+    pub fn code(self) -> i32 {
+        match self {
+            Opcode::LoadS => 9,                 // +00
+            Opcode::AddS => 10,                 // +0+
+            Opcode::SubtractS => 8,             // +0-
+            Opcode::Multiply => 12,             // ++0
+            Opcode::MultiplyAdd => 13,          // +++
+            Opcode::MultiplyAddOperand => 11,   // ++-
+            Opcode::TritwiseMultiply => 6,      // +-0
+            Opcode::LoadR => 7,                 // +-+
+            Opcode::Stop => 5,                  // +--
+            Opcode::JumpIfZero => 3,            // 0+0
+            Opcode::JumpIfPositive => 4,        // 0++
+            Opcode::JumpIfNegative => 2,        // 0+-
+            Opcode::Jump => 1,                  // 00+
+            Opcode::NoOperation => 0,           // 000
+            Opcode::StoreC => -1,               // 00-
+            Opcode::LoadF => -3,                // 0-0
+            Opcode::AddF => -2,                 // 0-+
+            Opcode::StoreF => -4,               // 0--
+            Opcode::StoreS => -9,               // -00
+            Opcode::Shift => -8,                // -0+
+            Opcode::Normalise => -10,           // -0-
+            Opcode::Input => -6,                // -+0
+            Opcode::Output => -7,               // -+-
+            Opcode::StoreR => -12               // --0
+        }
+    }
+
+    /// The opcode with the given code, or `None` for the three unused codes
+    /// `-++`, `--+` and `---`.
+    pub fn from_code(code: i32) -> Option<Self> {
+        Opcode::ALL.into_iter().find(|opcode| opcode.code() == code)
+    }
+}
+
+/// A single Setun instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    /// The address field `A`
+    pub address: Address,
+    pub opcode: Opcode,
+    /// Whether to add (`Pos`) or subtract (`Neg`) `F` from the address
+    pub modifier: Trit
+}
+
+impl Instruction {
+    /// An instruction with an unmodified address.
+    pub fn new(opcode: Opcode, address: Address) -> Self {
+        Instruction { address, opcode, modifier: Trit::Zero }
+    }
+
+    /// Encode the instruction as a short word: five address trits, three
+    /// opcode trits and the modifier trit.
+    pub fn encode(&self) -> ShortWord {
+        let opcode = Number::<3>::from_i64(self.opcode.code() as i64);
+
+        let mut trits = [Trit::Zero; SHORT_TRITS];
+        trits[..ADDRESS_TRITS].copy_from_slice(&self.address.0);
+        trits[ADDRESS_TRITS..SHORT_TRITS - 1].copy_from_slice(&opcode.0);
+        trits[SHORT_TRITS - 1] = self.modifier;
+        ShortWord::from_trits(trits)
+    }
+
+    /// Decode a short word into an instruction.
+    ///
+    /// **returns** The instruction, or `None` if the word holds an unused opcode
+    pub fn decode(word: ShortWord) -> Option<Self> {
+        let opcode = Number::<3>::from_trits(word.0[ADDRESS_TRITS..SHORT_TRITS - 1].try_into().unwrap());
+        Some(Instruction {
+            address: address_field(word),
+            opcode: Opcode::from_code(i32::from(opcode))?,
+            modifier: word.0[SHORT_TRITS - 1]
+        })
+    }
+}
+
+/// Errors that stop the emulated machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetunError {
+    /// A long word was accessed at address 121, with no room for its low half
+    AddressOutOfRange(i64),
+    /// The word at the given address holds an unused opcode
+    InvalidInstruction(i64),
+    /// The instruction at the given address left S out of range
+    Overflow(i64),
+    /// The instruction at the given address read input when none was left
+    InputExhausted(i64),
+    /// The machine did not stop within the allowed number of steps
+    StepLimitExceeded
+}
+
+impl fmt::Display for SetunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetunError::AddressOutOfRange(address) => write!(f, "no long word at address {}", address),
+            SetunError::InvalidInstruction(address) => write!(f, "invalid instruction at address {}", address),
+            SetunError::Overflow(address) => write!(f, "overflow in instruction at address {}", address),
+            SetunError::InputExhausted(address) => write!(f, "input exhausted by instruction at address {}", address),
+            SetunError::StepLimitExceeded => write!(f, "step limit exceeded before stopping")
+        }
+    }
+}
+
+impl Error for SetunError {}
+
+/// The reasons that a line of a program listing can fail to load.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoadErrorKind {
+    /// A character other than '+', '0', '-' or whitespace was found
+    InvalidTrit(char),
+    /// A word or address had the wrong number of trits
+    WrongLength { expected: usize, found: usize }
+}
+
+/// Error returned when a program listing cannot be loaded, identifying the
+/// offending line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LoadError {
+    /// The line number, counting from 1
+    pub line: usize,
+    pub kind: LoadErrorKind
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            LoadErrorKind::InvalidTrit(c) => write!(f, "invalid trit '{}'", c),
+            LoadErrorKind::WrongLength { expected, found } =>
+                write!(f, "expected {} trits but found {}", expected, found)
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// The state of an emulated Setun.
+#[derive(Clone, Debug)]
+pub struct Setun {
+    s: LongWord,
+    r: LongWord,
+    f: Address,
+    c: Address,
+    omega: Trit,
+    memory: [ShortWord; MEMORY_WORDS],
+    input: VecDeque<ShortWord>,
+    output: Vec<ShortWord>,
+    stopped: bool
+}
+
+impl Default for Setun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Setun {
+    /// Builds a machine with zeroed registers and memory, ready to execute from
+    /// address zero.
+    pub fn new() -> Self {
+        Setun {
+            s: LongWord::ZERO,
+            r: LongWord::ZERO,
+            f: Address::ZERO,
+            c: Address::ZERO,
+            omega: Trit::Zero,
+            memory: [ShortWord::ZERO; MEMORY_WORDS],
+            input: VecDeque::new(),
+            output: Vec::new(),
+            stopped: false
+        }
+    }
+
+    /// The accumulator.
+    pub fn s(&self) -> LongWord {
+        self.s
+    }
+
+    /// The multiplier register.
+    pub fn r(&self) -> LongWord {
+        self.r
+    }
+
+    /// The index register.
+    pub fn f(&self) -> Address {
+        self.f
+    }
+
+    /// The address of the next instruction.
+    pub fn c(&self) -> Address {
+        self.c
+    }
+
+    /// Set the address of the next instruction, and clear the stopped state so
+    /// that execution can resume.
+    pub fn set_c(&mut self, address: Address) {
+        self.c = address;
+        self.stopped = false;
+    }
+
+    /// The sign of the last result in S or F.
+    pub fn omega(&self) -> Trit {
+        self.omega
+    }
+
+    /// Whether the machine has executed a `Stop` instruction.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Queue a short word to be read by `Input` instructions.
+    pub fn push_input(&mut self, word: ShortWord) {
+        self.input.push_back(word);
+    }
+
+    /// The short words written by `Output` instructions so far.
+    pub fn output(&self) -> &[ShortWord] {
+        &self.output
+    }
+
+    /// Read the short word at an address.
+    pub fn read_short(&self, address: Address) -> ShortWord {
+        self.memory[index(address)]
+    }
+
+    /// Write the short word at an address.
+    pub fn write_short(&mut self, address: Address, word: ShortWord) {
+        self.memory[index(address)] = word;
+    }
+
+    /// Read the long word made up of the short words at an address and the one
+    /// following it.
+    ///
+    /// * `address` The address of the long word's most significant half
+    ///
+    /// **returns** The long word, or an error if the address is the last one
+    pub fn read_long(&self, address: Address) -> Result<LongWord, SetunError> {
+        let high = self.read_short(address);
+        let low = self.read_short(low_half(address)?);
+
+        let mut trits = [Trit::Zero; LONG_TRITS];
+        trits[..SHORT_TRITS].copy_from_slice(&high.0);
+        trits[SHORT_TRITS..].copy_from_slice(&low.0);
+        Ok(LongWord::from_trits(trits))
+    }
+
+    /// Write a long word to the short words at an address and the one
+    /// following it.
+    ///
+    /// * `address` The address of the long word's most significant half
+    /// * `word` The long word to write
+    ///
+    /// **returns** Success, or an error if the address is the last one
+    pub fn write_long(&mut self, address: Address, word: LongWord) -> Result<(), SetunError> {
+        let low_address = low_half(address)?;
+        self.write_short(address, ShortWord::from_trits(word.0[..SHORT_TRITS].try_into().unwrap()));
+        self.write_short(low_address, ShortWord::from_trits(word.0[SHORT_TRITS..].try_into().unwrap()));
+        Ok(())
+    }
+
+    /// Encode a sequence of instructions into consecutive short words,
+    /// wrapping around from address 121 to -121.
+    ///
+    /// * `address` The address of the first instruction
+    /// * `program` The instructions to write
+    pub fn load_program(&mut self, address: Address, program: &[Instruction]) {
+        let mut address = address;
+        for instruction in program {
+            self.write_short(address, instruction.encode());
+            address += Address::ONE;
+        }
+    }
+
+    /// Load a program listing written in trit notation. Each line holds a short
+    /// word of nine trits, which may be grouped with whitespace, and is written
+    /// to the address following the previous line, starting from zero. A line
+    /// may begin with a five trit address and a colon to load its word there
+    /// instead. Anything after a ';' is a comment.
+    ///
+    /// ```text
+    /// ; Add the long word at 22 to the one at 20
+    /// 0+-+-  +00 0
+    /// 0+-++  +0+ 0
+    /// 00000  +-- 0
+    /// 0+-+-: 000 000 000
+    ///        000 000 +--
+    /// ```
+    ///
+    /// * `listing` The program listing
+    ///
+    /// **returns** Success, or the first line that could not be read
+    pub fn load_listing(&mut self, listing: &str) -> Result<(), LoadError> {
+        let mut address = Address::ZERO;
+        for (idx, line) in listing.lines().enumerate() {
+            let error = |kind| LoadError { line: idx + 1, kind };
+
+            let text = line.split(';').next().unwrap_or_default();
+            let word = match text.split_once(':') {
+                Some((word_address, word)) => {
+                    address = parse_trits(word_address).map_err(error)?;
+                    word
+                },
+                None if text.trim().is_empty() => continue,
+                None => text
+            };
+
+            self.write_short(address, parse_trits(word).map_err(error)?);
+            address += Address::ONE;
+        }
+        Ok(())
+    }
+
+    /// Fetch, decode and execute a single instruction. Does nothing if the
+    /// machine has stopped.
+    ///
+    /// **returns** Success, or an error if the instruction could not be
+    /// executed. The machine is left unchanged on error.
+    pub fn step(&mut self) -> Result<(), SetunError> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        let here = i64::from(i32::from(self.c));
+        let instruction = Instruction::decode(self.read_short(self.c)).ok_or(SetunError::InvalidInstruction(here))?;
+        let a = match instruction.modifier {
+            Trit::Zero => instruction.address,
+            Trit::Pos => instruction.address + self.f,
+            Trit::Neg => instruction.address - self.f
+        };
+        let next = self.c + Address::ONE;
+        let overflow = SetunError::Overflow(here);
+
+        match instruction.opcode {
+            Opcode::LoadS => self.set_s(self.read_long(a)?),
+            Opcode::AddS => self.set_s(self.s.checked_add(self.read_long(a)?).ok_or(overflow)?),
+            Opcode::SubtractS => self.set_s(self.s.checked_sub(self.read_long(a)?).ok_or(overflow)?),
+            Opcode::Multiply => {
+                let product = fraction_product(self.read_long(a)?, self.s).ok_or(overflow)?;
+                self.r = self.s;
+                self.set_s(product);
+            },
+            Opcode::MultiplyAdd => {
+                let product = fraction_product(self.read_long(a)?, self.r).ok_or(overflow)?;
+                self.set_s(self.s.checked_add(product).ok_or(overflow)?);
+            },
+            Opcode::MultiplyAddOperand => {
+                let product = fraction_product(self.s, self.r).ok_or(overflow)?;
+                self.set_s(self.read_long(a)?.checked_add(product).ok_or(overflow)?);
+            },
+            // The product of two trits is the negation of their tritwise XOR
+            Opcode::TritwiseMultiply => self.set_s(!(self.s ^ self.read_long(a)?)),
+            Opcode::LoadR => self.r = self.read_long(a)?,
+            Opcode::Stop => self.stopped = true,
+            Opcode::JumpIfZero | Opcode::JumpIfPositive | Opcode::JumpIfNegative | Opcode::Jump => {
+                let taken = match instruction.opcode {
+                    Opcode::JumpIfZero => self.omega == Trit::Zero,
+                    Opcode::JumpIfPositive => self.omega == Trit::Pos,
+                    Opcode::JumpIfNegative => self.omega == Trit::Neg,
+                    _ => true
+                };
+                if taken {
+                    self.c = a;
+                    return Ok(());
+                }
+            },
+            Opcode::NoOperation => (),
+            Opcode::StoreC => self.write_short(a, with_address_field(self.read_short(a), next)),
+            Opcode::LoadF => self.set_f(address_field(self.read_short(a))),
+            Opcode::AddF => self.set_f(self.f + address_field(self.read_short(a))),
+            Opcode::StoreF => self.write_short(a, with_address_field(self.read_short(a), self.f)),
+            Opcode::StoreS => self.write_long(a, self.s)?,
+            Opcode::Shift => {
+                let amount = i32::from(address_field(self.read_short(a)));
+                let positions = amount.unsigned_abs() as usize;
+                self.set_s(if amount >= 0 { self.s << positions } else { self.s >> positions });
+            },
+            Opcode::Normalise => {
+                let (normalised, shift) = normalise(self.s);
+                self.write_long(a, normalised)?;
+                self.set_s(LongWord::from_i64(shift));
+            },
+            Opcode::Input => {
+                let word = self.input.pop_front().ok_or(SetunError::InputExhausted(here))?;
+                self.write_short(a, word);
+            },
+            Opcode::Output => self.output.push(self.read_short(a)),
+            Opcode::StoreR => self.write_long(a, self.r)?
+        }
+
+        self.c = next;
+        Ok(())
+    }
+
+    /// Execute instructions until the machine stops.
+    ///
+    /// * `max_steps` The most instructions to execute before giving up
+    ///
+    /// **returns** The number of instructions executed, including the final
+    /// `Stop`, or an error if execution failed or did not stop in time
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, SetunError> {
+        for steps in 1..=max_steps {
+            self.step()?;
+            if self.stopped {
+                return Ok(steps);
+            }
+        }
+        Err(SetunError::StepLimitExceeded)
+    }
+
+    fn set_s(&mut self, value: LongWord) {
+        self.s = value;
+        self.omega = value.signum();
+    }
+
+    fn set_f(&mut self, value: Address) {
+        self.f = value;
+        self.omega = value.signum();
+    }
+}
+
+/// The position of an address in memory, with -121 first.
+fn index(address: Address) -> usize {
+    (i32::from(address) - i32::from(Address::MIN)) as usize
+}
+
+/// The address of the low half of the long word at the given address.
+fn low_half(address: Address) -> Result<Address, SetunError> {
+    address.checked_add(Address::ONE)
+        .ok_or(SetunError::AddressOutOfRange(i32::from(address).into()))
+}
+
+/// The five most significant trits of a short word, where instructions hold
+/// their address.
+fn address_field(word: ShortWord) -> Address {
+    Address::from_trits(word.0[..ADDRESS_TRITS].try_into().unwrap())
+}
+
+/// A short word with its address field replaced.
+fn with_address_field(word: ShortWord, address: Address) -> ShortWord {
+    let mut trits = word.0;
+    trits[..ADDRESS_TRITS].copy_from_slice(&address.0);
+    ShortWord::from_trits(trits)
+}
+
+/// Multiply two fixed point long words, rounding to the nearest long word.
+///
+/// **returns** The product, or `None` if it is out of range
+fn fraction_product(lhs: LongWord, rhs: LongWord) -> Option<LongWord> {
+    let (low, high) = lhs.widening_mul(rhs);
+
+    let mut trits = [Trit::Zero; 2 * LONG_TRITS];
+    trits[..LONG_TRITS].copy_from_slice(&high.0);
+    trits[LONG_TRITS..].copy_from_slice(&low.0);
+    let scaled = Number::<{ 2 * LONG_TRITS }>::from_trits(trits) >> FRACTION_TRITS;
+
+    LongWord::try_from(i64::try_from(scaled).ok()?).ok()
+}
+
+/// Shift a long word so that its leading trit is zero and the one after it is
+/// not, which places its magnitude between 0.5 and 1.5.
+///
+/// **returns** The normalised value and the number of places it was shifted
+/// left, which is negative if it was shifted right. Zero is left unchanged.
+fn normalise(value: LongWord) -> (LongWord, i64) {
+    match value.0.iter().position(|trit| *trit != Trit::Zero) {
+        None => (value, 0),
+        Some(0) => (value >> 1, -1),
+        Some(leading) => (value << (leading - 1), leading as i64 - 1)
+    }
+}
+
+/// Parse exactly N trits, ignoring whitespace.
+fn parse_trits<const N: usize>(text: &str) -> Result<Number<N>, LoadErrorKind> {
+    let trits = text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| Trit::from_char(c).ok_or(LoadErrorKind::InvalidTrit(c)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let trits: [Trit; N] = trits.try_into()
+        .map_err(|trits: Vec<_>| LoadErrorKind::WrongLength { expected: N, found: trits.len() })?;
+    Ok(Number::<N>::from_trits(trits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bt;
+
+    /// A long word holding the given number of 1/81ths.
+    fn eighty_firsts(value: i64) -> LongWord {
+        LongWord::from_i64(value) << (FRACTION_TRITS - 4)
+    }
+
+    #[test]
+    fn instruction_encoding() {
+        let instruction = Instruction { address: bt!("+0-0-"), opcode: Opcode::AddF, modifier: Trit::Neg };
+        assert_eq!(instruction.encode(), ShortWord::from("+0-0-0-+-"));
+        assert_eq!(Instruction::decode(instruction.encode()), Some(instruction));
+
+        for opcode in Opcode::ALL {
+            let instruction = Instruction::new(opcode, bt!(-121));
+            assert_eq!(Instruction::decode(instruction.encode()), Some(instruction));
+        }
+
+        // -++ is one of the three unused opcodes
+        assert_eq!(Instruction::decode(ShortWord::from("00000-++0")), None);
+    }
+
+    #[test]
+    fn long_words() {
+        let mut machine = Setun::new();
+        let word = LongWord::from("+-0+-0+--0-+00+-+0");
+
+        machine.write_long(bt!(40), word).unwrap();
+        assert_eq!(machine.read_short(bt!(40)), ShortWord::from("+-0+-0+--"));
+        assert_eq!(machine.read_short(bt!(41)), ShortWord::from("0-+00+-+0"));
+        assert_eq!(machine.read_long(bt!(40)), Ok(word));
+
+        assert_eq!(machine.read_long(Address::MAX), Err(SetunError::AddressOutOfRange(121)));
+    }
+
+    #[test]
+    fn fixed_point_arithmetic() {
+        let mut machine = Setun::new();
+        machine.load_program(Address::ZERO, &[
+            Instruction::new(Opcode::LoadS, bt!(50)),
+            Instruction::new(Opcode::Multiply, bt!(52)),
+            Instruction::new(Opcode::AddS, bt!(54)),
+            Instruction::new(Opcode::StoreS, bt!(56)),
+            Instruction::new(Opcode::Stop, Address::ZERO)
+        ]);
+        machine.write_long(bt!(50), eighty_firsts(27)).unwrap();
+        machine.write_long(bt!(52), eighty_firsts(-162)).unwrap();
+        machine.write_long(bt!(54), eighty_firsts(1)).unwrap();
+
+        // 1/3 * -2 + 1/81 = -53/81
+        assert_eq!(machine.run(10), Ok(5));
+        assert_eq!(machine.read_long(bt!(56)), Ok(eighty_firsts(-53)));
+        assert_eq!(machine.r(), eighty_firsts(27));
+        assert_eq!(machine.omega(), Trit::Neg);
+        assert_eq!(machine.c(), bt!(5));
+    }
+
+    #[test]
+    fn overflow_stops_the_machine() {
+        let mut machine = Setun::new();
+        machine.load_program(Address::ZERO, &[
+            Instruction::new(Opcode::LoadS, bt!(50)),
+            Instruction::new(Opcode::AddS, bt!(50))
+        ]);
+        machine.write_long(bt!(50), LongWord::MAX).unwrap();
+
+        assert_eq!(machine.run(10), Err(SetunError::Overflow(1)));
+        assert_eq!(machine.s(), LongWord::MAX);
+    }
+
+    #[test]
+    fn normalise_and_shift() {
+        let mut machine = Setun::new();
+        machine.load_program(Address::ZERO, &[
+            Instruction::new(Opcode::LoadS, bt!(50)),
+            Instruction::new(Opcode::Normalise, bt!(52)),
+            Instruction::new(Opcode::LoadS, bt!(52)),
+            Instruction::new(Opcode::Shift, bt!(54)),
+            Instruction::new(Opcode::Stop, Address::ZERO)
+        ]);
+        machine.write_long(bt!(50), LongWord::from("00000+-0")).unwrap();
+        machine.write_short(bt!(54), ShortWord::from("----+0000"));
+
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.read_long(bt!(52)), Ok(LongWord::from("0+-000000000000000")));
+        assert_eq!(machine.s(), LongWord::from_i64(14));
+
+        // Shift right by the address field ----+, which is -119, clearing S
+        machine.run(10).unwrap();
+        assert_eq!(machine.s(), LongWord::ZERO);
+        assert_eq!(machine.omega(), Trit::Zero);
+
+        let normalised = LongWord::from("+-") << 15;
+        assert_eq!(normalise(normalised << 1), (normalised, -1));
+        assert_eq!(normalise(normalised), (normalised, 0));
+        assert_eq!(normalise(LongWord::ZERO), (LongWord::ZERO, 0));
+    }
+
+    #[test]
+    fn indexed_loop_with_io() {
+        // Read three words into 10 to 12, then write them out in reverse,
+        // using F as the index
+        let mut machine = Setun::new();
+        machine.load_program(bt!(-100), &[
+            Instruction::new(Opcode::LoadF, bt!(-50)),
+            Instruction { address: bt!(13), opcode: Opcode::Input, modifier: Trit::Pos },
+            Instruction::new(Opcode::AddF, bt!(-49)),
+            Instruction::new(Opcode::JumpIfNegative, bt!(-99)),
+            Instruction::new(Opcode::LoadF, bt!(-48)),
+            Instruction { address: bt!(10), opcode: Opcode::Output, modifier: Trit::Pos },
+            Instruction::new(Opcode::AddF, bt!(-47)),
+            Instruction::new(Opcode::JumpIfPositive, bt!(-95)),
+            Instruction::new(Opcode::JumpIfZero, bt!(-95)),
+            Instruction::new(Opcode::StoreC, bt!(-40)),
+            Instruction::new(Opcode::Stop, Address::ZERO)
+        ]);
+        for (address, value) in [(-50, -3), (-49, 1), (-48, 2), (-47, -1)] {
+            machine.write_short(Address::from_i64(address), with_address_field(ShortWord::ZERO, Address::from_i64(value)));
+        }
+
+        let inputs = [ShortWord::from("+0-"), ShortWord::from("-+0"), ShortWord::from("0-+")];
+        inputs.into_iter().for_each(|word| machine.push_input(word));
+        machine.set_c(bt!(-100));
+        machine.run(100).unwrap();
+
+        assert_eq!(machine.output(), [inputs[2], inputs[1], inputs[0]]);
+        assert_eq!(machine.f(), bt!(-1));
+        // StoreC at -91 recorded the address of the following instruction
+        assert_eq!(address_field(machine.read_short(bt!(-40))), bt!(-90));
+
+        machine.set_c(bt!(-99));
+        assert_eq!(machine.step(), Err(SetunError::InputExhausted(-99)));
+    }
+
+    #[test]
+    fn loads_listings() {
+        let listing = "
+            ; Add the long words at 20 and 22, storing the sum at 24
+            0+-+-  +00 0
+            0+-++  +0+ 0
+            0+0-0  -00 0
+            00000  +-- 0
+
+            0+-+-: 000 000 000
+                   000 000 +--   ; 5
+                   000 000 000
+                   000 000 +-+   ; 7
+        ";
+
+        let mut machine = Setun::new();
+        machine.load_listing(listing).unwrap();
+        assert_eq!(Instruction::decode(machine.read_short(bt!(1))), Some(Instruction::new(Opcode::AddS, bt!(22))));
+
+        machine.run(10).unwrap();
+        assert_eq!(machine.read_long(bt!(24)), Ok(LongWord::from_i64(12)));
+
+        assert_eq!(machine.load_listing("0000000+-\n\n+00 0"), Err(LoadError {
+            line: 3, kind: LoadErrorKind::WrongLength { expected: 9, found: 4 }
+        }));
+        assert_eq!(
+            machine.load_listing("0+-+: 000000000").unwrap_err().to_string(),
+            "line 1: expected 5 trits but found 4"
+        );
+        assert_eq!(machine.load_listing("00000 +0x 0").unwrap_err().kind, LoadErrorKind::InvalidTrit('x'));
+    }
+}
@@ -0,0 +1,314 @@
+//! Packed ternary weights for quantised neural networks.
+//!
+//! Ternary-weight networks restrict each weight to -1, 0 or +1, exactly the
+//! values of a `Trit`. A dot product with such weights needs no
+//! multiplication: each activation is added, skipped or subtracted.
+//!
+//! `TernaryVector` packs its trits into two bitmasks, one marking the positive
+//! trits and one the negative, 64 trits to a pair of words. Dot products walk
+//! the set bits of each mask, so zero weights cost nothing, and the dot
+//! product of two ternary vectors reduces to counting the bits of the masks'
+//! intersections.
+//!
+//! `quantise` turns floating-point tensors into such trits, with a scale for
+//! the whole tensor or for each group of values.
+
+mod quantise;
+pub use quantise::{quantise, ErrorStatistics, Granularity, QuantiseError, QuantisedTensor, Scheme};
+
+use std::ops::{AddAssign, SubAssign};
+
+use crate::trit::Trit;
+
+const WORD_BITS: usize = 64;
+
+/// A vector of trits packed into positive and negative bitmasks.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TernaryVector {
+    positive: Vec<u64>,
+    negative: Vec<u64>,
+    len: usize
+}
+
+impl TernaryVector {
+    /// A vector of zero trits.
+    pub fn zeros(len: usize) -> Self {
+        let words = len.div_ceil(WORD_BITS);
+        TernaryVector { positive: vec![0; words], negative: vec![0; words], len }
+    }
+
+    /// Packs a slice of trits.
+    pub fn from_trits(trits: &[Trit]) -> Self {
+        let mut vector = TernaryVector::zeros(trits.len());
+        for (idx, trit) in trits.iter().enumerate() {
+            vector.set(idx, *trit);
+        }
+        vector
+    }
+
+    /// The number of trits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no trits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The trit at an index.
+    ///
+    /// If the index is out of range then the program will exit with an error
+    /// message.
+    pub fn get(&self, idx: usize) -> Trit {
+        assert!(idx < self.len, "Index {} out of range for length {}", idx, self.len);
+        let (word, mask) = (idx / WORD_BITS, 1 << (idx % WORD_BITS));
+        match (self.positive[word] & mask != 0, self.negative[word] & mask != 0) {
+            (true, _) => Trit::Pos,
+            (_, true) => Trit::Neg,
+            _ => Trit::Zero
+        }
+    }
+
+    /// Replace the trit at an index.
+    ///
+    /// If the index is out of range then the program will exit with an error
+    /// message.
+    pub fn set(&mut self, idx: usize, trit: Trit) {
+        assert!(idx < self.len, "Index {} out of range for length {}", idx, self.len);
+        let (word, mask) = (idx / WORD_BITS, 1 << (idx % WORD_BITS));
+        self.positive[word] &= !mask;
+        self.negative[word] &= !mask;
+        match trit {
+            Trit::Pos => self.positive[word] |= mask,
+            Trit::Neg => self.negative[word] |= mask,
+            Trit::Zero => ()
+        }
+    }
+
+    /// The trits, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Trit> + '_ {
+        (0..self.len).map(|idx| self.get(idx))
+    }
+
+    /// The number of trits with the given value.
+    pub fn count(&self, value: Trit) -> usize {
+        let ones = |words: &[u64]| words.iter().map(|word| word.count_ones() as usize).sum::<usize>();
+        match value {
+            Trit::Pos => ones(&self.positive),
+            Trit::Neg => ones(&self.negative),
+            Trit::Zero => self.len - ones(&self.positive) - ones(&self.negative)
+        }
+    }
+
+    /// Add the activations where the trits are positive and subtract those
+    /// where they are negative, visiting only the set bits of each mask. The
+    /// two masks are summed separately so that the additions don't all wait
+    /// on one another.
+    fn accumulate<T: Copy, A: AddAssign + SubAssign + Default + From<T>>(&self, activations: &[T]) -> A {
+        assert_eq!(activations.len(), self.len, "Vector lengths differ");
+        let (mut added, mut subtracted) = (A::default(), A::default());
+        for ((positive, negative), chunk) in self.positive.iter().zip(&self.negative).zip(activations.chunks(WORD_BITS)) {
+            let (mut positive, mut negative) = (*positive, *negative);
+            while positive != 0 && negative != 0 {
+                added += A::from(chunk[positive.trailing_zeros() as usize]);
+                subtracted += A::from(chunk[negative.trailing_zeros() as usize]);
+                positive &= positive - 1;
+                negative &= negative - 1;
+            }
+            while positive != 0 {
+                added += A::from(chunk[positive.trailing_zeros() as usize]);
+                positive &= positive - 1;
+            }
+            while negative != 0 {
+                subtracted += A::from(chunk[negative.trailing_zeros() as usize]);
+                negative &= negative - 1;
+            }
+        }
+        added -= subtracted;
+        added
+    }
+
+    /// The dot product with floating-point activations.
+    ///
+    /// If the lengths differ then the program will exit with an error message.
+    pub fn dot_f32(&self, activations: &[f32]) -> f32 {
+        self.accumulate(activations)
+    }
+
+    /// The dot product with 8-bit activations, accumulated in 32 bits so that
+    /// it can't overflow for fewer than 2^24 trits.
+    ///
+    /// If the lengths differ then the program will exit with an error message.
+    pub fn dot_i8(&self, activations: &[i8]) -> i32 {
+        self.accumulate(activations)
+    }
+
+    /// The dot product with 32-bit activations, accumulated in 64 bits.
+    ///
+    /// If the lengths differ then the program will exit with an error message.
+    pub fn dot_i32(&self, activations: &[i32]) -> i64 {
+        self.accumulate(activations)
+    }
+
+    /// The dot product with another ternary vector. A product of two trits is
+    /// one where both masks agree in sign and minus one where they disagree,
+    /// so this just counts the bits in the four intersections of the masks.
+    ///
+    /// If the lengths differ then the program will exit with an error message.
+    pub fn dot(&self, other: &TernaryVector) -> i64 {
+        assert_eq!(other.len, self.len, "Vector lengths differ");
+        let words = self.positive.iter().zip(&self.negative).zip(other.positive.iter().zip(&other.negative));
+        words.map(|((lhs_positive, lhs_negative), (rhs_positive, rhs_negative))| {
+            let same = (lhs_positive & rhs_positive) | (lhs_negative & rhs_negative);
+            let opposite = (lhs_positive & rhs_negative) | (lhs_negative & rhs_positive);
+            i64::from(same.count_ones()) - i64::from(opposite.count_ones())
+        }).sum()
+    }
+}
+
+impl FromIterator<Trit> for TernaryVector {
+    fn from_iter<I: IntoIterator<Item = Trit>>(iter: I) -> Self {
+        let trits: Vec<Trit> = iter.into_iter().collect();
+        TernaryVector::from_trits(&trits)
+    }
+}
+
+/// A matrix of trits, stored as a packed `TernaryVector` for each row.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TernaryMatrix {
+    rows: Vec<TernaryVector>,
+    columns: usize
+}
+
+impl TernaryMatrix {
+    /// Builds a matrix from its rows.
+    ///
+    /// **returns** The matrix, or `None` if the rows have different lengths
+    pub fn from_rows(rows: Vec<TernaryVector>) -> Option<Self> {
+        let columns = rows.first().map_or(0, TernaryVector::len);
+        rows.iter().all(|row| row.len() == columns).then_some(TernaryMatrix { rows, columns })
+    }
+
+    /// The rows, in order.
+    pub fn rows(&self) -> &[TernaryVector] {
+        &self.rows
+    }
+
+    /// The number of rows.
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The number of columns, which is the length of every row.
+    pub fn column_count(&self) -> usize {
+        self.columns
+    }
+
+    /// Multiply a vector of floating-point activations by this matrix.
+    ///
+    /// If the vector length isn't the number of columns then the program will
+    /// exit with an error message.
+    pub fn mul_f32(&self, activations: &[f32]) -> Vec<f32> {
+        self.rows.iter().map(|row| row.dot_f32(activations)).collect()
+    }
+
+    /// Multiply a vector of 8-bit activations by this matrix.
+    ///
+    /// If the vector length isn't the number of columns then the program will
+    /// exit with an error message.
+    pub fn mul_i8(&self, activations: &[i8]) -> Vec<i32> {
+        self.rows.iter().map(|row| row.dot_i8(activations)).collect()
+    }
+
+    /// Multiply a vector of 32-bit activations by this matrix.
+    ///
+    /// If the vector length isn't the number of columns then the program will
+    /// exit with an error message.
+    pub fn mul_i32(&self, activations: &[i32]) -> Vec<i64> {
+        self.rows.iter().map(|row| row.dot_i32(activations)).collect()
+    }
+
+    /// Multiply a ternary vector by this matrix.
+    ///
+    /// If the vector length isn't the number of columns then the program will
+    /// exit with an error message.
+    pub fn mul_ternary(&self, vector: &TernaryVector) -> Vec<i64> {
+        self.rows.iter().map(|row| row.dot(vector)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::TritRng;
+
+    fn naive_dot(trits: &[Trit], activations: &[f32]) -> f32 {
+        trits.iter().zip(activations).map(|(trit, activation)| f32::from(i8::from(*trit)) * activation).sum()
+    }
+
+    #[test]
+    fn packing() {
+        let trits: Vec<Trit> = TritRng::new(11).take(150).collect();
+        let mut vector = TernaryVector::from_trits(&trits);
+        assert_eq!(vector.len(), 150);
+        assert_eq!(vector.iter().collect::<Vec<_>>(), trits);
+        assert_eq!(vector.count(Trit::Neg) + vector.count(Trit::Zero) + vector.count(Trit::Pos), 150);
+
+        vector.set(149, Trit::Neg);
+        vector.set(149, Trit::Pos);
+        assert_eq!(vector.get(149), Trit::Pos);
+        assert_eq!(trits.iter().copied().collect::<TernaryVector>().get(64), trits[64]);
+        assert!(TernaryVector::zeros(0).is_empty());
+        assert_eq!(TernaryVector::zeros(70).count(Trit::Zero), 70);
+    }
+
+    #[test]
+    fn activation_dot_products() {
+        let trits: Vec<Trit> = TritRng::new(12).take(200).collect();
+        let vector = TernaryVector::from_trits(&trits);
+        let floats: Vec<f32> = (0..200).map(|idx| idx as f32 * 0.25 - 20.0).collect();
+        assert!((vector.dot_f32(&floats) - naive_dot(&trits, &floats)).abs() < 1e-3);
+
+        let bytes: Vec<i8> = (0..200).map(|idx| (idx * 37 % 256) as u8 as i8).collect();
+        let expected: i32 = trits.iter().zip(&bytes).map(|(trit, byte)| i32::from(i8::from(*trit)) * i32::from(*byte)).sum();
+        assert_eq!(vector.dot_i8(&bytes), expected);
+
+        let words = vec![i32::MAX; 200];
+        let difference = vector.count(Trit::Pos) as i64 - vector.count(Trit::Neg) as i64;
+        assert_eq!(vector.dot_i32(&words), difference * i64::from(i32::MAX));
+    }
+
+    #[test]
+    fn ternary_dot_product() {
+        let mut rng = TritRng::new(13);
+        for len in [0, 1, 63, 64, 65, 300] {
+            let lhs: Vec<Trit> = rng.by_ref().take(len).collect();
+            let rhs: Vec<Trit> = rng.by_ref().take(len).collect();
+            let expected: i64 = lhs.iter().zip(&rhs).map(|(lhs, rhs)| i64::from(i8::from(*lhs) * i8::from(*rhs))).sum();
+            assert_eq!(TernaryVector::from_trits(&lhs).dot(&TernaryVector::from_trits(&rhs)), expected);
+        }
+    }
+
+    #[test]
+    fn matrix_vector() {
+        let rows: Vec<TernaryVector> = ["+-0", "000", "++-"].iter()
+            .map(|row| row.chars().map(Trit::from).collect())
+            .collect();
+        let matrix = TernaryMatrix::from_rows(rows).unwrap();
+        assert_eq!((matrix.row_count(), matrix.column_count()), (3, 3));
+        assert_eq!(matrix.mul_f32(&[1.5, 2.0, -1.0]), [-0.5, 0.0, 4.5]);
+        assert_eq!(matrix.mul_i8(&[3, -4, 5]), [7, 0, -6]);
+        assert_eq!(matrix.mul_i32(&[3, -4, 5]), [7, 0, -6]);
+        assert_eq!(matrix.mul_ternary(&"+-+".chars().map(Trit::from).collect()), [2, 0, -1]);
+
+        let ragged = vec![TernaryVector::zeros(2), TernaryVector::zeros(3)];
+        assert_eq!(TernaryMatrix::from_rows(ragged), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vector lengths differ")]
+    fn length_mismatch() {
+        TernaryVector::zeros(3).dot_f32(&[1.0, 2.0]);
+    }
+}
@@ -0,0 +1,345 @@
+//! Quantising floating-point tensors to trits and back.
+//!
+//! Each value is replaced by a trit times a scale shared by its group, which
+//! is either the whole tensor or a fixed number of consecutive values. Two
+//! schemes choose the trits and scales:
+//!
+//! * `Scheme::AbsMean`, as in BitNet b1.58, scales by the mean absolute value
+//!   and rounds each value to the nearest of -1, 0 and 1 times that scale
+//! * `Scheme::Threshold`, as in ternary weight networks, zeroes values below a
+//!   fraction of the mean absolute value and scales the rest by their own
+//!   mean absolute value, which minimises the squared error for those trits
+
+use std::error::Error;
+use std::fmt;
+
+use crate::trit::Trit;
+use crate::weights::TernaryVector;
+
+/// How values are mapped to trits and scales.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheme {
+    /// Divide by the mean absolute value, then round and clamp to a trit
+    AbsMean,
+    /// Zero every value whose magnitude is at most this multiple of the mean
+    /// absolute value, which is usually 0.7
+    Threshold(f32)
+}
+
+/// How many values share each scale.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Granularity {
+    /// One scale for the whole tensor
+    PerTensor,
+    /// A scale for each run of this many values
+    PerGroup(usize)
+}
+
+/// Error returned when values cannot be quantised.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuantiseError {
+    /// Groups must have at least one value
+    ZeroGroupSize,
+    /// The value at this index was infinite or NaN
+    NotFinite(usize)
+}
+
+impl fmt::Display for QuantiseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuantiseError::ZeroGroupSize => write!(f, "group size must not be zero"),
+            QuantiseError::NotFinite(idx) => write!(f, "value at index {} is not finite", idx)
+        }
+    }
+}
+
+impl Error for QuantiseError {}
+
+/// A tensor quantised to packed trits with a scale for each group.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantisedTensor {
+    trits: TernaryVector,
+    scales: Vec<f32>,
+    group_size: usize
+}
+
+/// How closely dequantised values match the originals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorStatistics {
+    mean_squared_error: f64,
+    max_error: f64,
+    signal_power: f64
+}
+
+/// Quantise values to trits.
+///
+/// * `values` The values to quantise
+/// * `scheme` How to choose each group's trits and scale
+/// * `granularity` Whether the whole tensor shares a scale, or groups of values
+///
+/// **returns** The trits and scales, or an error if a group size is zero or a
+/// value isn't finite
+pub fn quantise(values: &[f32], scheme: Scheme, granularity: Granularity) -> Result<QuantisedTensor, QuantiseError> {
+    let group_size = match granularity {
+        Granularity::PerTensor => values.len().max(1),
+        Granularity::PerGroup(0) => return Err(QuantiseError::ZeroGroupSize),
+        Granularity::PerGroup(size) => size
+    };
+    if let Some(idx) = values.iter().position(|value| !value.is_finite()) {
+        return Err(QuantiseError::NotFinite(idx));
+    }
+
+    let mut trits = Vec::with_capacity(values.len());
+    let mut scales = Vec::with_capacity(values.len().div_ceil(group_size));
+    for group in values.chunks(group_size) {
+        let mean = mean_abs(group);
+        let scale = match scheme {
+            Scheme::AbsMean => {
+                trits.extend(group.iter().map(|value| round_to_trit(value / mean)));
+                mean
+            }
+            Scheme::Threshold(ratio) => {
+                let threshold = ratio * mean;
+                let kept: Vec<f32> = group.iter().map(|value| value.abs()).filter(|value| *value > threshold).collect();
+                trits.extend(group.iter().map(|value| match value.abs() > threshold {
+                    true => sign(*value),
+                    false => Trit::Zero
+                }));
+                mean_abs(&kept)
+            }
+        };
+        scales.push(scale);
+    }
+    Ok(QuantisedTensor { trits: TernaryVector::from_trits(&trits), scales, group_size })
+}
+
+/// The mean absolute value, or zero for no values. The sum is taken in `f64`
+/// so that large finite values cannot overflow it.
+fn mean_abs(values: &[f32]) -> f32 {
+    match values.len() {
+        0 => 0.0,
+        len => {
+            let mean = values.iter().map(|value| f64::from(value.abs())).sum::<f64>() / len as f64;
+            mean.min(f64::from(f32::MAX)) as f32
+        }
+    }
+}
+
+/// The nearest of -1, 0 and 1, where NaN from dividing zero by a zero scale
+/// becomes zero.
+fn round_to_trit(value: f32) -> Trit {
+    match value {
+        value if value >= 0.5 => Trit::Pos,
+        value if value <= -0.5 => Trit::Neg,
+        _ => Trit::Zero
+    }
+}
+
+fn sign(value: f32) -> Trit {
+    if value > 0.0 { Trit::Pos } else if value < 0.0 { Trit::Neg } else { Trit::Zero }
+}
+
+impl QuantisedTensor {
+    /// The number of values quantised.
+    pub fn len(&self) -> usize {
+        self.trits.len()
+    }
+
+    /// Whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.trits.is_empty()
+    }
+
+    /// The packed trits, one per value.
+    pub fn trits(&self) -> &TernaryVector {
+        &self.trits
+    }
+
+    /// The scale for each group, in order.
+    pub fn scales(&self) -> &[f32] {
+        &self.scales
+    }
+
+    /// The number of values sharing each scale. The last group may be shorter.
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+
+    /// The fraction of values quantised to zero.
+    pub fn sparsity(&self) -> f64 {
+        match self.len() {
+            0 => 0.0,
+            len => self.trits.count(Trit::Zero) as f64 / len as f64
+        }
+    }
+
+    /// Approximate the original values, each trit times its group's scale.
+    pub fn dequantise(&self) -> Vec<f32> {
+        self.trits.iter().enumerate()
+            .map(|(idx, trit)| f32::from(i8::from(trit)) * self.scales[idx / self.group_size])
+            .collect()
+    }
+
+    /// Compare the dequantised values to the originals.
+    ///
+    /// If the number of values differs then the program will exit with an
+    /// error message.
+    pub fn error_statistics(&self, original: &[f32]) -> ErrorStatistics {
+        assert_eq!(original.len(), self.len(), "Tensor lengths differ");
+        let mut statistics = ErrorStatistics { mean_squared_error: 0.0, max_error: 0.0, signal_power: 0.0 };
+        for (value, approximation) in original.iter().zip(self.dequantise()) {
+            let error = f64::from(value - approximation).abs();
+            statistics.mean_squared_error += error * error;
+            statistics.max_error = statistics.max_error.max(error);
+            statistics.signal_power += f64::from(*value) * f64::from(*value);
+        }
+        if !original.is_empty() {
+            statistics.mean_squared_error /= original.len() as f64;
+            statistics.signal_power /= original.len() as f64;
+        }
+        statistics
+    }
+}
+
+impl ErrorStatistics {
+    /// The mean of the squared differences between original and dequantised
+    /// values.
+    pub fn mean_squared_error(&self) -> f64 {
+        self.mean_squared_error
+    }
+
+    /// The square root of the mean squared error, in the units of the values.
+    pub fn root_mean_squared_error(&self) -> f64 {
+        self.mean_squared_error.sqrt()
+    }
+
+    /// The largest difference between an original and dequantised value.
+    pub fn max_error(&self) -> f64 {
+        self.max_error
+    }
+
+    /// The signal-to-noise ratio in decibels, which is infinite when there is
+    /// no error.
+    pub fn signal_to_noise_db(&self) -> f64 {
+        match self.mean_squared_error {
+            0.0 => f64::INFINITY,
+            noise => 10.0 * (self.signal_power / noise).log10()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::TritRng;
+
+    fn trits(tensor: &QuantisedTensor) -> String {
+        tensor.trits().iter().map(|trit| trit.to_string()).collect()
+    }
+
+    #[test]
+    fn abs_mean() {
+        // The mean magnitude is 0.6875, and 0.625 / 0.6875 rounds up to one
+        let values = [0.75, -0.125, -1.25, 0.625];
+        let tensor = quantise(&values, Scheme::AbsMean, Granularity::PerTensor).unwrap();
+        assert_eq!(trits(&tensor), "+0-+");
+        assert_eq!(tensor.scales(), [0.6875]);
+        assert_eq!(tensor.dequantise(), [0.6875, 0.0, -0.6875, 0.6875]);
+        assert_eq!(tensor.sparsity(), 0.25);
+    }
+
+    #[test]
+    fn threshold() {
+        // Values at most 0.9 × 0.6875 are zeroed, and the rest average 1
+        let values = [0.75, -0.125, -1.25, 0.5];
+        let tensor = quantise(&values, Scheme::Threshold(0.9), Granularity::PerTensor).unwrap();
+        assert_eq!(trits(&tensor), "+0-0");
+        assert_eq!(tensor.scales(), [1.0]);
+
+        // Values that are already a scaled ternary vector are recovered exactly
+        let exact = [2.0, -2.0, 0.0, 2.0];
+        let tensor = quantise(&exact, Scheme::Threshold(0.7), Granularity::PerTensor).unwrap();
+        assert_eq!(tensor.dequantise(), exact);
+        let statistics = tensor.error_statistics(&exact);
+        assert_eq!((statistics.mean_squared_error(), statistics.max_error()), (0.0, 0.0));
+        assert_eq!(statistics.signal_to_noise_db(), f64::INFINITY);
+    }
+
+    #[test]
+    fn per_group() {
+        let values = [10.0, -10.0, 0.1, -0.1, 0.0];
+        let tensor = quantise(&values, Scheme::AbsMean, Granularity::PerGroup(2)).unwrap();
+        assert_eq!(tensor.group_size(), 2);
+        assert_eq!(tensor.scales(), [10.0, 0.1, 0.0]);
+        assert_eq!(trits(&tensor), "+-+-0");
+        assert_eq!(tensor.dequantise(), [10.0, -10.0, 0.1, -0.1, 0.0]);
+
+        // One scale for the whole tensor loses the small values entirely
+        let tensor = quantise(&values, Scheme::AbsMean, Granularity::PerTensor).unwrap();
+        assert_eq!(trits(&tensor), "+-000");
+    }
+
+    #[test]
+    fn error_statistics() {
+        let values = [0.75, -0.125, -1.25, 0.625];
+        let tensor = quantise(&values, Scheme::AbsMean, Granularity::PerTensor).unwrap();
+        let statistics = tensor.error_statistics(&values);
+        // Errors of 0.0625, 0.125, 0.5625 and 0.0625
+        assert_eq!(statistics.mean_squared_error(), 0.0849609375);
+        assert_eq!(statistics.root_mean_squared_error(), 0.0849609375_f64.sqrt());
+        assert_eq!(statistics.max_error(), 0.5625);
+        // Signal power 0.6328125 against noise 0.0849609375
+        assert!((statistics.signal_to_noise_db() - 8.7205).abs() < 1e-4);
+    }
+
+    #[test]
+    fn quantised_dot_products() {
+        // A dot product with the packed trits, times the scale, matches one
+        // with the dequantised values
+        let mut rng = TritRng::new(14);
+        let weights: Vec<f32> = (0..500).map(|_| (rng.next_u64() % 2001) as f32 / 1000.0 - 1.0).collect();
+        let activations: Vec<f32> = (0..500).map(|idx| (idx % 11) as f32 - 5.0).collect();
+        let tensor = quantise(&weights, Scheme::Threshold(0.7), Granularity::PerTensor).unwrap();
+        let packed = tensor.trits().dot_f32(&activations) * tensor.scales()[0];
+        let dequantised: f32 = tensor.dequantise().iter().zip(&activations).map(|(weight, activation)| weight * activation).sum();
+        assert!((packed - dequantised).abs() < 1e-2);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(quantise(&[1.0], Scheme::AbsMean, Granularity::PerGroup(0)), Err(QuantiseError::ZeroGroupSize));
+        assert_eq!(quantise(&[1.0, f32::NAN], Scheme::AbsMean, Granularity::PerTensor), Err(QuantiseError::NotFinite(1)));
+        let empty = quantise(&[], Scheme::AbsMean, Granularity::PerTensor).unwrap();
+        assert!(empty.is_empty() && empty.dequantise().is_empty());
+    }
+
+    #[test]
+    fn all_zero_tensor() {
+        // No signal and no noise counts as no error
+        let zeros = [0.0; 6];
+        for scheme in [Scheme::AbsMean, Scheme::Threshold(0.7)] {
+            let tensor = quantise(&zeros, scheme, Granularity::PerGroup(4)).unwrap();
+            assert_eq!(tensor.scales(), [0.0, 0.0]);
+            assert_eq!(tensor.sparsity(), 1.0);
+            assert_eq!(tensor.dequantise(), zeros);
+            let statistics = tensor.error_statistics(&zeros);
+            assert_eq!(statistics.mean_squared_error(), 0.0);
+            assert_eq!(statistics.signal_to_noise_db(), f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn large_values() {
+        // The sum of the magnitudes overflows f32, but the scales stay finite
+        let values = [f32::MAX, f32::MAX, 1.0];
+        let tensor = quantise(&values, Scheme::AbsMean, Granularity::PerTensor).unwrap();
+        assert_eq!(trits(&tensor), "++0");
+        assert_eq!(tensor.scales(), [(f64::from(f32::MAX) * 2.0 / 3.0) as f32]);
+        assert!(tensor.dequantise().iter().all(|value| value.is_finite()));
+
+        let tensor = quantise(&values, Scheme::Threshold(0.7), Granularity::PerTensor).unwrap();
+        assert_eq!(trits(&tensor), "++0");
+        assert_eq!(tensor.scales(), [f32::MAX]);
+        assert_eq!(tensor.dequantise(), [f32::MAX, f32::MAX, 0.0]);
+    }
+}